@@ -30,6 +30,7 @@ pub struct App {
     pub resources: Resources,
     pub runner: Box<dyn Fn(App)>,
     pub schedule: Schedule,
+    pub sub_apps: Vec<SubApp>,
 }
 
 impl Default for App {
@@ -39,10 +40,28 @@ impl Default for App {
             resources: Default::default(),
             schedule: Default::default(),
             runner: Box::new(run_once),
+            sub_apps: Default::default(),
         }
     }
 }
 
+/// An [App] with its own World, Resources and Schedule, owned and updated by another App. See
+/// [AppBuilder::add_sub_app].
+pub struct SubApp {
+    pub name: &'static str,
+    pub app: App,
+    extract: Box<dyn Fn(&World, &Resources, &mut App)>,
+}
+
+impl SubApp {
+    /// Copies data from the World and Resources of the parent app with the extract function, then updates the
+    /// sub-app. The runner of the sub-app is not used.
+    pub fn update(&mut self, world: &World, resources: &Resources) {
+        (self.extract)(world, resources, &mut self.app);
+        self.app.update();
+    }
+}
+
 fn run_once(mut app: App) {
     app.update();
 }
@@ -52,9 +71,48 @@ impl App {
         AppBuilder::default()
     }
 
+    /// Runs the schedule once, then updates each sub-app in the order they were added
     pub fn update(&mut self) {
         self.schedule
             .initialize_and_run(&mut self.world, &mut self.resources);
+        for sub_app in self.sub_apps.iter_mut() {
+            #[cfg(feature = "trace")]
+            let sub_app_span = info_span!("sub_app", name = sub_app.name);
+            #[cfg(feature = "trace")]
+            let _sub_app_guard = sub_app_span.enter();
+            sub_app.update(&self.world, &self.resources);
+        }
+    }
+
+    /// Adds `app` as a sub-app, see [AppBuilder::add_sub_app]
+    pub fn add_sub_app(
+        &mut self,
+        name: &'static str,
+        app: App,
+        extract: impl Fn(&World, &Resources, &mut App) + 'static,
+    ) {
+        if self.sub_app(name).is_some() {
+            panic!("Sub-app {} already exists.", name);
+        }
+        self.sub_apps.push(SubApp {
+            name,
+            app,
+            extract: Box::new(extract),
+        });
+    }
+
+    pub fn sub_app(&self, name: &str) -> Option<&App> {
+        self.sub_apps
+            .iter()
+            .find(|sub_app| sub_app.name == name)
+            .map(|sub_app| &sub_app.app)
+    }
+
+    pub fn sub_app_mut(&mut self, name: &str) -> Option<&mut App> {
+        self.sub_apps
+            .iter_mut()
+            .find(|sub_app| sub_app.name == name)
+            .map(|sub_app| &mut sub_app.app)
     }
 
     pub fn run(mut self) {
@@ -71,3 +129,59 @@ impl App {
 /// An event that indicates the app should exit. This will fully exit the app process.
 #[derive(Debug, Clone)]
 pub struct AppExit;
+
+#[cfg(test)]
+mod tests {
+    use crate::AppBuilder;
+    use bevy_ecs::{IntoSystem, Query, ResMut};
+    use std::sync::{Arc, Mutex};
+
+    struct Position(u32);
+
+    fn move_system(mut positions: Query<&mut Position>) {
+        for mut position in positions.iter_mut() {
+            position.0 += 1;
+        }
+    }
+
+    fn record_positions(positions: Query<&Position>, mut seen: ResMut<Vec<u32>>) {
+        seen.extend(positions.iter().map(|position| position.0));
+    }
+
+    #[test]
+    fn sub_apps() {
+        let mut app = AppBuilder::default();
+        app.add_system(move_system.system());
+        app.app.world.spawn((Position(0),));
+
+        let order = Arc::new(Mutex::new(Vec::new()));
+        for name in ["first", "second"].iter() {
+            let mut sub_app = AppBuilder::default();
+            sub_app
+                .add_resource(Vec::<u32>::new())
+                .add_system(record_positions.system());
+            let order = order.clone();
+            app.add_sub_app(name, sub_app.app, move |world, _resources, sub_app| {
+                order.lock().unwrap().push(*name);
+                sub_app.world.clear();
+                for position in world.query::<&Position>() {
+                    sub_app.world.spawn((Position(position.0),));
+                }
+            });
+        }
+
+        app.app.update();
+        app.app.update();
+
+        assert_eq!(
+            *order.lock().unwrap(),
+            vec!["first", "second", "first", "second"]
+        );
+        for name in ["first", "second"].iter() {
+            let sub_app = app.app.sub_app(name).unwrap();
+            assert_eq!(*sub_app.resources.get::<Vec<u32>>().unwrap(), vec![1, 2]);
+            assert_eq!(sub_app.world.query::<&Position>().count(), 1);
+        }
+        assert_eq!(app.app.world.query::<&Position>().next().unwrap().0, 2);
+    }
+}
@@ -1,12 +1,13 @@
 use crate::{
     app::{App, AppExit},
-    event::Events,
+    event::{EventRetention, Events},
     plugin::Plugin,
     stage, startup_stage, PluginGroup, PluginGroupBuilder,
 };
 use bevy_ecs::{
-    clear_trackers_system, FromResources, IntoSystem, Resource, Resources, RunOnce, Schedule,
-    Stage, StateStage, System, SystemStage, World,
+    clear_trackers_system, FromResources, IntoSystem, Relation, RequiredComponents, Resource,
+    Resources, RunOnce, Schedule, Stage, StateStage, SystemDescriptor, SystemError, SystemSet,
+    SystemStage, World,
 };
 use bevy_utils::tracing::debug;
 
@@ -24,6 +25,7 @@ impl Default for AppBuilder {
         app_builder
             .add_default_stages()
             .add_event::<AppExit>()
+            .add_event::<SystemError>()
             .add_system_to_stage(stage::LAST, clear_trackers_system.system());
         app_builder
     }
@@ -125,11 +127,15 @@ impl AppBuilder {
         self
     }
 
-    pub fn add_system<S: System<In = (), Out = ()>>(&mut self, system: S) -> &mut Self {
+    pub fn add_system<S: Into<SystemDescriptor>>(&mut self, system: S) -> &mut Self {
         self.add_system_to_stage(stage::UPDATE, system)
     }
 
-    pub fn on_state_enter<T: Clone + Resource, S: System<In = (), Out = ()>>(
+    pub fn add_system_set(&mut self, system_set: SystemSet) -> &mut Self {
+        self.add_system_set_to_stage(stage::UPDATE, system_set)
+    }
+
+    pub fn on_state_enter<T: Clone + Resource, S: Into<SystemDescriptor>>(
         &mut self,
         stage: &str,
         state: T,
@@ -140,7 +146,7 @@ impl AppBuilder {
         })
     }
 
-    pub fn on_state_update<T: Clone + Resource, S: System<In = (), Out = ()>>(
+    pub fn on_state_update<T: Clone + Resource, S: Into<SystemDescriptor>>(
         &mut self,
         stage: &str,
         state: T,
@@ -151,7 +157,7 @@ impl AppBuilder {
         })
     }
 
-    pub fn on_state_exit<T: Clone + Resource, S: System<In = (), Out = ()>>(
+    pub fn on_state_exit<T: Clone + Resource, S: Into<SystemDescriptor>>(
         &mut self,
         stage: &str,
         state: T,
@@ -162,7 +168,29 @@ impl AppBuilder {
         })
     }
 
-    pub fn add_startup_system_to_stage<S: System<In = (), Out = ()>>(
+    pub fn on_state_pause<T: Clone + Resource, S: Into<SystemDescriptor>>(
+        &mut self,
+        stage: &str,
+        state: T,
+        system: S,
+    ) -> &mut Self {
+        self.stage(stage, |stage: &mut StateStage<T>| {
+            stage.on_state_pause(state, system)
+        })
+    }
+
+    pub fn on_state_resume<T: Clone + Resource, S: Into<SystemDescriptor>>(
+        &mut self,
+        stage: &str,
+        state: T,
+        system: S,
+    ) -> &mut Self {
+        self.stage(stage, |stage: &mut StateStage<T>| {
+            stage.on_state_resume(state, system)
+        })
+    }
+
+    pub fn add_startup_system_to_stage<S: Into<SystemDescriptor>>(
         &mut self,
         stage_name: &'static str,
         system: S,
@@ -175,7 +203,7 @@ impl AppBuilder {
         self
     }
 
-    pub fn add_startup_system<S: System<In = (), Out = ()>>(&mut self, system: S) -> &mut Self {
+    pub fn add_startup_system<S: Into<SystemDescriptor>>(&mut self, system: S) -> &mut Self {
         self.add_startup_system_to_stage(startup_stage::STARTUP, system)
     }
 
@@ -197,7 +225,7 @@ impl AppBuilder {
         .add_stage(stage::LAST, SystemStage::parallel())
     }
 
-    pub fn add_system_to_stage<S: System<In = (), Out = ()>>(
+    pub fn add_system_to_stage<S: Into<SystemDescriptor>>(
         &mut self,
         stage_name: &'static str,
         system: S,
@@ -206,14 +234,51 @@ impl AppBuilder {
         self
     }
 
+    pub fn add_system_set_to_stage(
+        &mut self,
+        stage_name: &'static str,
+        system_set: SystemSet,
+    ) -> &mut Self {
+        self.app
+            .schedule
+            .add_system_set_to_stage(stage_name, system_set);
+        self
+    }
+
     pub fn add_event<T>(&mut self) -> &mut Self
     where
         T: Send + Sync + 'static,
     {
-        self.add_resource(Events::<T>::default())
+        self.add_event_with_retention::<T>(EventRetention::default())
+    }
+
+    /// Like [AppBuilder::add_event], with events kept according to `retention`
+    pub fn add_event_with_retention<T>(&mut self, retention: EventRetention) -> &mut Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.add_resource(Events::<T>::with_retention(retention))
             .add_system_to_stage(stage::EVENT, Events::<T>::update_system.system())
     }
 
+    /// Removes links of relation `R` to entities as they are despawned, see [World::register_relation]
+    pub fn add_relation<R>(&mut self) -> &mut Self
+    where
+        R: Relation,
+    {
+        self.app.world.register_relation::<R>();
+        self
+    }
+
+    /// Inserts the components required by `T` on entities it is added to, see [RequiredComponents]
+    pub fn register_required_components<T>(&mut self) -> &mut Self
+    where
+        T: RequiredComponents,
+    {
+        self.app.world.register_required_components::<T>();
+        self
+    }
+
     /// Adds a resource to the current [App] and overwrites any resource previously added of the same type.
     pub fn add_resource<T>(&mut self, resource: T) -> &mut Self
     where
@@ -251,6 +316,19 @@ impl AppBuilder {
         self
     }
 
+    /// Adds `app` as a sub-app with its own World, Resources and Schedule. Every update, once the schedule of this
+    /// app has run, `extract` copies data from this app's World and Resources into the sub-app, which is then
+    /// updated. Sub-apps are updated in the order they were added.
+    pub fn add_sub_app(
+        &mut self,
+        name: &'static str,
+        app: App,
+        extract: impl Fn(&World, &Resources, &mut App) + 'static,
+    ) -> &mut Self {
+        self.app.add_sub_app(name, app, extract);
+        self
+    }
+
     pub fn set_runner(&mut self, run_fn: impl Fn(App) + 'static) -> &mut Self {
         self.app.runner = Box::new(run_fn);
         self
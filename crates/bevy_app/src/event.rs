@@ -1,6 +1,14 @@
-use bevy_ecs::ResMut;
+use bevy_ecs::{In, ResMut, SystemError};
 use bevy_utils::tracing::trace;
-use std::{fmt, marker::PhantomData};
+use std::{
+    collections::VecDeque,
+    fmt,
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, Weak,
+    },
+};
 
 /// An `EventId` uniquely identifies an event.
 ///
@@ -42,16 +50,32 @@ struct EventInstance<T> {
     pub event: T,
 }
 
-#[derive(Debug)]
-enum State {
-    A,
-    B,
+/// How long [Events] keep the events they receive, see [Events::with_retention]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventRetention {
+    /// Events are dropped during the second [Events::update] call after they were sent.
+    DoubleBuffer,
+    /// Only the last `n` events are kept, regardless of [Events::update] calls.
+    Count(usize),
+    /// Events are kept until every registered [EventReader] has read them, and at least as long as with
+    /// [EventRetention::DoubleBuffer]. A reader is registered when it is created with [Events::get_reader] or
+    /// [Events::get_reader_current], or when it reads from the [Events] for the first time, and stops being
+    /// registered when it is dropped.
+    ///
+    /// The events are never dropped if a registered reader stops reading, make sure to drop unused readers.
+    UntilConsumed,
+}
+
+impl Default for EventRetention {
+    fn default() -> Self {
+        EventRetention::DoubleBuffer
+    }
 }
 
 /// An event collection that represents the events that occurred within the last two [Events::update] calls. Events can be cheaply read using
 /// an [EventReader]. This collection is meant to be paired with a system that calls [Events::update] exactly once per update/frame. [Events::update_system]
 /// is a system that does this. [EventReader]s are expected to read events from this collection at least once per update/frame. If events are not handled
-/// within one frame/update, they will be dropped.
+/// within one frame/update, they will be dropped, unless another [EventRetention] is used.
 ///
 /// # Example
 /// ```
@@ -82,33 +106,38 @@ enum State {
 ///
 /// # Details
 ///
-/// [Events] is implemented using a double buffer. Each call to [Events::update] swaps buffers and clears out the oldest buffer.
+/// By default, [Events] behaves like a double buffer. Each call to [Events::update] drops the events sent before the previous call.
 /// [EventReader]s that read at least once per update will never drop events. [EventReader]s that read once within two updates might
 /// still receive some events. [EventReader]s that read after two updates are guaranteed to drop all events that occurred before those updates.
+/// Readers that run less often, for example in a stage with a fixed timestep, can use [EventRetention::UntilConsumed] or
+/// [EventRetention::Count] instead, and check [EventReader::missed_events].
 ///
-/// The buffers in [Events] will grow indefinitely if [Events::update] is never called.
+/// The events in [Events] will be kept indefinitely if [Events::update] is never called.
 ///
 /// An alternative call pattern would be to call [Events::update] manually across frames to control when events are cleared. However
 /// this complicates consumption
 #[derive(Debug)]
 pub struct Events<T> {
-    events_a: Vec<EventInstance<T>>,
-    events_b: Vec<EventInstance<T>>,
-    a_start_event_count: usize,
-    b_start_event_count: usize,
+    events: VecDeque<EventInstance<T>>,
+    /// The id of the first event in `events`
+    start_event_count: usize,
+    /// The value of `event_count` during the last [Events::update]
+    update_event_count: usize,
     event_count: usize,
-    state: State,
+    retention: EventRetention,
+    /// The cursors of the registered readers
+    readers: Mutex<Vec<Weak<AtomicUsize>>>,
 }
 
 impl<T> Default for Events<T> {
     fn default() -> Self {
         Events {
-            a_start_event_count: 0,
-            b_start_event_count: 0,
+            events: VecDeque::new(),
+            start_event_count: 0,
+            update_event_count: 0,
             event_count: 0,
-            events_a: Vec::new(),
-            events_b: Vec::new(),
-            state: State::A,
+            retention: EventRetention::default(),
+            readers: Mutex::new(Vec::new()),
         }
     }
 }
@@ -124,6 +153,9 @@ fn map_instance_event<T>(event_instance: &EventInstance<T>) -> &T {
 /// Reads events of type `T` in order and tracks which events have already been read.
 pub struct EventReader<T> {
     last_event_count: usize,
+    missed_events: usize,
+    /// Shared with the [Events] this reader is registered in
+    cursor: Option<Arc<AtomicUsize>>,
     _marker: PhantomData<T>,
 }
 
@@ -131,6 +163,8 @@ impl<T> Default for EventReader<T> {
     fn default() -> Self {
         Self {
             last_event_count: 0,
+            missed_events: 0,
+            cursor: None,
             _marker: PhantomData::default(),
         }
     }
@@ -159,49 +193,29 @@ impl<T> EventReader<T> {
         &mut self,
         events: &'a Events<T>,
     ) -> impl DoubleEndedIterator<Item = (&'a T, EventId<T>)> {
-        // if the reader has seen some of the events in a buffer, find the proper index offset.
-        // otherwise read all events in the buffer
-        let a_index = if self.last_event_count > events.a_start_event_count {
-            self.last_event_count - events.a_start_event_count
-        } else {
-            0
-        };
-        let b_index = if self.last_event_count > events.b_start_event_count {
-            self.last_event_count - events.b_start_event_count
-        } else {
-            0
-        };
-        self.last_event_count = events.event_count;
-        match events.state {
-            State::A => events
-                .events_b
-                .get(b_index..)
-                .unwrap_or_else(|| &[])
-                .iter()
-                .map(map_instance_event_with_id)
-                .chain(
-                    events
-                        .events_a
-                        .get(a_index..)
-                        .unwrap_or_else(|| &[])
-                        .iter()
-                        .map(map_instance_event_with_id),
-                ),
-            State::B => events
-                .events_a
-                .get(a_index..)
-                .unwrap_or_else(|| &[])
-                .iter()
-                .map(map_instance_event_with_id)
-                .chain(
-                    events
-                        .events_b
-                        .get(b_index..)
-                        .unwrap_or_else(|| &[])
-                        .iter()
-                        .map(map_instance_event_with_id),
-                ),
+        match &self.cursor {
+            Some(cursor) => {
+                // events dropped before this reader read them
+                if self.last_event_count < events.start_event_count {
+                    self.missed_events += events.start_event_count - self.last_event_count;
+                }
+                cursor.store(events.event_count, Ordering::Relaxed);
+            }
+            None => self.cursor = Some(events.register_reader(events.event_count)),
         }
+        let index = self
+            .last_event_count
+            .saturating_sub(events.start_event_count)
+            .min(events.events.len());
+        self.last_event_count = events.event_count;
+        events.events.range(index..).map(map_instance_event_with_id)
+    }
+
+    /// The number of events this reader did not read because they were dropped before it read from the [Events]
+    /// again. This is updated each time the reader reads. Events sent before the first read of a reader created
+    /// with [EventReader::default] are not counted.
+    pub fn missed_events(&self) -> usize {
+        self.missed_events
     }
 
     /// Retrieves the latest event that this EventReader hasn't seen yet. This updates the EventReader's
@@ -259,6 +273,36 @@ impl<T> EventReader<T> {
     }
 }
 
+impl<T> Events<T> {
+    /// Creates an empty event collection that keeps events according to `retention`
+    pub fn with_retention(retention: EventRetention) -> Self {
+        Events {
+            retention,
+            ..Default::default()
+        }
+    }
+
+    pub fn retention(&self) -> EventRetention {
+        self.retention
+    }
+
+    /// Registers a reader that has read every event before `event_count`
+    fn register_reader(&self, event_count: usize) -> Arc<AtomicUsize> {
+        let cursor = Arc::new(AtomicUsize::new(event_count));
+        let mut readers = self.readers.lock().unwrap();
+        readers.retain(|reader| reader.strong_count() > 0);
+        readers.push(Arc::downgrade(&cursor));
+        cursor
+    }
+
+    /// Drops the events sent before `event_count`
+    fn drop_events_before(&mut self, event_count: usize) {
+        while self.start_event_count < event_count && self.events.pop_front().is_some() {
+            self.start_event_count += 1;
+        }
+    }
+}
+
 impl<T: bevy_ecs::Resource> Events<T> {
     /// "Sends" an `event` by writing it to the current event buffer. [EventReader]s can then read the event.
     pub fn send(&mut self, event: T) {
@@ -269,19 +313,20 @@ impl<T: bevy_ecs::Resource> Events<T> {
         trace!("Events::send() -> {}", event_id);
 
         let event_instance = EventInstance { event, event_id };
+        self.events.push_back(event_instance);
+        self.event_count += 1;
 
-        match self.state {
-            State::A => self.events_a.push(event_instance),
-            State::B => self.events_b.push(event_instance),
+        if let EventRetention::Count(count) = self.retention {
+            self.drop_events_before(self.event_count.saturating_sub(count));
         }
-
-        self.event_count += 1;
     }
 
     /// Gets a new [EventReader]. This will include all events already in the event buffers.
     pub fn get_reader(&self) -> EventReader<T> {
         EventReader {
-            last_event_count: 0,
+            last_event_count: self.start_event_count,
+            missed_events: 0,
+            cursor: Some(self.register_reader(self.start_event_count)),
             _marker: PhantomData,
         }
     }
@@ -290,24 +335,32 @@ impl<T: bevy_ecs::Resource> Events<T> {
     pub fn get_reader_current(&self) -> EventReader<T> {
         EventReader {
             last_event_count: self.event_count,
+            missed_events: 0,
+            cursor: Some(self.register_reader(self.event_count)),
             _marker: PhantomData,
         }
     }
 
-    /// Swaps the event buffers and clears the oldest event buffer. In general, this should be called once per frame/update.
+    /// Drops the events that should not be kept anymore according to the [EventRetention]. With the default retention,
+    /// this drops the events sent before the previous call. In general, this should be called once per frame/update.
     pub fn update(&mut self) {
-        match self.state {
-            State::A => {
-                self.events_b = Vec::new();
-                self.state = State::B;
-                self.b_start_event_count = self.event_count;
-            }
-            State::B => {
-                self.events_a = Vec::new();
-                self.state = State::A;
-                self.a_start_event_count = self.event_count;
+        match self.retention {
+            EventRetention::DoubleBuffer => self.drop_events_before(self.update_event_count),
+            EventRetention::Count(_) => {}
+            EventRetention::UntilConsumed => {
+                let mut consumed = self.update_event_count;
+                let readers = self.readers.get_mut().unwrap();
+                readers.retain(|reader| match reader.upgrade() {
+                    Some(cursor) => {
+                        consumed = consumed.min(cursor.load(Ordering::Relaxed));
+                        true
+                    }
+                    None => false,
+                });
+                self.drop_events_before(consumed);
             }
         }
+        self.update_event_count = self.event_count;
     }
 
     /// A system that calls [Events::update] once per frame.
@@ -317,25 +370,14 @@ impl<T: bevy_ecs::Resource> Events<T> {
 
     /// Removes all events.
     pub fn clear(&mut self) {
-        self.events_a.clear();
-        self.events_b.clear();
+        self.events.clear();
+        self.start_event_count = self.event_count;
     }
 
     /// Creates a draining iterator that removes all events.
     pub fn drain(&mut self) -> impl Iterator<Item = T> + '_ {
-        let map = |i: EventInstance<T>| i.event;
-        match self.state {
-            State::A => self
-                .events_b
-                .drain(..)
-                .map(map)
-                .chain(self.events_a.drain(..).map(map)),
-            State::B => self
-                .events_a
-                .drain(..)
-                .map(map)
-                .chain(self.events_b.drain(..).map(map)),
-        }
+        self.start_event_count = self.event_count;
+        self.events.drain(..).map(|i| i.event)
     }
 
     pub fn extend<I>(&mut self, events: I)
@@ -353,13 +395,21 @@ impl<T: bevy_ecs::Resource> Events<T> {
     /// If events happen outside that window, they will not be handled. For example, any events that happen after this call and before
     /// the next `update()` call will be dropped.
     pub fn iter_current_update_events(&self) -> impl DoubleEndedIterator<Item = &T> {
-        match self.state {
-            State::A => self.events_a.iter().map(map_instance_event),
-            State::B => self.events_b.iter().map(map_instance_event),
-        }
+        let index = self
+            .update_event_count
+            .saturating_sub(self.start_event_count)
+            .min(self.events.len());
+        self.events.range(index..).map(map_instance_event)
     }
 }
 
+/// Sends the errors of a system as [SystemError] events, to use with
+/// [IntoPipeErrorSystem::pipe_error](bevy_ecs::IntoPipeErrorSystem::pipe_error). The [Events] are added by
+/// [AppBuilder::default](crate::AppBuilder).
+pub fn send_system_error(In(error): In<SystemError>, mut events: ResMut<Events<SystemError>>) {
+    events.send(error);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -459,6 +509,83 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_missed_events() {
+        let mut events = Events::<TestEvent>::default();
+        let mut reader = events.get_reader();
+        let mut default_reader = EventReader::default();
+
+        events.send(TestEvent { i: 0 });
+        events.update();
+        events.send(TestEvent { i: 1 });
+        events.update();
+        events.send(TestEvent { i: 2 });
+
+        assert_eq!(
+            get_events(&events, &mut reader),
+            vec![TestEvent { i: 1 }, TestEvent { i: 2 }]
+        );
+        assert_eq!(reader.missed_events(), 1);
+        assert_eq!(
+            get_events(&events, &mut default_reader),
+            vec![TestEvent { i: 1 }, TestEvent { i: 2 }]
+        );
+        assert_eq!(
+            default_reader.missed_events(),
+            0,
+            "events sent before the first read are not missed"
+        );
+    }
+
+    #[test]
+    fn test_retention_until_consumed() {
+        let mut events = Events::<TestEvent>::with_retention(EventRetention::UntilConsumed);
+        let mut fast_reader = events.get_reader();
+        let mut slow_reader = events.get_reader();
+
+        for i in 0..4 {
+            events.send(TestEvent { i });
+            assert_eq!(get_events(&events, &mut fast_reader), vec![TestEvent { i }]);
+            events.update();
+        }
+        assert_eq!(get_events(&events, &mut slow_reader).len(), 4);
+        assert_eq!(slow_reader.missed_events(), 0);
+
+        events.update();
+        events.update();
+        assert_eq!(events.events.len(), 0, "consumed events are dropped");
+
+        events.send(TestEvent { i: 4 });
+        drop(slow_reader);
+        events.update();
+        events.update();
+        assert_eq!(
+            events.events.len(),
+            1,
+            "events are kept until the remaining reader reads them"
+        );
+        assert_eq!(
+            get_events(&events, &mut fast_reader),
+            vec![TestEvent { i: 4 }]
+        );
+    }
+
+    #[test]
+    fn test_retention_count() {
+        let mut events = Events::<TestEvent>::with_retention(EventRetention::Count(2));
+        let mut reader = events.get_reader();
+
+        for i in 0..5 {
+            events.send(TestEvent { i });
+            events.update();
+        }
+        assert_eq!(
+            get_events(&events, &mut reader),
+            vec![TestEvent { i: 3 }, TestEvent { i: 4 }]
+        );
+        assert_eq!(reader.missed_events(), 3);
+    }
+
     fn get_events(
         events: &Events<TestEvent>,
         reader: &mut EventReader<TestEvent>,
@@ -22,7 +22,7 @@ pub mod prelude {
     pub use crate::{
         app::App,
         app_builder::AppBuilder,
-        event::{EventReader, Events},
+        event::{send_system_error, EventReader, EventRetention, Events},
         stage, DynamicPlugin, Plugin, PluginGroup,
     };
 }
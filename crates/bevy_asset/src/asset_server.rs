@@ -1,16 +1,25 @@
 use crate::{
-    path::{AssetPath, AssetPathId, SourcePathId},
+    get_meta_path, get_processed_path,
+    path::{get_hasher, AssetPath, AssetPathId, SourcePathId},
     Asset, AssetIo, AssetIoError, AssetLifecycle, AssetLifecycleChannel, AssetLifecycleEvent,
-    AssetLoader, Assets, Handle, HandleId, HandleUntyped, LabelId, LoadContext, LoadState,
-    RefChange, RefChangeChannel, SourceInfo, SourceMeta,
+    AssetLoader, AssetProcessor, AssetSaver, AssetSaverDynamic, Assets, Handle, HandleId,
+    HandleUntyped, LabelId, LoadContext, LoadState, RefChange, RefChangeChannel, SourceInfo,
+    SourceMeta, SourceSettings,
 };
 use anyhow::Result;
-use bevy_ecs::Res;
-use bevy_tasks::TaskPool;
-use bevy_utils::{HashMap, Uuid};
-use crossbeam_channel::TryRecvError;
+use bevy_app::Events;
+use bevy_ecs::{Res, ResMut};
+use bevy_reflect::TypeUuid;
+use bevy_tasks::{Task, TaskPool};
+use bevy_utils::{tracing::warn, HashMap, HashSet, Uuid};
+use crossbeam_channel::{Receiver, Sender, TryRecvError};
 use parking_lot::RwLock;
-use std::{collections::hash_map::Entry, path::Path, sync::Arc};
+use std::{
+    collections::hash_map::Entry,
+    hash::{Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 use thiserror::Error;
 
 /// Errors that occur while loading assets with an AssetServer
@@ -20,12 +29,43 @@ pub enum AssetServerError {
     AssetFolderNotADirectory(String),
     #[error("no AssetLoader found for the given extension")]
     MissingAssetLoader(Option<String>),
+    #[error("no AssetLoader found for the name given in a .meta file")]
+    MissingNamedAssetLoader(String),
+    #[error(
+        "several AssetLoaders have the name given in a .meta file, it needs their full type name"
+    )]
+    AmbiguousNamedAssetLoader(String),
+    #[error("failed to parse the .meta file of an asset")]
+    SourceSettingsError(ron::Error),
     #[error("the given type does not match the type of the loaded asset")]
     IncorrectHandleType,
     #[error("encountered an error while loading an asset")]
     AssetLoaderError(anyhow::Error),
     #[error("`PathLoader` encountered an error")]
     PathLoaderError(#[from] AssetIoError),
+    #[error("no AssetSaver found for the given asset type and extension")]
+    MissingAssetSaver(Option<String>),
+    #[error("the asset of the given handle is not loaded")]
+    AssetNotLoaded,
+    #[error("encountered an error while saving an asset")]
+    AssetSaverError(anyhow::Error),
+    #[error("encountered an error while processing an asset")]
+    AssetProcessorError(anyhow::Error),
+}
+
+/// Events sent by the [AssetServer] when an asset source finished loading or failed to load. `path` and `handle`
+/// refer to the source, without label.
+#[derive(Debug)]
+pub enum AssetLoadEvent {
+    Loaded {
+        path: AssetPath<'static>,
+        handle: HandleUntyped,
+    },
+    Failed {
+        path: AssetPath<'static>,
+        handle: HandleUntyped,
+        error: AssetServerError,
+    },
 }
 
 #[derive(Default)]
@@ -41,7 +81,15 @@ pub struct AssetServerInternal {
     pub(crate) asset_lifecycles: Arc<RwLock<HashMap<Uuid, Box<dyn AssetLifecycle>>>>,
     loaders: RwLock<Vec<Arc<Box<dyn AssetLoader>>>>,
     extension_to_loader_index: RwLock<HashMap<String, usize>>,
+    /// `None` for the short type names shared by several loaders
+    type_name_to_loader_index: RwLock<HashMap<String, Option<usize>>>,
+    savers: RwLock<HashMap<Uuid, Vec<Arc<dyn AssetSaverDynamic>>>>,
+    processors: RwLock<Vec<Arc<Box<dyn AssetProcessor>>>>,
+    extension_to_processor_index: RwLock<HashMap<String, usize>>,
+    imported_folder: RwLock<Option<PathBuf>>,
     handle_to_path: Arc<RwLock<HashMap<HandleId, AssetPath<'static>>>>,
+    load_event_sender: Sender<AssetLoadEvent>,
+    load_event_receiver: Receiver<AssetLoadEvent>,
     task_pool: TaskPool,
 }
 
@@ -64,14 +112,22 @@ impl AssetServer {
     }
 
     pub fn with_boxed_io(asset_io: Box<dyn AssetIo>, task_pool: TaskPool) -> Self {
+        let (load_event_sender, load_event_receiver) = crossbeam_channel::unbounded();
         AssetServer {
             server: Arc::new(AssetServerInternal {
                 loaders: Default::default(),
                 extension_to_loader_index: Default::default(),
+                type_name_to_loader_index: Default::default(),
+                savers: Default::default(),
+                processors: Default::default(),
+                extension_to_processor_index: Default::default(),
+                imported_folder: Default::default(),
                 asset_sources: Default::default(),
                 asset_ref_counter: Default::default(),
                 handle_to_path: Default::default(),
                 asset_lifecycles: Default::default(),
+                load_event_sender,
+                load_event_receiver,
                 task_pool,
                 asset_io,
             }),
@@ -98,9 +154,66 @@ impl AssetServer {
                 .write()
                 .insert(extension.to_string(), loader_index);
         }
+        // loaders can be selected in .meta files by their type name, with or without module path
+        let type_name = std::any::type_name::<T>();
+        let mut type_name_to_loader_index = self.server.type_name_to_loader_index.write();
+        let is_new_type = type_name_to_loader_index
+            .insert(type_name.to_string(), Some(loader_index))
+            .is_none();
+        let short_name = type_name.rsplit("::").next().unwrap_or(type_name);
+        if short_name != type_name {
+            // a short name shared by loaders of different modules selects none of them
+            let short_name_index = match type_name_to_loader_index.get(short_name) {
+                None => Some(loader_index),
+                Some(Some(_)) if is_new_type => {
+                    warn!(
+                        "Several AssetLoaders are named {}, .meta files have to select {} by its full type name.",
+                        short_name, type_name
+                    );
+                    None
+                }
+                Some(Some(_)) => Some(loader_index),
+                Some(None) => None,
+            };
+            type_name_to_loader_index.insert(short_name.to_string(), short_name_index);
+        }
         loaders.push(Arc::new(Box::new(loader)));
     }
 
+    pub fn add_saver<T>(&self, saver: T)
+    where
+        T: AssetSaver,
+    {
+        self.server
+            .savers
+            .write()
+            .entry(T::Asset::TYPE_UUID)
+            .or_insert_with(Vec::new)
+            .push(Arc::new(saver));
+    }
+
+    /// Adds a processor for the sources with its extensions, see [AssetProcessor]
+    pub fn add_processor<T>(&self, processor: T)
+    where
+        T: AssetProcessor,
+    {
+        let mut processors = self.server.processors.write();
+        let processor_index = processors.len();
+        for extension in processor.extensions().iter() {
+            self.server
+                .extension_to_processor_index
+                .write()
+                .insert(extension.to_string(), processor_index);
+        }
+        processors.push(Arc::new(Box::new(processor)));
+    }
+
+    /// Sets the folder, relative to the root of the [AssetIo], where processed sources are cached. Without one,
+    /// sources are processed every time they are loaded.
+    pub fn set_imported_folder<P: Into<PathBuf>>(&self, path: P) {
+        *self.server.imported_folder.write() = Some(path.into());
+    }
+
     pub fn watch_for_changes(&self) -> Result<(), AssetServerError> {
         self.server.asset_io.watch_for_changes()?;
         Ok(())
@@ -139,6 +252,84 @@ impl AssetServer {
             .and_then(|extension| self.get_asset_loader(extension))
     }
 
+    fn get_path_asset_processor<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Option<Arc<Box<dyn AssetProcessor>>> {
+        let extension = path.as_ref().extension().and_then(|e| e.to_str())?;
+        self.server
+            .extension_to_processor_index
+            .read()
+            .get(extension)
+            .map(|index| self.server.processors.read()[*index].clone())
+    }
+
+    fn get_named_asset_loader(
+        &self,
+        type_name: &str,
+    ) -> Result<Arc<Box<dyn AssetLoader>>, AssetServerError> {
+        match self.server.type_name_to_loader_index.read().get(type_name) {
+            Some(Some(index)) => Ok(self.server.loaders.read()[*index].clone()),
+            Some(None) => Err(AssetServerError::AmbiguousNamedAssetLoader(
+                type_name.to_string(),
+            )),
+            None => Err(AssetServerError::MissingNamedAssetLoader(
+                type_name.to_string(),
+            )),
+        }
+    }
+
+    /// Returns the processor of the source at `path`, if any, and the loader of the (processed) source. A loader
+    /// named in the .meta file of the source takes precedence.
+    #[allow(clippy::type_complexity)]
+    fn get_path_asset_processor_and_loader<P: AsRef<Path>>(
+        &self,
+        path: P,
+        loader_name: Option<&str>,
+    ) -> Result<
+        (
+            Option<Arc<Box<dyn AssetProcessor>>>,
+            Arc<Box<dyn AssetLoader>>,
+        ),
+        AssetServerError,
+    > {
+        let asset_processor = self.get_path_asset_processor(&path);
+        let processed_extension = asset_processor
+            .as_ref()
+            .and_then(|processor| processor.processed_extension());
+        let asset_loader = match (loader_name, processed_extension) {
+            (Some(loader_name), _) => self.get_named_asset_loader(loader_name)?,
+            (None, Some(extension)) => self.get_asset_loader(extension)?,
+            (None, None) => self.get_path_asset_loader(&path)?,
+        };
+        Ok((asset_processor, asset_loader))
+    }
+
+    fn get_path_asset_saver<P: AsRef<Path>>(
+        &self,
+        type_uuid: Uuid,
+        path: P,
+    ) -> Result<Arc<dyn AssetSaverDynamic>, AssetServerError> {
+        let extension = path
+            .as_ref()
+            .extension()
+            .and_then(|e| e.to_str())
+            .ok_or(AssetServerError::MissingAssetSaver(None))?;
+        // savers added last take precedence, like loaders do
+        self.server
+            .savers
+            .read()
+            .get(&type_uuid)
+            .and_then(|savers| {
+                savers
+                    .iter()
+                    .rev()
+                    .find(|saver| saver.extensions().contains(&extension))
+                    .cloned()
+            })
+            .ok_or_else(|| AssetServerError::MissingAssetSaver(Some(extension.to_string())))
+    }
+
     pub fn get_handle_path<H: Into<HandleId>>(&self, handle: H) -> Option<AssetPath<'_>> {
         self.server
             .handle_to_path
@@ -178,18 +369,54 @@ impl AssetServer {
         load_state
     }
 
+    /// Like [AssetServer::get_load_state], but only returns [LoadState::Loaded] once the dependencies of the source
+    /// are loaded too, recursively. Dependencies are added with [LoadedAsset::with_dependency](crate::LoadedAsset::with_dependency).
+    pub fn get_load_state_with_dependencies<H: Into<HandleId>>(&self, handle: H) -> LoadState {
+        match handle.into() {
+            HandleId::AssetPathId(id) => {
+                let asset_sources = self.server.asset_sources.read();
+                if asset_sources.contains_key(&id.source_path_id()) {
+                    get_source_load_state_with_dependencies(
+                        &asset_sources,
+                        id.source_path_id(),
+                        &mut HashSet::default(),
+                    )
+                } else {
+                    LoadState::NotLoaded
+                }
+            }
+            HandleId::Id(_, _) => LoadState::NotLoaded,
+        }
+    }
+
+    /// Like [AssetServer::get_group_load_state], but waits for dependencies, see
+    /// [AssetServer::get_load_state_with_dependencies]
+    pub fn get_group_load_state_with_dependencies(
+        &self,
+        handles: impl IntoIterator<Item = HandleId>,
+    ) -> LoadState {
+        let mut load_state = LoadState::Loaded;
+        for handle_id in handles {
+            match self.get_load_state_with_dependencies(handle_id) {
+                LoadState::Loaded => continue,
+                LoadState::Loading => {
+                    load_state = LoadState::Loading;
+                }
+                LoadState::Failed => return LoadState::Failed,
+                LoadState::NotLoaded => return LoadState::NotLoaded,
+            }
+        }
+
+        load_state
+    }
+
     pub fn load<'a, T: Asset, P: Into<AssetPath<'a>>>(&self, path: P) -> Handle<T> {
         self.load_untyped(path).typed()
     }
 
-    // TODO: properly set failed LoadState in all failure cases
-    async fn load_async<'a, P: Into<AssetPath<'a>>>(
-        &self,
-        path: P,
-        force: bool,
-    ) -> Result<AssetPathId, AssetServerError> {
+    /// Loads the source of `path`. Failures set the [LoadState::Failed] state and are reported with [AssetLoadEvent]s.
+    async fn load_async<'a, P: Into<AssetPath<'a>>>(&self, path: P, force: bool) {
         let asset_path: AssetPath = path.into();
-        let asset_loader = self.get_path_asset_loader(asset_path.path())?;
         let asset_path_id: AssetPathId = asset_path.get_id();
 
         // load metadata and update source info. this is done in a scope to ensure we release the locks before loading
@@ -213,7 +440,7 @@ impl AssetServer {
                     .committed_assets
                     .contains(&asset_path_id.label_id())
             {
-                return Ok(asset_path_id);
+                return;
             }
 
             source_info.load_state = LoadState::Loading;
@@ -223,8 +450,57 @@ impl AssetServer {
             source_info.version
         };
 
-        // load the asset bytes
-        let bytes = self.server.asset_io.load_path(asset_path.path()).await?;
+        if let Err(error) = self.load_source(&asset_path, version).await {
+            // only report the failure if no newer version is being loaded
+            let mut asset_sources = self.server.asset_sources.write();
+            let source_info = asset_sources
+                .get_mut(&asset_path_id.source_path_id())
+                .expect("`AssetSource` should exist at this point.");
+            if version == source_info.version {
+                source_info.load_state = LoadState::Failed;
+                let path = AssetPath::new(source_info.path.clone(), None);
+                let handle = HandleUntyped::weak(path.get_id().into());
+                self.server
+                    .load_event_sender
+                    .send(AssetLoadEvent::Failed {
+                        path,
+                        handle,
+                        error,
+                    })
+                    .unwrap();
+            }
+        }
+    }
+
+    async fn load_source(
+        &self,
+        asset_path: &AssetPath<'_>,
+        version: usize,
+    ) -> Result<(), AssetServerError> {
+        let source_settings_bytes = self.load_source_settings(asset_path.path()).await?;
+        let loader_name = match source_settings_bytes {
+            Some(ref bytes) => {
+                ron::de::from_bytes::<SourceSettings>(bytes)
+                    .map_err(AssetServerError::SourceSettingsError)?
+                    .loader
+            }
+            None => None,
+        };
+        let (asset_processor, asset_loader) =
+            self.get_path_asset_processor_and_loader(asset_path.path(), loader_name.as_deref())?;
+
+        // load the asset bytes, processing them first if a processor is registered for the source
+        let mut bytes = self.server.asset_io.load_path(asset_path.path()).await?;
+        let processed_hash = match asset_processor {
+            Some(asset_processor) => {
+                let (processed_bytes, processed_hash) = self
+                    .process_source(&**asset_processor, asset_path.path(), bytes)
+                    .await?;
+                bytes = processed_bytes;
+                Some(processed_hash)
+            }
+            None => None,
+        };
 
         // load the asset source using the corresponding AssetLoader
         let mut load_context = LoadContext::new(
@@ -232,24 +508,29 @@ impl AssetServer {
             &self.server.asset_ref_counter.channel,
             &*self.server.asset_io,
             version,
+            source_settings_bytes.as_deref(),
         );
         asset_loader
             .load(&bytes, &mut load_context)
             .await
             .map_err(AssetServerError::AssetLoaderError)?;
 
+        let source_meta = SourceMeta {
+            assets: load_context.get_asset_metas(),
+            processed_hash,
+        };
+        if processed_hash.is_some() {
+            self.save_imported_meta(asset_path.path(), &source_meta)
+                .await;
+        }
+
         // if version has changed since we loaded and grabbed a lock, return. theres is a newer version being loaded
         let mut asset_sources = self.server.asset_sources.write();
         let source_info = asset_sources
-            .get_mut(&asset_path_id.source_path_id())
+            .get_mut(&asset_path.get_id().source_path_id())
             .expect("`AssetSource` should exist at this point.");
         if version != source_info.version {
-            return Ok(asset_path_id);
-        }
-
-        // if all assets have been committed already (aka there were 0), set state to "Loaded"
-        if source_info.is_loaded() {
-            source_info.load_state = LoadState::Loaded;
+            return Ok(());
         }
 
         // reset relevant SourceInfo fields
@@ -257,9 +538,13 @@ impl AssetServer {
         // TODO: queue free old assets
         source_info.asset_types.clear();
 
-        source_info.meta = Some(SourceMeta {
-            assets: load_context.get_asset_metas(),
-        });
+        source_info.meta = Some(source_meta);
+
+        // if all assets have been committed already (aka there were 0), set state to "Loaded"
+        if source_info.is_loaded() {
+            source_info.load_state = LoadState::Loaded;
+            self.send_loaded_event(source_info);
+        }
 
         // load asset dependencies and prepare asset type hashmap
         for (label, loaded_asset) in load_context.labeled_assets.iter_mut() {
@@ -273,10 +558,96 @@ impl AssetServer {
 
         self.server
             .asset_io
-            .watch_path_for_changes(asset_path.path())
-            .unwrap();
+            .watch_path_for_changes(asset_path.path())?;
+        if source_settings_bytes.is_some() {
+            self.server
+                .asset_io
+                .watch_path_for_changes(&get_meta_path(asset_path.path()))?;
+        }
         self.create_assets_in_load_context(&mut load_context);
-        Ok(asset_path_id)
+        Ok(())
+    }
+
+    fn send_loaded_event(&self, source_info: &SourceInfo) {
+        let path = AssetPath::new(source_info.path.clone(), None);
+        let handle = HandleUntyped::weak(path.get_id().into());
+        self.server
+            .load_event_sender
+            .send(AssetLoadEvent::Loaded { path, handle })
+            .unwrap();
+    }
+
+    /// Reads the .meta file of the source at `path`, see [SourceSettings]
+    async fn load_source_settings(&self, path: &Path) -> Result<Option<Vec<u8>>, AssetServerError> {
+        match self.server.asset_io.load_path(&get_meta_path(path)).await {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(AssetIoError::NotFound(_)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Processes the source at `path`, or reads the processed artifact from the imported folder if the
+    /// persisted [SourceMeta] shows it was created from the same source and processor settings.
+    async fn process_source(
+        &self,
+        asset_processor: &dyn AssetProcessor,
+        path: &Path,
+        bytes: Vec<u8>,
+    ) -> Result<(Vec<u8>, u64), AssetServerError> {
+        let mut hasher = get_hasher();
+        bytes.hash(&mut hasher);
+        asset_processor.settings_hash().hash(&mut hasher);
+        let processed_hash = hasher.finish();
+
+        let imported_folder = self.server.imported_folder.read().clone();
+        let processed_path = imported_folder.as_ref().map(|imported_folder| {
+            imported_folder.join(get_processed_path(
+                path,
+                asset_processor.processed_extension(),
+            ))
+        });
+
+        if let (Some(imported_folder), Some(processed_path)) = (&imported_folder, &processed_path) {
+            let meta_path = imported_folder.join(get_meta_path(path));
+            let is_processed = match self.server.asset_io.load_path(&meta_path).await {
+                Ok(meta_bytes) => ron::de::from_bytes::<SourceMeta>(&meta_bytes)
+                    .map_or(false, |meta| meta.processed_hash == Some(processed_hash)),
+                Err(_) => false,
+            };
+            if is_processed {
+                if let Ok(processed_bytes) = self.server.asset_io.load_path(processed_path).await {
+                    return Ok((processed_bytes, processed_hash));
+                }
+            }
+        }
+
+        let processed_bytes = asset_processor
+            .process(&bytes, path)
+            .await
+            .map_err(AssetServerError::AssetProcessorError)?;
+        if let Some(processed_path) = processed_path {
+            // the cache is best effort: sources are processed again if the AssetIo can't save them
+            let _ = self
+                .server
+                .asset_io
+                .save_path(&processed_path, &processed_bytes)
+                .await;
+        }
+        Ok((processed_bytes, processed_hash))
+    }
+
+    async fn save_imported_meta(&self, path: &Path, source_meta: &SourceMeta) {
+        let imported_folder = self.server.imported_folder.read().clone();
+        if let Some(imported_folder) = imported_folder {
+            let meta_path = imported_folder.join(get_meta_path(path));
+            if let Ok(meta) = ron::ser::to_string(source_meta) {
+                let _ = self
+                    .server
+                    .asset_io
+                    .save_path(&meta_path, meta.as_bytes())
+                    .await;
+            }
+        }
     }
 
     pub fn load_untyped<'a, P: Into<AssetPath<'a>>>(&self, path: P) -> HandleUntyped {
@@ -295,7 +666,7 @@ impl AssetServer {
         self.server
             .task_pool
             .spawn(async move {
-                server.load_async(owned_path, force).await.unwrap();
+                server.load_async(owned_path, force).await;
             })
             .detach();
         asset_path.into()
@@ -312,12 +683,19 @@ impl AssetServer {
             ));
         }
 
+        let imported_folder = self.server.imported_folder.read().clone();
         let mut handles = Vec::new();
         for child_path in self.server.asset_io.read_directory(path.as_ref())? {
+            if imported_folder.as_ref() == Some(&child_path) {
+                continue;
+            }
             if self.server.asset_io.is_directory(&child_path) {
                 handles.extend(self.load_folder(&child_path)?);
             } else {
-                if self.get_path_asset_loader(&child_path).is_err() {
+                if self
+                    .get_path_asset_processor_and_loader(&child_path, None)
+                    .is_err()
+                {
                     continue;
                 }
                 let handle =
@@ -329,6 +707,42 @@ impl AssetServer {
         Ok(handles)
     }
 
+    /// Saves the asset of `handle` to `path`, see [AssetServer::save_asset]
+    pub fn save<T: Asset, P: AsRef<Path>>(
+        &self,
+        assets: &Assets<T>,
+        handle: &Handle<T>,
+        path: P,
+    ) -> Result<Task<Result<(), AssetIoError>>, AssetServerError> {
+        let asset = assets.get(handle).ok_or(AssetServerError::AssetNotLoaded)?;
+        self.save_asset(asset, path)
+    }
+
+    /// Saves `asset` to `path` with the [AssetSaver] registered for its type and the extension of `path`.
+    /// The asset is serialized before this returns, the returned task writes the bytes with [AssetIo::save_path].
+    pub fn save_asset<T: Asset, P: AsRef<Path>>(
+        &self,
+        asset: &T,
+        path: P,
+    ) -> Result<Task<Result<(), AssetIoError>>, AssetServerError> {
+        let path = path.as_ref();
+        let asset_saver = self.get_path_asset_saver(T::TYPE_UUID, path)?;
+        let bytes = self
+            .server
+            .task_pool
+            .scope(|scope| scope.spawn(asset_saver.save_dynamic(asset, path)))
+            .pop()
+            .expect("The save task should have returned a result.")
+            .map_err(AssetServerError::AssetSaverError)?;
+
+        let server = self.clone();
+        let path = path.to_owned();
+        Ok(self
+            .server
+            .task_pool
+            .spawn(async move { server.server.asset_io.save_path(&path, &bytes).await }))
+    }
+
     pub fn free_unused_assets(&self) {
         let receiver = &self.server.asset_ref_counter.channel.receiver;
         let mut ref_counts = self.server.asset_ref_counter.ref_counts.write();
@@ -410,8 +824,11 @@ impl AssetServer {
                         if let Some(source_info) = asset_sources.get_mut(&id.source_path_id()) {
                             if source_info.version == result.version {
                                 source_info.committed_assets.insert(id.label_id());
-                                if source_info.is_loaded() {
+                                if source_info.is_loaded()
+                                    && source_info.load_state != LoadState::Loaded
+                                {
                                     source_info.load_state = LoadState::Loaded;
+                                    self.send_loaded_event(source_info);
                                 }
                             }
                         }
@@ -441,6 +858,457 @@ impl AssetServer {
     }
 }
 
+fn get_source_load_state_with_dependencies(
+    asset_sources: &HashMap<SourcePathId, SourceInfo>,
+    source_path_id: SourcePathId,
+    visited: &mut HashSet<SourcePathId>,
+) -> LoadState {
+    // sources that are already being visited are reported by their first visit, which also handles cycles
+    if !visited.insert(source_path_id) {
+        return LoadState::Loaded;
+    }
+    let source_info = match asset_sources.get(&source_path_id) {
+        Some(source_info) => source_info,
+        // dependencies are added to the sources once their load starts
+        None => return LoadState::Loading,
+    };
+    match source_info.load_state {
+        LoadState::Loaded => {}
+        LoadState::NotLoaded => return LoadState::Loading,
+        load_state => return load_state,
+    }
+
+    let mut load_state = LoadState::Loaded;
+    let dependencies = source_info
+        .meta
+        .iter()
+        .flat_map(|meta| meta.assets.iter())
+        .flat_map(|asset_meta| asset_meta.dependencies.iter());
+    for dependency in dependencies {
+        match get_source_load_state_with_dependencies(
+            asset_sources,
+            dependency.get_id().source_path_id(),
+            visited,
+        ) {
+            LoadState::Loaded => continue,
+            LoadState::Failed => return LoadState::Failed,
+            _ => load_state = LoadState::Loading,
+        }
+    }
+    load_state
+}
+
 pub fn free_unused_assets_system(asset_server: Res<AssetServer>) {
     asset_server.free_unused_assets();
 }
+
+pub fn asset_load_event_system(
+    asset_server: Res<AssetServer>,
+    mut events: ResMut<Events<AssetLoadEvent>>,
+) {
+    for event in asset_server.server.load_event_receiver.try_iter() {
+        events.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        get_source_load_state_with_dependencies, AssetLoadEvent, AssetServer, AssetServerError,
+    };
+    use crate::{
+        path::SourcePathId, AssetIo, AssetLoader, AssetMeta, AssetPath, AssetProcessor, Assets,
+        Handle, HandleId, LoadContext, LoadState, LoadedAsset, MemoryAssetIo, SourceInfo,
+        SourceMeta,
+    };
+    use anyhow::Result;
+    use bevy_reflect::TypeUuid;
+    use bevy_tasks::TaskPool;
+    use bevy_utils::{BoxedFuture, HashMap, HashSet};
+    use futures_lite::future;
+    use serde::Deserialize;
+    use std::{
+        path::Path,
+        sync::{
+            atomic::{AtomicU64, AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    #[derive(Debug, TypeUuid)]
+    #[uuid = "0b3e9b2f-1a6c-4d7e-9f55-6a1d2c3b4e5f"]
+    struct Text(String);
+
+    struct TextLoader;
+
+    impl AssetLoader for TextLoader {
+        fn load<'a>(
+            &'a self,
+            bytes: &'a [u8],
+            load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<()>> {
+            Box::pin(async move {
+                let text = String::from_utf8(bytes.to_vec())?;
+                load_context.set_default_asset(LoadedAsset::new(Text(text)));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+    }
+
+    /// Uppercases `.txt` sources, counting how many times it ran
+    #[derive(Default)]
+    struct UppercaseProcessor {
+        runs: Arc<AtomicUsize>,
+        settings_hash: Arc<AtomicU64>,
+    }
+
+    impl AssetProcessor for UppercaseProcessor {
+        fn process<'a>(
+            &'a self,
+            bytes: &'a [u8],
+            _path: &'a Path,
+        ) -> BoxedFuture<'a, Result<Vec<u8>>> {
+            Box::pin(async move {
+                self.runs.fetch_add(1, Ordering::SeqCst);
+                Ok(bytes.to_ascii_uppercase())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["txt"]
+        }
+
+        fn settings_hash(&self) -> u64 {
+            self.settings_hash.load(Ordering::SeqCst)
+        }
+    }
+
+    fn setup(asset_io: MemoryAssetIo) -> (AssetServer, Assets<Text>) {
+        let asset_server = AssetServer::new(asset_io, TaskPool::default());
+        let assets = asset_server.register_asset_type::<Text>();
+        asset_server.add_loader(TextLoader);
+        (asset_server, assets)
+    }
+
+    fn setup_processor(asset_server: &AssetServer) -> (Arc<AtomicUsize>, Arc<AtomicU64>) {
+        let processor = UppercaseProcessor::default();
+        let counters = (processor.runs.clone(), processor.settings_hash.clone());
+        asset_server.set_imported_folder("imported");
+        asset_server.add_processor(processor);
+        counters
+    }
+
+    fn asset_io(asset_server: &AssetServer) -> &MemoryAssetIo {
+        asset_server
+            .server
+            .asset_io
+            .downcast_ref::<MemoryAssetIo>()
+            .unwrap()
+    }
+
+    /// Loads the source at `path` on this thread, and returns its text
+    fn load(asset_server: &AssetServer, assets: &mut Assets<Text>, path: &str) -> String {
+        future::block_on(asset_server.load_async(path, true));
+        asset_server.update_asset_storage(assets);
+        let handle: Handle<Text> = asset_server.get_handle(path);
+        assets.get(&handle).unwrap().0.clone()
+    }
+
+    #[test]
+    fn processed_sources_are_cached() {
+        let (asset_server, mut assets) =
+            setup(MemoryAssetIo::default().with_asset("a.txt", &b"hello"[..]));
+        let (runs, _) = setup_processor(&asset_server);
+
+        assert_eq!(load(&asset_server, &mut assets, "a.txt"), "HELLO");
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        let imported =
+            future::block_on(asset_io(&asset_server).load_path(Path::new("imported/a.txt")));
+        assert_eq!(imported.unwrap(), b"HELLO");
+
+        assert_eq!(load(&asset_server, &mut assets, "a.txt"), "HELLO");
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        asset_io(&asset_server).insert("a.txt", &b"bye"[..]);
+        assert_eq!(load(&asset_server, &mut assets, "a.txt"), "BYE");
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        assert_eq!(load(&asset_server, &mut assets, "a.txt"), "BYE");
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn processor_settings_invalidate_processed_sources() {
+        let (asset_server, mut assets) =
+            setup(MemoryAssetIo::default().with_asset("a.txt", &b"hello"[..]));
+        let (runs, settings_hash) = setup_processor(&asset_server);
+
+        load(&asset_server, &mut assets, "a.txt");
+        load(&asset_server, &mut assets, "a.txt");
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+
+        settings_hash.store(1, Ordering::SeqCst);
+        load(&asset_server, &mut assets, "a.txt");
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+        load(&asset_server, &mut assets, "a.txt");
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn load_folder_skips_imported_folder() {
+        let (asset_server, mut assets) = setup(
+            MemoryAssetIo::default()
+                .with_asset("a.txt", &b"a"[..])
+                .with_asset("sub/b.txt", &b"b"[..]),
+        );
+        setup_processor(&asset_server);
+        load(&asset_server, &mut assets, "a.txt");
+        load(&asset_server, &mut assets, "sub/b.txt");
+        assert!(asset_io(&asset_server).is_directory(Path::new("imported")));
+
+        let handles = asset_server
+            .load_folder("")
+            .unwrap()
+            .iter()
+            .map(|handle| handle.id)
+            .collect::<HashSet<_>>();
+        let expected = vec![HandleId::from("a.txt"), HandleId::from("sub/b.txt")];
+        assert_eq!(handles, expected.into_iter().collect());
+    }
+
+    fn source(path: &str, load_state: LoadState, dependencies: &[&str]) -> SourceInfo {
+        let dependencies = dependencies
+            .iter()
+            .map(|dependency| AssetPath::from(*dependency).to_owned())
+            .collect();
+        SourceInfo {
+            meta: Some(SourceMeta {
+                assets: vec![AssetMeta {
+                    label: None,
+                    dependencies,
+                    type_uuid: Text::TYPE_UUID,
+                }],
+                processed_hash: None,
+            }),
+            path: path.into(),
+            asset_types: Default::default(),
+            load_state,
+            committed_assets: Default::default(),
+            version: 1,
+        }
+    }
+
+    fn load_state_with_dependencies(sources: &[SourceInfo], path: &str) -> LoadState {
+        let asset_sources = sources
+            .iter()
+            .map(|source| (SourcePathId::from(source.path.as_path()), source.clone()))
+            .collect::<HashMap<_, _>>();
+        get_source_load_state_with_dependencies(
+            &asset_sources,
+            SourcePathId::from(Path::new(path)),
+            &mut HashSet::default(),
+        )
+    }
+
+    #[test]
+    fn missing_loader_fails() {
+        let (asset_server, mut assets) =
+            setup(MemoryAssetIo::default().with_asset("a.unknown", &b""[..]));
+        future::block_on(asset_server.load_async("a.unknown", false));
+        asset_server.update_asset_storage(&mut assets);
+
+        assert_eq!(asset_server.get_load_state("a.unknown"), LoadState::Failed);
+        let events = asset_server
+            .server
+            .load_event_receiver
+            .try_iter()
+            .collect::<Vec<_>>();
+        match &events[..] {
+            [AssetLoadEvent::Failed {
+                path,
+                handle,
+                error: AssetServerError::MissingAssetLoader(Some(extension)),
+            }] => {
+                assert_eq!(path.path(), Path::new("a.unknown"));
+                assert_eq!(handle.id, HandleId::from("a.unknown"));
+                assert_eq!(extension, "unknown");
+            }
+            events => panic!("unexpected events {:?}", events),
+        }
+    }
+
+    #[test]
+    fn loading_dependencies_keep_sources_loading() {
+        let sources = [
+            source("a.txt", LoadState::Loaded, &["b.txt"]),
+            source("b.txt", LoadState::Loaded, &["c.txt"]),
+            source("c.txt", LoadState::Loading, &[]),
+        ];
+        assert_eq!(
+            load_state_with_dependencies(&sources, "a.txt"),
+            LoadState::Loading
+        );
+        // dependencies that did not start loading yet are loading too
+        assert_eq!(
+            load_state_with_dependencies(&sources[..2], "a.txt"),
+            LoadState::Loading
+        );
+
+        let sources = [
+            source("a.txt", LoadState::Loaded, &["b.txt", "c.txt"]),
+            source("b.txt", LoadState::Loaded, &[]),
+            source("c.txt", LoadState::Loaded, &[]),
+        ];
+        assert_eq!(
+            load_state_with_dependencies(&sources, "a.txt"),
+            LoadState::Loaded
+        );
+    }
+
+    #[test]
+    fn failed_dependencies_fail_sources() {
+        let sources = [
+            source("a.txt", LoadState::Loaded, &["b.txt", "c.txt"]),
+            source("b.txt", LoadState::Loading, &[]),
+            source("c.txt", LoadState::Failed, &[]),
+        ];
+        assert_eq!(
+            load_state_with_dependencies(&sources, "a.txt"),
+            LoadState::Failed
+        );
+    }
+
+    #[test]
+    fn dependency_cycles_terminate() {
+        let sources = [
+            source("a.txt", LoadState::Loaded, &["b.txt"]),
+            source("b.txt", LoadState::Loaded, &["a.txt"]),
+        ];
+        assert_eq!(
+            load_state_with_dependencies(&sources, "a.txt"),
+            LoadState::Loaded
+        );
+
+        let sources = [
+            source("a.txt", LoadState::Loaded, &["b.txt"]),
+            source("b.txt", LoadState::Loaded, &["a.txt", "c.txt"]),
+            source("c.txt", LoadState::Loading, &["a.txt"]),
+        ];
+        assert_eq!(
+            load_state_with_dependencies(&sources, "b.txt"),
+            LoadState::Loading
+        );
+    }
+
+    #[derive(Default, Deserialize)]
+    struct SuffixSettings {
+        suffix: String,
+    }
+
+    /// Loads `.txt` sources like [TextLoader], appending the suffix of its settings
+    struct SuffixLoader;
+
+    impl AssetLoader for SuffixLoader {
+        fn load<'a>(
+            &'a self,
+            bytes: &'a [u8],
+            load_context: &'a mut LoadContext,
+        ) -> BoxedFuture<'a, Result<()>> {
+            Box::pin(async move {
+                let settings = load_context.settings::<SuffixSettings>()?;
+                let text = String::from_utf8(bytes.to_vec())? + &settings.suffix;
+                load_context.set_default_asset(LoadedAsset::new(Text(text)));
+                Ok(())
+            })
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &[]
+        }
+    }
+
+    mod other {
+        /// A loader with the same name as [TextLoader](super::TextLoader)
+        pub struct TextLoader;
+
+        impl crate::AssetLoader for TextLoader {
+            fn load<'a>(
+                &'a self,
+                bytes: &'a [u8],
+                load_context: &'a mut crate::LoadContext,
+            ) -> bevy_utils::BoxedFuture<'a, anyhow::Result<()>> {
+                super::TextLoader.load(bytes, load_context)
+            }
+
+            fn extensions(&self) -> &[&str] {
+                &[]
+            }
+        }
+    }
+
+    #[test]
+    fn meta_files_select_loader_and_settings() {
+        let (asset_server, mut assets) = setup(
+            MemoryAssetIo::default()
+                .with_asset("a.txt", &b"a"[..])
+                .with_asset(
+                    "a.txt.meta",
+                    &br#"(loader: Some("SuffixLoader"), settings: (suffix: "!"))"#[..],
+                )
+                .with_asset("b.txt", &b"b"[..])
+                .with_asset(
+                    "b.txt.meta",
+                    &br#"(loader: Some("bevy_asset::asset_server::tests::SuffixLoader"))"#[..],
+                )
+                .with_asset("c.txt", &b"c"[..])
+                .with_asset("c.txt.meta", &br#"(settings: (suffix: "?"))"#[..]),
+        );
+        asset_server.add_loader(SuffixLoader);
+
+        assert_eq!(load(&asset_server, &mut assets, "a.txt"), "a!");
+        // settings default when the .meta file has none
+        assert_eq!(load(&asset_server, &mut assets, "b.txt"), "b");
+        // without a loader name, the loader is selected by extension and ignores the settings
+        assert_eq!(load(&asset_server, &mut assets, "c.txt"), "c");
+    }
+
+    #[test]
+    fn ambiguous_loader_names_need_module_path() {
+        let (asset_server, mut assets) = setup(
+            MemoryAssetIo::default()
+                .with_asset("a.txt", &b"a"[..])
+                .with_asset("a.txt.meta", &br#"(loader: Some("TextLoader"))"#[..])
+                .with_asset("b.txt", &b"b"[..])
+                .with_asset(
+                    "b.txt.meta",
+                    &br#"(loader: Some("bevy_asset::asset_server::tests::other::TextLoader"))"#[..],
+                ),
+        );
+        asset_server.add_loader(other::TextLoader);
+        // adding one of them again doesn't make the name select it
+        asset_server.add_loader(TextLoader);
+
+        future::block_on(asset_server.load_async("a.txt", false));
+        assert_eq!(asset_server.get_load_state("a.txt"), LoadState::Failed);
+        assert!(matches!(
+            asset_server.server.load_event_receiver.try_recv(),
+            Ok(AssetLoadEvent::Failed {
+                error: AssetServerError::AmbiguousNamedAssetLoader(_),
+                ..
+            })
+        ));
+        assert_eq!(load(&asset_server, &mut assets, "b.txt"), "b");
+
+        let (asset_server, mut assets) = setup(
+            MemoryAssetIo::default()
+                .with_asset("a.txt", &b"a"[..])
+                .with_asset("a.txt.meta", &br#"(loader: Some("TextLoader"))"#[..]),
+        );
+        // a loader added twice keeps its short name
+        asset_server.add_loader(TextLoader);
+        assert_eq!(load(&asset_server, &mut assets, "a.txt"), "a");
+    }
+}
@@ -1,5 +1,6 @@
 use crate::{
-    update_asset_storage_system, Asset, AssetLoader, AssetServer, Handle, HandleId, RefChange,
+    update_asset_storage_system, Asset, AssetLoader, AssetProcessor, AssetSaver, AssetServer,
+    Handle, HandleId, RefChange,
 };
 use bevy_app::{prelude::Events, AppBuilder};
 use bevy_ecs::{FromResources, IntoSystem, ResMut};
@@ -205,6 +206,18 @@ pub trait AddAsset {
     fn add_asset_loader<T>(&mut self, loader: T) -> &mut Self
     where
         T: AssetLoader;
+    fn init_asset_saver<T>(&mut self) -> &mut Self
+    where
+        T: AssetSaver + FromResources;
+    fn add_asset_saver<T>(&mut self, saver: T) -> &mut Self
+    where
+        T: AssetSaver;
+    fn init_asset_processor<T>(&mut self) -> &mut Self
+    where
+        T: AssetProcessor + FromResources;
+    fn add_asset_processor<T>(&mut self, processor: T) -> &mut Self
+    where
+        T: AssetProcessor;
 }
 
 impl AddAsset for AppBuilder {
@@ -247,4 +260,39 @@ impl AddAsset for AppBuilder {
             .add_loader(loader);
         self
     }
+
+    fn init_asset_saver<T>(&mut self) -> &mut Self
+    where
+        T: AssetSaver + FromResources,
+    {
+        self.add_asset_saver(T::from_resources(self.resources()))
+    }
+
+    fn add_asset_saver<T>(&mut self, saver: T) -> &mut Self
+    where
+        T: AssetSaver,
+    {
+        self.resources()
+            .get_mut::<AssetServer>()
+            .expect("AssetServer does not exist. Consider adding it as a resource.")
+            .add_saver(saver);
+        self
+    }
+    fn init_asset_processor<T>(&mut self) -> &mut Self
+    where
+        T: AssetProcessor + FromResources,
+    {
+        self.add_asset_processor(T::from_resources(self.resources()))
+    }
+
+    fn add_asset_processor<T>(&mut self, processor: T) -> &mut Self
+    where
+        T: AssetProcessor,
+    {
+        self.resources()
+            .get_mut::<AssetServer>()
+            .expect("AssetServer does not exist. Consider adding it as a resource.")
+            .add_processor(processor);
+        self
+    }
 }
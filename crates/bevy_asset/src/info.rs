@@ -1,11 +1,14 @@
 use crate::{path::AssetPath, LabelId};
 use bevy_utils::{HashMap, HashSet, Uuid};
-use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use serde::{de::IgnoredAny, Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SourceMeta {
     pub assets: Vec<AssetMeta>,
+    /// The hash of the source and processor settings the processed artifact was created from, see [AssetProcessor](crate::AssetProcessor)
+    #[serde(default)]
+    pub processed_hash: Option<u64>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -15,6 +18,33 @@ pub struct AssetMeta {
     pub type_uuid: Uuid,
 }
 
+/// The contents of the `.meta` file of an asset source, for example `texture.png.meta` for `texture.png`. It selects the
+/// loader of the source and carries settings for it, which loaders read with [LoadContext::settings](crate::LoadContext::settings).
+///
+/// ```ron
+/// (
+///     loader: Some("ImageTextureLoader"),
+///     settings: (srgb: false),
+/// )
+/// ```
+///
+/// `loader` is the type name of an [AssetLoader](crate::AssetLoader), with or without its module path. Loaders of
+/// different modules that have the same name can only be selected with their module path. Without it, the loader is
+/// selected by the extension of the source.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SourceSettings<T = IgnoredAny> {
+    pub loader: Option<String>,
+    pub settings: T,
+}
+
+/// The path of the `.meta` file of the file at `path`
+pub(crate) fn get_meta_path(path: &Path) -> PathBuf {
+    let mut meta_path = path.as_os_str().to_owned();
+    meta_path.push(".meta");
+    meta_path.into()
+}
+
 /// Info about a specific asset, such as its path and its current load state
 #[derive(Clone, Debug)]
 pub struct SourceInfo {
@@ -1,10 +1,12 @@
-use crate::{filesystem_watcher::FilesystemWatcher, AssetIo, AssetIoError, AssetServer};
+use crate::{
+    filesystem_watcher::FilesystemWatcher, AssetIo, AssetIoError, AssetServer, LayeredAssetIo,
+};
 use anyhow::Result;
 use bevy_ecs::{bevy_utils::BoxedFuture, Res};
 use bevy_utils::HashSet;
 use crossbeam_channel::TryRecvError;
 use fs::File;
-use io::Read;
+use io::{Read, Write};
 use parking_lot::RwLock;
 use std::{
     env, fs, io,
@@ -62,6 +64,21 @@ impl AssetIo for FileAssetIo {
         })
     }
 
+    fn save_path<'a>(
+        &'a self,
+        path: &'a Path,
+        bytes: &'a [u8],
+    ) -> BoxedFuture<'a, Result<(), AssetIoError>> {
+        Box::pin(async move {
+            let full_path = self.root_path.join(path);
+            if let Some(parent) = full_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            File::create(&full_path)?.write_all(bytes)?;
+            Ok(())
+        })
+    }
+
     fn read_directory(
         &self,
         path: &Path,
@@ -110,12 +127,23 @@ impl AssetIo for FileAssetIo {
 ))]
 pub fn filesystem_watcher_system(asset_server: Res<AssetServer>) {
     let mut changed = HashSet::default();
-    let asset_io =
-        if let Some(asset_io) = asset_server.server.asset_io.downcast_ref::<FileAssetIo>() {
+    // the FileAssetIo can also be a layer of a LayeredAssetIo
+    let asset_io = &*asset_server.server.asset_io;
+    let asset_io = if let Some(asset_io) = asset_io.downcast_ref::<FileAssetIo>() {
+        asset_io
+    } else if let Some(asset_io) = asset_io
+        .downcast_ref::<LayeredAssetIo>()
+        .and_then(|asset_io| {
             asset_io
-        } else {
-            return;
-        };
+                .layers
+                .iter()
+                .find_map(|layer| layer.downcast_ref::<FileAssetIo>())
+        })
+    {
+        asset_io
+    } else {
+        return;
+    };
     let watcher = asset_io.filesystem_watcher.read();
     if let Some(ref watcher) = *watcher {
         loop {
@@ -133,6 +161,12 @@ pub fn filesystem_watcher_system(asset_server: Res<AssetServer>) {
                 for path in paths.iter() {
                     if !changed.contains(path) {
                         let relative_path = path.strip_prefix(&asset_io.root_path).unwrap();
+                        // a changed .meta file reloads the source it configures
+                        let relative_path = if relative_path.extension() == Some("meta".as_ref()) {
+                            relative_path.with_extension("")
+                        } else {
+                            relative_path.to_owned()
+                        };
                         let _ = asset_server.load_untracked(relative_path, true);
                     }
                 }
@@ -0,0 +1,218 @@
+use crate::{AssetIo, AssetIoError};
+use anyhow::Result;
+use bevy_ecs::bevy_utils::BoxedFuture;
+use bevy_utils::HashSet;
+use std::path::{Path, PathBuf};
+
+/// An [AssetIo] that combines several others. Assets are read from the first layer that has them, so layers added
+/// first take precedence: add the platform default [AssetIo] (see
+/// [create_platform_default_asset_io](crate::create_platform_default_asset_io)) before a [MemoryAssetIo](crate::MemoryAssetIo)
+/// of embedded assets to let users override the assets shipped in the binary.
+///
+/// Assets are saved to the first layer that supports saving.
+#[derive(Default)]
+pub struct LayeredAssetIo {
+    pub layers: Vec<Box<dyn AssetIo>>,
+}
+
+impl LayeredAssetIo {
+    pub fn with_layer<T: AssetIo>(self, layer: T) -> Self {
+        self.with_boxed_layer(Box::new(layer))
+    }
+
+    pub fn with_boxed_layer(mut self, layer: Box<dyn AssetIo>) -> Self {
+        self.layers.push(layer);
+        self
+    }
+}
+
+impl AssetIo for LayeredAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            for layer in self.layers.iter() {
+                match layer.load_path(path).await {
+                    Err(AssetIoError::NotFound(_)) => continue,
+                    result => return result,
+                }
+            }
+            Err(AssetIoError::NotFound(path.to_owned()))
+        })
+    }
+
+    fn save_path<'a>(
+        &'a self,
+        path: &'a Path,
+        bytes: &'a [u8],
+    ) -> BoxedFuture<'a, Result<(), AssetIoError>> {
+        Box::pin(async move {
+            for layer in self.layers.iter() {
+                match layer.save_path(path, bytes).await {
+                    Err(AssetIoError::SaveNotSupported(_)) => continue,
+                    result => return result,
+                }
+            }
+            Err(AssetIoError::SaveNotSupported(path.to_owned()))
+        })
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        let mut children = HashSet::default();
+        let mut found = false;
+        for layer in self.layers.iter().filter(|layer| layer.is_directory(path)) {
+            children.extend(layer.read_directory(path)?);
+            found = true;
+        }
+        if found {
+            Ok(Box::new(children.into_iter()))
+        } else {
+            Err(AssetIoError::NotFound(path.to_owned()))
+        }
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        self.layers.iter().any(|layer| layer.is_directory(path))
+    }
+
+    fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+        // the path usually only exists in some of the layers, so watching it in the others is allowed to fail
+        for layer in self.layers.iter() {
+            let _ = layer.watch_path_for_changes(path);
+        }
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        for layer in self.layers.iter() {
+            layer.watch_for_changes()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LayeredAssetIo;
+    use crate::{AssetIo, AssetIoError, MemoryAssetIo};
+    use anyhow::Result;
+    use bevy_ecs::bevy_utils::BoxedFuture;
+    use futures_lite::future;
+    use std::path::{Path, PathBuf};
+
+    /// A layer that can't save, like the assets bundled in an Android package
+    struct ReadOnlyAssetIo(MemoryAssetIo);
+
+    impl AssetIo for ReadOnlyAssetIo {
+        fn load_path<'a>(
+            &'a self,
+            path: &'a Path,
+        ) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+            self.0.load_path(path)
+        }
+
+        fn read_directory(
+            &self,
+            path: &Path,
+        ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+            self.0.read_directory(path)
+        }
+
+        fn is_directory(&self, path: &Path) -> bool {
+            self.0.is_directory(path)
+        }
+
+        fn watch_path_for_changes(&self, path: &Path) -> Result<(), AssetIoError> {
+            self.0.watch_path_for_changes(path)
+        }
+
+        fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+            self.0.watch_for_changes()
+        }
+    }
+
+    fn load(asset_io: &impl AssetIo, path: &str) -> Result<Vec<u8>, AssetIoError> {
+        future::block_on(asset_io.load_path(Path::new(path)))
+    }
+
+    fn layer<T: AssetIo>(asset_io: &LayeredAssetIo, index: usize) -> &T {
+        asset_io.layers[index].downcast_ref::<T>().unwrap()
+    }
+
+    #[test]
+    fn load_falls_through_layers() {
+        let asset_io = LayeredAssetIo::default()
+            .with_layer(MemoryAssetIo::default().with_asset("a.txt", &b"overridden"[..]))
+            .with_layer(
+                MemoryAssetIo::default()
+                    .with_asset("a.txt", &b"a"[..])
+                    .with_asset("b.txt", &b"b"[..]),
+            );
+
+        assert_eq!(load(&asset_io, "a.txt").unwrap(), b"overridden");
+        assert_eq!(load(&asset_io, "b.txt").unwrap(), b"b");
+        assert!(matches!(
+            load(&asset_io, "c.txt"),
+            Err(AssetIoError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn read_directory_merges_layers() {
+        let asset_io = LayeredAssetIo::default()
+            .with_layer(
+                MemoryAssetIo::default()
+                    .with_asset("textures/a.png", &b""[..])
+                    .with_asset("textures/b.png", &b""[..]),
+            )
+            .with_layer(MemoryAssetIo::default().with_asset("sounds/a.ogg", &b""[..]))
+            .with_layer(
+                MemoryAssetIo::default()
+                    .with_asset("textures/b.png", &b""[..])
+                    .with_asset("textures/c.png", &b""[..]),
+            );
+
+        let mut children = asset_io
+            .read_directory(Path::new("textures"))
+            .unwrap()
+            .collect::<Vec<_>>();
+        children.sort();
+        assert_eq!(
+            children,
+            vec![
+                PathBuf::from("textures/a.png"),
+                PathBuf::from("textures/b.png"),
+                PathBuf::from("textures/c.png"),
+            ]
+        );
+        assert!(asset_io.is_directory(Path::new("sounds")));
+        assert!(matches!(
+            asset_io.read_directory(Path::new("models")),
+            Err(AssetIoError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn save_to_first_layer_that_supports_it() {
+        let asset_io = LayeredAssetIo::default()
+            .with_layer(ReadOnlyAssetIo(MemoryAssetIo::default()))
+            .with_layer(MemoryAssetIo::default())
+            .with_layer(MemoryAssetIo::default());
+
+        future::block_on(asset_io.save_path(Path::new("a.txt"), b"a")).unwrap();
+        assert!(load(layer::<ReadOnlyAssetIo>(&asset_io, 0), "a.txt").is_err());
+        assert_eq!(
+            load(layer::<MemoryAssetIo>(&asset_io, 1), "a.txt").unwrap(),
+            b"a"
+        );
+        assert!(load(layer::<MemoryAssetIo>(&asset_io, 2), "a.txt").is_err());
+
+        let read_only =
+            LayeredAssetIo::default().with_layer(ReadOnlyAssetIo(MemoryAssetIo::default()));
+        assert!(matches!(
+            future::block_on(read_only.save_path(Path::new("a.txt"), b"a")),
+            Err(AssetIoError::SaveNotSupported(_))
+        ));
+    }
+}
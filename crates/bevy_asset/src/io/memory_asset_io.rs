@@ -0,0 +1,193 @@
+use crate::{AssetIo, AssetIoError};
+use anyhow::Result;
+use bevy_ecs::bevy_utils::BoxedFuture;
+use bevy_utils::{HashMap, HashSet};
+use parking_lot::RwLock;
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+};
+
+/// An [AssetIo] backed by a map of paths to bytes, for example to bundle assets into the binary with
+/// [embedded_asset_io](crate::embedded_asset_io). Directories are implied by the paths of the assets.
+#[derive(Default)]
+pub struct MemoryAssetIo {
+    assets: RwLock<HashMap<PathBuf, Cow<'static, [u8]>>>,
+}
+
+impl MemoryAssetIo {
+    pub fn insert<P: Into<PathBuf>, B: Into<Cow<'static, [u8]>>>(&self, path: P, bytes: B) {
+        self.assets.write().insert(path.into(), bytes.into());
+    }
+
+    pub fn with_asset<P: Into<PathBuf>, B: Into<Cow<'static, [u8]>>>(
+        self,
+        path: P,
+        bytes: B,
+    ) -> Self {
+        self.insert(path, bytes);
+        self
+    }
+
+    pub fn remove<P: AsRef<Path>>(&self, path: P) -> Option<Cow<'static, [u8]>> {
+        self.assets.write().remove(path.as_ref())
+    }
+}
+
+impl AssetIo for MemoryAssetIo {
+    fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+        Box::pin(async move {
+            self.assets
+                .read()
+                .get(path)
+                .map(|bytes| bytes.to_vec())
+                .ok_or_else(|| AssetIoError::NotFound(path.to_owned()))
+        })
+    }
+
+    fn save_path<'a>(
+        &'a self,
+        path: &'a Path,
+        bytes: &'a [u8],
+    ) -> BoxedFuture<'a, Result<(), AssetIoError>> {
+        Box::pin(async move {
+            self.insert(path, bytes.to_vec());
+            Ok(())
+        })
+    }
+
+    fn read_directory(
+        &self,
+        path: &Path,
+    ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+        if !self.is_directory(path) {
+            return Err(AssetIoError::NotFound(path.to_owned()));
+        }
+
+        // the children of a directory are the first components of the asset paths below it
+        let mut children = HashSet::default();
+        for asset_path in self.assets.read().keys() {
+            if let Some(child) = asset_path
+                .strip_prefix(path)
+                .ok()
+                .and_then(|relative_path| relative_path.components().next())
+            {
+                children.insert(path.join(child));
+            }
+        }
+        Ok(Box::new(children.into_iter()))
+    }
+
+    fn is_directory(&self, path: &Path) -> bool {
+        self.assets
+            .read()
+            .keys()
+            .any(|asset_path| asset_path != path && asset_path.starts_with(path))
+    }
+
+    fn watch_path_for_changes(&self, _path: &Path) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+
+    fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+        Ok(())
+    }
+}
+
+/// Creates a [MemoryAssetIo] with files embedded into the binary at compile time. The first argument is the folder of
+/// the files, relative to the manifest of the crate, the others are the paths of the files in that folder, which are
+/// also the paths they are loaded from.
+///
+/// ```
+/// use bevy_asset::{embedded_asset_io, AssetIo};
+/// use std::path::Path;
+///
+/// // embeds `src/lib.rs` and `src/io/mod.rs` of this crate, loaded from `lib.rs` and `io/mod.rs`
+/// let asset_io = embedded_asset_io!("src", "lib.rs", "io/mod.rs");
+/// assert!(asset_io.is_directory(Path::new("io")));
+/// assert!(!asset_io.is_directory(Path::new("lib.rs")));
+/// ```
+#[macro_export]
+macro_rules! embedded_asset_io {
+    ($folder: literal, $($path: literal),* $(,)?) => {{
+        let asset_io = $crate::MemoryAssetIo::default();
+        $(
+            asset_io.insert(
+                $path,
+                &include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $folder, "/", $path))[..],
+            );
+        )*
+        asset_io
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryAssetIo;
+    use crate::{AssetIo, AssetIoError};
+    use futures_lite::future;
+    use std::path::{Path, PathBuf};
+
+    fn read_directory(asset_io: &impl AssetIo, path: &str) -> Vec<PathBuf> {
+        let mut children = asset_io
+            .read_directory(Path::new(path))
+            .unwrap()
+            .collect::<Vec<_>>();
+        children.sort();
+        children
+    }
+
+    #[test]
+    fn load_and_save() {
+        let asset_io = MemoryAssetIo::default().with_asset("a.txt", &b"a"[..]);
+        assert_eq!(
+            future::block_on(asset_io.load_path(Path::new("a.txt"))).unwrap(),
+            b"a"
+        );
+        assert!(matches!(
+            future::block_on(asset_io.load_path(Path::new("b.txt"))),
+            Err(AssetIoError::NotFound(_))
+        ));
+
+        future::block_on(asset_io.save_path(Path::new("b.txt"), b"b")).unwrap();
+        assert_eq!(
+            future::block_on(asset_io.load_path(Path::new("b.txt"))).unwrap(),
+            b"b"
+        );
+    }
+
+    #[test]
+    fn read_nested_directories() {
+        let asset_io = MemoryAssetIo::default()
+            .with_asset("root.txt", &b""[..])
+            .with_asset("textures/a.png", &b""[..])
+            .with_asset("textures/b.png", &b""[..])
+            .with_asset("textures/ui/button.png", &b""[..])
+            .with_asset("textures/ui/icons/close.png", &b""[..]);
+
+        assert!(asset_io.is_directory(Path::new("textures")));
+        assert!(asset_io.is_directory(Path::new("textures/ui/icons")));
+        assert!(!asset_io.is_directory(Path::new("textures/a.png")));
+        assert!(!asset_io.is_directory(Path::new("text")));
+
+        assert_eq!(
+            read_directory(&asset_io, "textures"),
+            vec![
+                PathBuf::from("textures/a.png"),
+                PathBuf::from("textures/b.png"),
+                PathBuf::from("textures/ui"),
+            ]
+        );
+        assert_eq!(
+            read_directory(&asset_io, "textures/ui"),
+            vec![
+                PathBuf::from("textures/ui/button.png"),
+                PathBuf::from("textures/ui/icons"),
+            ]
+        );
+        assert!(matches!(
+            asset_io.read_directory(Path::new("textures/a.png")),
+            Err(AssetIoError::NotFound(_))
+        ));
+    }
+}
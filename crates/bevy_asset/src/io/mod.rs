@@ -2,6 +2,8 @@
 mod android_asset_io;
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 mod file_asset_io;
+mod layered_asset_io;
+mod memory_asset_io;
 #[cfg(target_arch = "wasm32")]
 mod wasm_asset_io;
 
@@ -9,6 +11,8 @@ mod wasm_asset_io;
 pub use android_asset_io::*;
 #[cfg(all(not(target_arch = "wasm32"), not(target_os = "android")))]
 pub use file_asset_io::*;
+pub use layered_asset_io::*;
+pub use memory_asset_io::*;
 #[cfg(target_arch = "wasm32")]
 pub use wasm_asset_io::*;
 
@@ -30,11 +34,21 @@ pub enum AssetIoError {
     Io(#[from] io::Error),
     #[error("failed to watch path")]
     PathWatchError(PathBuf),
+    #[error("saving is not supported by this AssetIo")]
+    SaveNotSupported(PathBuf),
 }
 
 /// Handles load requests from an AssetServer
 pub trait AssetIo: Downcast + Send + Sync + 'static {
     fn load_path<'a>(&'a self, path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>>;
+    /// Writes `bytes` to `path`. Fails with [AssetIoError::SaveNotSupported] unless implemented.
+    fn save_path<'a>(
+        &'a self,
+        path: &'a Path,
+        _bytes: &'a [u8],
+    ) -> BoxedFuture<'a, Result<(), AssetIoError>> {
+        Box::pin(async move { Err(AssetIoError::SaveNotSupported(path.to_owned())) })
+    }
     fn read_directory(
         &self,
         path: &Path,
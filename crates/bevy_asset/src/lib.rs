@@ -11,6 +11,8 @@ mod info;
 mod io;
 mod loader;
 mod path;
+mod processor;
+mod saver;
 
 pub use asset_server::*;
 pub use assets::*;
@@ -22,6 +24,8 @@ pub use info::*;
 pub use io::*;
 pub use loader::*;
 pub use path::*;
+pub use processor::*;
+pub use saver::*;
 
 /// The names of asset stages in an App Schedule
 pub mod stage {
@@ -42,12 +46,15 @@ pub struct AssetPlugin;
 
 pub struct AssetServerSettings {
     pub asset_folder: String,
+    /// The folder, relative to `asset_folder`, where sources processed by an [AssetProcessor] are cached
+    pub imported_folder: String,
 }
 
 impl Default for AssetServerSettings {
     fn default() -> Self {
         Self {
             asset_folder: "assets".to_string(),
+            imported_folder: ".imported".to_string(),
         }
     }
 }
@@ -88,6 +95,16 @@ impl Plugin for AssetPlugin {
             app.add_resource(asset_server);
         }
 
+        let imported_folder = app
+            .resources_mut()
+            .get_or_insert_with(AssetServerSettings::default)
+            .imported_folder
+            .clone();
+        app.resources()
+            .get::<AssetServer>()
+            .unwrap()
+            .set_imported_folder(imported_folder);
+
         app.add_stage_before(
             bevy_app::stage::PRE_UPDATE,
             stage::LOAD_ASSETS,
@@ -99,9 +116,14 @@ impl Plugin for AssetPlugin {
             SystemStage::parallel(),
         )
         .register_type::<HandleId>()
+        .add_event::<AssetLoadEvent>()
         .add_system_to_stage(
             bevy_app::stage::PRE_UPDATE,
             asset_server::free_unused_assets_system.system(),
+        )
+        .add_system_to_stage(
+            stage::ASSET_EVENTS,
+            asset_server::asset_load_event_system.system(),
         );
 
         #[cfg(all(
@@ -1,6 +1,6 @@
 use crate::{
     path::AssetPath, AssetIo, AssetIoError, AssetMeta, AssetServer, Assets, Handle, HandleId,
-    RefChangeChannel,
+    RefChangeChannel, SourceSettings,
 };
 use anyhow::Result;
 use bevy_ecs::{Res, ResMut, Resource};
@@ -8,6 +8,7 @@ use bevy_reflect::{TypeUuid, TypeUuidDynamic};
 use bevy_utils::{BoxedFuture, HashMap};
 use crossbeam_channel::{Receiver, Sender};
 use downcast_rs::{impl_downcast, Downcast};
+use serde::de::DeserializeOwned;
 use std::path::Path;
 
 /// A loader for an asset source
@@ -59,6 +60,7 @@ pub struct LoadContext<'a> {
     pub(crate) labeled_assets: HashMap<Option<String>, LoadedAsset>,
     pub(crate) path: &'a Path,
     pub(crate) version: usize,
+    pub(crate) meta: Option<&'a [u8]>,
 }
 
 impl<'a> LoadContext<'a> {
@@ -67,6 +69,7 @@ impl<'a> LoadContext<'a> {
         ref_change_channel: &'a RefChangeChannel,
         asset_io: &'a dyn AssetIo,
         version: usize,
+        meta: Option<&'a [u8]>,
     ) -> Self {
         Self {
             ref_change_channel,
@@ -74,6 +77,7 @@ impl<'a> LoadContext<'a> {
             labeled_assets: Default::default(),
             version,
             path,
+            meta,
         }
     }
 
@@ -81,6 +85,15 @@ impl<'a> LoadContext<'a> {
         &self.path
     }
 
+    /// Returns the loader settings from the `.meta` file of the source, or the default settings if it has none. See
+    /// [SourceSettings].
+    pub fn settings<T: DeserializeOwned + Default>(&self) -> Result<T, ron::Error> {
+        match self.meta {
+            Some(meta) => Ok(ron::de::from_bytes::<SourceSettings<T>>(meta)?.settings),
+            None => Ok(T::default()),
+        }
+    }
+
     pub fn has_labeled_asset(&self, label: &str) -> bool {
         self.labeled_assets.contains_key(&Some(label.to_string()))
     }
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use bevy_utils::BoxedFuture;
+use std::path::{Path, PathBuf};
+
+/// Transforms asset sources before they are handed to their [AssetLoader](crate::AssetLoader), for example to compress
+/// textures or to precompile shaders.
+///
+/// Processed sources are cached in the imported folder of the [AssetServer](crate::AssetServer), see
+/// [AssetServer::set_imported_folder](crate::AssetServer::set_imported_folder). The cache is keyed by a hash of the
+/// source bytes and of [AssetProcessor::settings_hash], so sources are only processed again when one of them changes.
+pub trait AssetProcessor: Send + Sync + 'static {
+    fn process<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, anyhow::Error>>;
+    fn extensions(&self) -> &[&str];
+
+    /// The extension of the processed bytes, which selects the loader for them. Defaults to the extension of the source.
+    fn processed_extension(&self) -> Option<&str> {
+        None
+    }
+
+    /// A hash of the settings of this processor. Processed sources are invalidated when it changes.
+    fn settings_hash(&self) -> u64 {
+        0
+    }
+}
+
+/// The path of the processed artifact of the source at `path`, relative to the imported folder
+pub(crate) fn get_processed_path(path: &Path, processed_extension: Option<&str>) -> PathBuf {
+    match processed_extension {
+        Some(extension) => {
+            let mut processed_path = path.as_os_str().to_owned();
+            processed_path.push(".");
+            processed_path.push(extension);
+            processed_path.into()
+        }
+        None => path.to_owned(),
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{Asset, AssetDynamic};
+use anyhow::Result;
+use bevy_utils::BoxedFuture;
+use std::path::Path;
+
+/// A saver for an asset type. It serializes the asset into bytes that are written with [AssetIo::save_path](crate::AssetIo::save_path)
+pub trait AssetSaver: Send + Sync + 'static {
+    type Asset: Asset;
+
+    fn save<'a>(
+        &'a self,
+        asset: &'a Self::Asset,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, anyhow::Error>>;
+    fn extensions(&self) -> &[&str];
+}
+
+/// A type-erased [AssetSaver], used by the [AssetServer](crate::AssetServer) to store savers of all asset types
+pub(crate) trait AssetSaverDynamic: Send + Sync + 'static {
+    fn save_dynamic<'a>(
+        &'a self,
+        asset: &'a dyn AssetDynamic,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, anyhow::Error>>;
+    fn extensions(&self) -> &[&str];
+}
+
+impl<T: AssetSaver> AssetSaverDynamic for T {
+    fn save_dynamic<'a>(
+        &'a self,
+        asset: &'a dyn AssetDynamic,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Vec<u8>, anyhow::Error>> {
+        let asset = asset.downcast_ref::<T::Asset>().unwrap_or_else(|| {
+            panic!(
+                "Failed to downcast asset to {}.",
+                std::any::type_name::<T::Asset>()
+            )
+        });
+        self.save(asset, path)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        AssetSaver::extensions(self)
+    }
+}
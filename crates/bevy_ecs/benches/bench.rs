@@ -16,9 +16,11 @@
 
 use bencher::{benchmark_group, benchmark_main, Bencher};
 use bevy_ecs::*;
+use bevy_tasks::{BatchSize, ParallelIterator, TaskPool, TaskPoolBuilder};
 
 struct Position(f32);
 struct Velocity(f32);
+struct Selected;
 
 fn spawn_tuple(b: &mut Bencher) {
     let mut world = World::new();
@@ -83,12 +85,82 @@ fn build(b: &mut Bencher) {
     });
 }
 
+/// Adds and removes a marker component on 10k entities, which moves them between archetypes unless the marker is
+/// stored in a sparse set
+fn add_remove(b: &mut Bencher, storage_type: StorageType) {
+    let mut world = World::new();
+    world.set_storage_type::<Selected>(storage_type).unwrap();
+    let entities = (0..10_000)
+        .map(|i| world.spawn((Position(i as f32), Velocity(i as f32), [0.0f32; 16])))
+        .collect::<Vec<_>>();
+    b.iter(|| {
+        for entity in entities.iter() {
+            world.insert_one(*entity, Selected).unwrap();
+        }
+        for entity in entities.iter() {
+            world.remove_one::<Selected>(*entity).unwrap();
+        }
+    });
+}
+
+fn add_remove_table(b: &mut Bencher) {
+    add_remove(b, StorageType::Table);
+}
+
+fn add_remove_sparse_set(b: &mut Bencher) {
+    add_remove(b, StorageType::SparseSet);
+}
+
+/// Iterates in parallel over 100k entities split across a large and a small archetype, with some work per entity
+fn par_iterate_100k(b: &mut Bencher, batch_size: BatchSize) {
+    let mut world = World::new();
+    for i in 0..90_000 {
+        world.spawn((Position(-(i as f32)), Velocity(i as f32)));
+    }
+    for i in 0..10_000 {
+        world.spawn((Position(-(i as f32)), Velocity(i as f32), Selected));
+    }
+    let pool: TaskPool = TaskPoolBuilder::new().build();
+    b.iter(|| {
+        ParIter::new(world.query_batched_mut::<(&mut Position, &Velocity)>(batch_size)).for_each(
+            &pool,
+            |(mut pos, vel)| {
+                for _ in 0..10 {
+                    pos.0 = (pos.0 + vel.0).sqrt();
+                }
+            },
+        );
+    })
+}
+
+fn par_iterate_100k_fixed_64(b: &mut Bencher) {
+    par_iterate_100k(b, BatchSize::Fixed(64));
+}
+
+fn par_iterate_100k_fixed_1024(b: &mut Bencher) {
+    par_iterate_100k(b, BatchSize::Fixed(1024));
+}
+
+fn par_iterate_100k_fixed_16384(b: &mut Bencher) {
+    par_iterate_100k(b, BatchSize::Fixed(16384));
+}
+
+fn par_iterate_100k_auto(b: &mut Bencher) {
+    par_iterate_100k(b, BatchSize::Auto);
+}
+
 benchmark_group!(
     benches,
     spawn_tuple,
     spawn_static,
     spawn_batch,
     iterate_100k,
-    build
+    build,
+    add_remove_table,
+    add_remove_sparse_set,
+    par_iterate_100k_fixed_64,
+    par_iterate_100k_fixed_1024,
+    par_iterate_100k_fixed_16384,
+    par_iterate_100k_auto
 );
 benchmark_main!(benches);
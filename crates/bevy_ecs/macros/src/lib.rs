@@ -54,17 +54,7 @@ fn derive_bundle_(input: DeriveInput) -> Result<TokenStream2> {
         }
     };
     let (tys, field_members) = struct_fields(&data.fields);
-    let manifest = Manifest::new().unwrap();
-    let path_str = if let Some(package) = manifest.find(|name| name == "bevy") {
-        format!("{}::ecs", package.name)
-    } else if let Some(package) = manifest.find(|name| name == "bevy_internal") {
-        format!("{}::ecs", package.name)
-    } else if let Some(package) = manifest.find(|name| name == "bevy_ecs") {
-        package.name
-    } else {
-        "bevy_ecs".to_string()
-    };
-    let crate_path: Path = syn::parse(path_str.parse::<TokenStream>().unwrap()).unwrap();
+    let crate_path = bevy_ecs_path();
     let field_idents = member_as_idents(&field_members);
     let generics = add_additional_bounds_to_generic_params(&crate_path, input.generics);
 
@@ -87,6 +77,20 @@ fn derive_bundle_(input: DeriveInput) -> Result<TokenStream2> {
     Ok(ts)
 }
 
+fn bevy_ecs_path() -> Path {
+    let manifest = Manifest::new().unwrap();
+    let path_str = if let Some(package) = manifest.find(|name| name == "bevy") {
+        format!("{}::ecs", package.name)
+    } else if let Some(package) = manifest.find(|name| name == "bevy_internal") {
+        format!("{}::ecs", package.name)
+    } else if let Some(package) = manifest.find(|name| name == "bevy_ecs") {
+        package.name
+    } else {
+        "bevy_ecs".to_string()
+    };
+    syn::parse(path_str.parse::<TokenStream>().unwrap()).unwrap()
+}
+
 fn gen_dynamic_bundle_impl(
     crate_path: &syn::Path,
     ident: &syn::Ident,
@@ -97,7 +101,7 @@ fn gen_dynamic_bundle_impl(
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     quote! {
         impl #impl_generics ::#crate_path::DynamicBundle for #ident #ty_generics #where_clause {
-            fn with_ids<__hecs__T>(&self, f: impl ::std::ops::FnOnce(&[::std::any::TypeId]) -> __hecs__T) -> __hecs__T {
+            fn with_ids<__hecs__T>(&self, f: impl ::std::ops::FnOnce(&[::#crate_path::ComponentId]) -> __hecs__T) -> __hecs__T {
                 <Self as ::#crate_path::Bundle>::with_static_ids(f)
             }
 
@@ -106,9 +110,9 @@ fn gen_dynamic_bundle_impl(
             }
 
             #[allow(clippy::forget_copy)]
-            unsafe fn put(mut self, mut f: impl ::std::ops::FnMut(*mut u8, ::std::any::TypeId, usize) -> bool) {
+            unsafe fn put(mut self, mut f: impl ::std::ops::FnMut(*mut u8, ::#crate_path::ComponentId, usize) -> bool) {
                 #(
-                    if f((&mut self.#field_members as *mut #tys).cast::<u8>(), ::std::any::TypeId::of::<#tys>(), ::std::mem::size_of::<#tys>()) {
+                    if f((&mut self.#field_members as *mut #tys).cast::<u8>(), ::#crate_path::ComponentId::of::<#tys>(), ::std::mem::size_of::<#tys>()) {
                         #[allow(clippy::forget_copy)]
                         ::std::mem::forget(self.#field_members);
                     }
@@ -130,13 +134,13 @@ fn gen_bundle_impl(
     let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
     let with_static_ids_inner = quote! {
         {
-            let mut tys = [#((::std::mem::align_of::<#tys>(), ::std::any::TypeId::of::<#tys>())),*];
+            let mut tys = [#((::std::mem::align_of::<#tys>(), ::#crate_path::ComponentId::of::<#tys>())),*];
             tys.sort_unstable_by(|x, y| {
                 ::std::cmp::Ord::cmp(&x.0, &y.0)
                     .reverse()
                     .then(::std::cmp::Ord::cmp(&x.1, &y.1))
             });
-            let mut ids = [::std::any::TypeId::of::<()>(); #num_tys];
+            let mut ids = [::#crate_path::ComponentId::of::<()>(); #num_tys];
             for (id, info) in ::std::iter::Iterator::zip(ids.iter_mut(), tys.iter()) {
                 *id = info.1;
             }
@@ -146,7 +150,7 @@ fn gen_bundle_impl(
     let with_static_ids_body = if generics.params.is_empty() {
         quote! {
             ::#crate_path::lazy_static::lazy_static! {
-                static ref ELEMENTS: [::std::any::TypeId; #num_tys] = {
+                static ref ELEMENTS: [::#crate_path::ComponentId; #num_tys] = {
                     #with_static_ids_inner
                 };
             }
@@ -160,7 +164,7 @@ fn gen_bundle_impl(
     quote! {
         impl #impl_generics ::#crate_path::Bundle for #ident #ty_generics #where_clause {
             #[allow(non_camel_case_types)]
-            fn with_static_ids<__hecs__T>(f: impl ::std::ops::FnOnce(&[::std::any::TypeId]) -> __hecs__T) -> __hecs__T {
+            fn with_static_ids<__hecs__T>(f: impl ::std::ops::FnOnce(&[::#crate_path::ComponentId]) -> __hecs__T) -> __hecs__T {
                 #with_static_ids_body
             }
 
@@ -171,10 +175,10 @@ fn gen_bundle_impl(
             }
 
             unsafe fn get(
-                mut f: impl ::std::ops::FnMut(::std::any::TypeId, usize) -> ::std::option::Option<::std::ptr::NonNull<u8>>,
+                mut f: impl ::std::ops::FnMut(::#crate_path::ComponentId, usize) -> ::std::option::Option<::std::ptr::NonNull<u8>>,
             ) -> ::std::result::Result<Self, ::#crate_path::MissingComponent> {
                 #(
-                    let #field_idents = f(::std::any::TypeId::of::<#tys>(), ::std::mem::size_of::<#tys>())
+                    let #field_idents = f(::#crate_path::ComponentId::of::<#tys>(), ::std::mem::size_of::<#tys>())
                             .ok_or_else(::#crate_path::MissingComponent::new::<#tys>)?
                             .cast::<#tys>()
                             .as_ptr();
@@ -195,11 +199,11 @@ fn gen_unit_struct_bundle_impl(
     quote! {
         impl #impl_generics ::#crate_path::Bundle for #ident #ty_generics #where_clause {
             #[allow(non_camel_case_types)]
-            fn with_static_ids<__hecs__T>(f: impl ::std::ops::FnOnce(&[::std::any::TypeId]) -> __hecs__T) -> __hecs__T { f(&[]) }
+            fn with_static_ids<__hecs__T>(f: impl ::std::ops::FnOnce(&[::#crate_path::ComponentId]) -> __hecs__T) -> __hecs__T { f(&[]) }
             fn static_type_info() -> ::std::vec::Vec<::#crate_path::TypeInfo> { ::std::vec::Vec::new() }
 
             unsafe fn get(
-                f: impl ::std::ops::FnMut(::std::any::TypeId, usize) -> ::std::option::Option<::std::ptr::NonNull<u8>>,
+                f: impl ::std::ops::FnMut(::#crate_path::ComponentId, usize) -> ::std::option::Option<::std::ptr::NonNull<u8>>,
             ) -> Result<Self, ::#crate_path::MissingComponent> {
                 Ok(Self {/* for some reason this works for all unit struct variations */})
             }
@@ -438,3 +442,173 @@ pub fn derive_system_param(input: TokenStream) -> TokenStream {
         }
     })
 }
+
+static REQUIRE_ATTRIBUTE_NAME: &str = "require";
+
+/// Implement `RequiredComponents` for the component types listed in `#[require(...)]`, which must implement
+/// `FromResources` or `Default`.
+#[proc_macro_derive(RequiredComponents, attributes(require))]
+pub fn derive_required_components(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_required_components_(input) {
+        Ok(ts) => ts,
+        Err(e) => e.to_compile_error(),
+    }
+    .into()
+}
+
+fn derive_required_components_(input: DeriveInput) -> Result<TokenStream2> {
+    let ident = input.ident;
+    let crate_path = bevy_ecs_path();
+
+    let mut required = Vec::new();
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident(REQUIRE_ATTRIBUTE_NAME))
+    {
+        let types = attr.parse_args_with(
+            syn::punctuated::Punctuated::<syn::Type, syn::Token![,]>::parse_terminated,
+        )?;
+        required.extend(types);
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #crate_path::RequiredComponents for #ident #ty_generics #where_clause {
+            fn register_required(world: &mut #crate_path::World) {
+                #(world.require::<Self, #required>();)*
+            }
+        }
+    })
+}
+
+static MAP_ENTITIES_ATTRIBUTE_NAME: &str = "map_entities";
+
+/// Implement `MapEntities` by mapping the fields whose type mentions `Entity`, like `Entity`, `Option<Entity>`,
+/// `SmallVec<[Entity; 8]>` or `HashSet<Entity>`. Entities can be nested in `Option`, `Vec`, `VecDeque`, `SmallVec`,
+/// `HashSet` and arrays, other types that mention `Entity` are reported as errors.
+///
+/// Fields with other types that hold entities can be mapped with `#[map_entities]`, and fields can be skipped with
+/// `#[map_entities(ignore)]`. The types of the mapped fields must implement `MapEntities`.
+#[proc_macro_derive(MapEntities, attributes(map_entities))]
+pub fn derive_map_entities(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match derive_map_entities_(input) {
+        Ok(ts) => ts,
+        Err(e) => e.to_compile_error(),
+    }
+    .into()
+}
+
+fn derive_map_entities_(input: DeriveInput) -> Result<TokenStream2> {
+    let ident = input.ident;
+    let data = match input.data {
+        syn::Data::Struct(s) => s,
+        _ => {
+            return Err(Error::new_spanned(
+                ident,
+                "derive(MapEntities) does not support enums or unions",
+            ))
+        }
+    };
+    let crate_path = bevy_ecs_path();
+
+    let mut mapped_members = Vec::new();
+    for (index, field) in data.fields.iter().enumerate() {
+        let mut marked = None;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident(MAP_ENTITIES_ATTRIBUTE_NAME))
+        {
+            syn::custom_keyword!(ignore);
+            if attr.tokens.is_empty() {
+                marked = Some(true);
+            } else {
+                attr.parse_args_with(|input: ParseStream| {
+                    input.parse::<ignore>()?;
+                    marked = Some(false);
+                    Ok(())
+                })?;
+            }
+        }
+        let mapped = match marked {
+            Some(mapped) => mapped,
+            None if mentions_entity(&field.ty) => {
+                if !is_mappable(&field.ty) {
+                    return Err(Error::new_spanned(
+                        &field.ty,
+                        "derive(MapEntities) can't map the entities in this type. Implement `MapEntities` for it \
+                         and mark the field with `#[map_entities]`, or skip the field with `#[map_entities(ignore)]`",
+                    ));
+                }
+                true
+            }
+            None => false,
+        };
+        if mapped {
+            mapped_members.push(match &field.ident {
+                Some(ident) => syn::Member::Named(ident.clone()),
+                None => syn::Member::Unnamed(Index::from(index)),
+            });
+        }
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #crate_path::MapEntities for #ident #ty_generics #where_clause {
+            fn map_entities(
+                &mut self,
+                entity_map: &#crate_path::EntityMap,
+            ) -> ::std::result::Result<(), #crate_path::MapEntitiesError> {
+                #(#crate_path::MapEntities::map_entities(&mut self.#mapped_members, entity_map)?;)*
+                Ok(())
+            }
+        }
+    })
+}
+
+/// Whether `Entity` appears in `ty`, for example in `Option<Entity>`
+fn mentions_entity(ty: &syn::Type) -> bool {
+    fn tokens_mention_entity(tokens: TokenStream2) -> bool {
+        tokens.into_iter().any(|token| match token {
+            proc_macro2::TokenTree::Ident(ident) => ident == "Entity",
+            proc_macro2::TokenTree::Group(group) => tokens_mention_entity(group.stream()),
+            _ => false,
+        })
+    }
+    tokens_mention_entity(quote!(#ty))
+}
+
+/// Whether `ty` is `Entity`, or nests it in types that implement `MapEntities`, for example `Option<[Entity; 2]>`
+fn is_mappable(ty: &syn::Type) -> bool {
+    const CONTAINERS: &[&str] = &["Option", "Vec", "VecDeque", "SmallVec", "HashSet"];
+    match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => {
+            let segment = match type_path.path.segments.last() {
+                Some(segment) => segment,
+                None => return false,
+            };
+            match &segment.arguments {
+                syn::PathArguments::None => segment.ident == "Entity",
+                syn::PathArguments::AngleBracketed(arguments) => {
+                    CONTAINERS
+                        .iter()
+                        .any(|container| segment.ident == container)
+                        && arguments.args.iter().all(|argument| match argument {
+                            syn::GenericArgument::Type(ty) => {
+                                !mentions_entity(ty) || is_mappable(ty)
+                            }
+                            _ => true,
+                        })
+                }
+                syn::PathArguments::Parenthesized(_) => false,
+            }
+        }
+        syn::Type::Array(array) => is_mappable(&array.elem),
+        syn::Type::Group(group) => is_mappable(&group.elem),
+        syn::Type::Paren(paren) => is_mappable(&paren.elem),
+        _ => false,
+    }
+}
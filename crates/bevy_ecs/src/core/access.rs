@@ -1,7 +1,7 @@
 use bevy_utils::HashSet;
-use std::{any::TypeId, boxed::Box, hash::Hash, vec::Vec};
+use std::{boxed::Box, hash::Hash, vec::Vec};
 
-use super::{Archetype, World};
+use super::{Archetype, ComponentId, World};
 
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq)]
 pub enum Access {
@@ -13,7 +13,7 @@ pub enum Access {
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct ArchetypeComponent {
     pub archetype_index: u32,
-    pub component: TypeId,
+    pub component: ComponentId,
 }
 
 impl ArchetypeComponent {
@@ -21,12 +21,12 @@ impl ArchetypeComponent {
     pub fn new<T: 'static>(archetype_index: u32) -> Self {
         ArchetypeComponent {
             archetype_index,
-            component: TypeId::of::<T>(),
+            component: ComponentId::of::<T>(),
         }
     }
 
     #[inline]
-    pub fn new_ty(archetype_index: u32, component: TypeId) -> Self {
+    pub fn new_ty(archetype_index: u32, component: ComponentId) -> Self {
         ArchetypeComponent {
             archetype_index,
             component,
@@ -36,29 +36,29 @@ impl ArchetypeComponent {
 
 pub enum QueryAccess {
     None,
-    Read(TypeId, &'static str),
-    Write(TypeId, &'static str),
+    Read(ComponentId, &'static str),
+    Write(ComponentId, &'static str),
     Optional(Box<QueryAccess>),
-    With(TypeId, Box<QueryAccess>),
-    Without(TypeId, Box<QueryAccess>),
+    With(ComponentId, Box<QueryAccess>),
+    Without(ComponentId, Box<QueryAccess>),
     Union(Vec<QueryAccess>),
 }
 
 impl QueryAccess {
     pub fn read<T: 'static>() -> QueryAccess {
-        QueryAccess::Read(TypeId::of::<T>(), std::any::type_name::<T>())
+        QueryAccess::Read(ComponentId::of::<T>(), std::any::type_name::<T>())
     }
 
     pub fn write<T: 'static>() -> QueryAccess {
-        QueryAccess::Write(TypeId::of::<T>(), std::any::type_name::<T>())
+        QueryAccess::Write(ComponentId::of::<T>(), std::any::type_name::<T>())
     }
 
     pub fn with<T: 'static>(access: QueryAccess) -> QueryAccess {
-        QueryAccess::With(TypeId::of::<T>(), Box::new(access))
+        QueryAccess::With(ComponentId::of::<T>(), Box::new(access))
     }
 
     pub fn without<T: 'static>(access: QueryAccess) -> QueryAccess {
-        QueryAccess::Without(TypeId::of::<T>(), Box::new(access))
+        QueryAccess::Without(ComponentId::of::<T>(), Box::new(access))
     }
 
     pub fn optional(access: QueryAccess) -> QueryAccess {
@@ -81,7 +81,7 @@ impl QueryAccess {
         }
     }
 
-    pub fn get_type_name(&self, type_id: TypeId) -> Option<&'static str> {
+    pub fn get_type_name(&self, type_id: ComponentId) -> Option<&'static str> {
         match self {
             QueryAccess::None => None,
             QueryAccess::Read(current_type_id, name) => {
@@ -115,6 +115,8 @@ impl QueryAccess {
 
     /// Returns how this [QueryAccess] accesses the given `archetype`.
     /// If `type_access` is set, it will populate type access with the types this query reads/writes
+    ///
+    /// Components stored in sparse sets may belong to any entity, so they are considered part of every archetype.
     pub fn get_access(
         &self,
         archetype: &Archetype,
@@ -124,7 +126,7 @@ impl QueryAccess {
         match self {
             QueryAccess::None => Some(Access::None),
             QueryAccess::Read(ty, _) => {
-                if archetype.has_type(*ty) {
+                if archetype.has_type(*ty) || archetype.is_sparse(*ty) {
                     if let Some(type_access) = type_access {
                         type_access.add_read(ArchetypeComponent::new_ty(archetype_index, *ty));
                     }
@@ -134,7 +136,7 @@ impl QueryAccess {
                 }
             }
             QueryAccess::Write(ty, _) => {
-                if archetype.has_type(*ty) {
+                if archetype.has_type(*ty) || archetype.is_sparse(*ty) {
                     if let Some(type_access) = type_access {
                         type_access.add_write(ArchetypeComponent::new_ty(archetype_index, *ty));
                     }
@@ -156,7 +158,7 @@ impl QueryAccess {
                 }
             }
             QueryAccess::With(ty, query_access) => {
-                if archetype.has_type(*ty) {
+                if archetype.has_type(*ty) || archetype.is_sparse(*ty) {
                     query_access.get_access(archetype, archetype_index, type_access)
                 } else {
                     None
@@ -240,6 +242,20 @@ impl<T: Hash + Eq + PartialEq + Copy> TypeAccess<T> {
         self.reads_and_writes.intersection(&other.writes).next()
     }
 
+    /// Returns every type that one access writes and the other reads or writes, without duplicates
+    pub fn get_conflicts<'a>(&'a self, other: &'a TypeAccess<T>) -> Vec<&'a T> {
+        let mut conflicts = self
+            .writes
+            .intersection(&other.reads_and_writes)
+            .collect::<Vec<_>>();
+        for conflict in self.reads_and_writes.intersection(&other.writes) {
+            if !self.writes.contains(conflict) {
+                conflicts.push(conflict);
+            }
+        }
+        conflicts
+    }
+
     pub fn union(&mut self, other: &TypeAccess<T>) {
         self.writes.extend(&other.writes);
         self.reads.extend(&other.reads);
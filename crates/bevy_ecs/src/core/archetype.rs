@@ -23,8 +23,11 @@ use std::{
     collections::HashMap,
     mem,
     ptr::{self, NonNull},
+    sync::Arc,
 };
 
+use super::sparse_set::{ComponentSparseSet, SparseRows, SparseSets};
+
 /// A collection of entities having the same component types
 ///
 /// Accessing `Archetype`s is only required for complex dynamic scheduling. To manipulate entities,
@@ -32,7 +35,7 @@ use std::{
 #[derive(Debug)]
 pub struct Archetype {
     types: Vec<TypeInfo>,
-    state: TypeIdMap<TypeState>,
+    state: ComponentIdMap<TypeState>,
     len: usize,
     entities: Vec<Entity>,
     // UnsafeCell allows unique references into `data` to be constructed while shared references
@@ -40,6 +43,7 @@ pub struct Archetype {
     data: UnsafeCell<NonNull<u8>>,
     data_size: usize,
     grow_size: usize,
+    sparse_sets: Arc<SparseSets>,
 }
 
 impl Archetype {
@@ -68,6 +72,15 @@ impl Archetype {
 
     #[allow(missing_docs)]
     pub fn with_grow(types: Vec<TypeInfo>, grow_size: usize) -> Self {
+        Self::new_inner(types, grow_size, Arc::default())
+    }
+
+    /// Creates an archetype of a [World](crate::World), which shares its sparse sets
+    pub(crate) fn with_sparse_sets(types: Vec<TypeInfo>, sparse_sets: Arc<SparseSets>) -> Self {
+        Self::new_inner(types, 64, sparse_sets)
+    }
+
+    fn new_inner(types: Vec<TypeInfo>, grow_size: usize, sparse_sets: Arc<SparseSets>) -> Self {
         Self::assert_type_info(&types);
         let mut state = HashMap::with_capacity_and_hasher(types.len(), Default::default());
         for ty in &types {
@@ -81,6 +94,7 @@ impl Archetype {
             data: UnsafeCell::new(NonNull::dangling()),
             data_size: 0,
             grow_size,
+            sparse_sets,
         }
     }
 
@@ -102,23 +116,23 @@ impl Archetype {
     #[allow(missing_docs)]
     #[inline]
     pub fn has<T: Component>(&self) -> bool {
-        self.has_dynamic(TypeId::of::<T>())
+        self.has_dynamic(ComponentId::of::<T>())
     }
 
     #[allow(missing_docs)]
     #[inline]
-    pub fn has_type(&self, ty: TypeId) -> bool {
-        self.has_dynamic(ty)
+    pub fn has_type(&self, ty: impl Into<ComponentId>) -> bool {
+        self.has_dynamic(ty.into())
     }
 
-    pub(crate) fn has_dynamic(&self, id: TypeId) -> bool {
+    pub(crate) fn has_dynamic(&self, id: ComponentId) -> bool {
         self.state.contains_key(&id)
     }
 
     #[allow(missing_docs)]
     #[inline]
     pub fn get<T: Component>(&self) -> Option<NonNull<T>> {
-        let state = self.state.get(&TypeId::of::<T>())?;
+        let state = self.state.get(&ComponentId::of::<T>())?;
         Some(unsafe {
             NonNull::new_unchecked(
                 (*self.data.get()).as_ptr().add(state.offset).cast::<T>() as *mut T
@@ -129,7 +143,7 @@ impl Archetype {
     #[allow(missing_docs)]
     #[inline]
     pub fn get_with_type_state<T: Component>(&self) -> Option<(NonNull<T>, &TypeState)> {
-        let state = self.state.get(&TypeId::of::<T>())?;
+        let state = self.state.get(&ComponentId::of::<T>())?;
         Some(unsafe {
             (
                 NonNull::new_unchecked(
@@ -140,14 +154,82 @@ impl Archetype {
         })
     }
 
+    /// Returns the start of the `ty` components of this archetype, along with the start of their flags
+    #[inline]
+    pub(crate) fn get_column(
+        &self,
+        ty: ComponentId,
+    ) -> Option<(NonNull<u8>, NonNull<ComponentFlags>)> {
+        let state = self.state.get(&ty)?;
+        Some(unsafe {
+            (
+                NonNull::new_unchecked((*self.data.get()).as_ptr().add(state.offset)),
+                state.component_flags(),
+            )
+        })
+    }
+
+    /// Whether components of type `ty` are stored in sparse sets rather than in archetypes. Any entity of this
+    /// archetype may have such a component.
+    #[inline]
+    pub fn is_sparse(&self, ty: impl Into<ComponentId>) -> bool {
+        self.sparse_sets.contains(ty.into())
+    }
+
+    #[inline]
+    pub(crate) fn sparse_set(&self, ty: ComponentId) -> Option<&ComponentSparseSet> {
+        self.sparse_sets.get(ty)
+    }
+
+    /// Looks up the rows of this archetype starting at `offset` in the sparse set of `ty`, if that type uses one
+    ///
+    /// # Safety
+    /// `offset` must be in bounds
+    #[inline]
+    pub(crate) unsafe fn sparse_rows(&self, ty: ComponentId, offset: usize) -> Option<SparseRows> {
+        self.sparse_set(ty)
+            .map(|set| SparseRows::new(self.entities(), offset, set))
+    }
+
+    /// Returns the `T` component of the entity at `index` along with its flags, whether the component is stored in
+    /// this archetype or in a sparse set
+    ///
+    /// # Safety
+    /// `index` must be in bounds
+    pub(crate) unsafe fn get_component<T: Component>(
+        &self,
+        index: usize,
+    ) -> Option<(NonNull<T>, NonNull<ComponentFlags>)> {
+        if let Some((components, type_state)) = self.get_with_type_state::<T>() {
+            return Some((
+                NonNull::new_unchecked(components.as_ptr().add(index)),
+                NonNull::new_unchecked(type_state.component_flags().as_ptr().add(index)),
+            ));
+        }
+        let entity = *self.entities.get(index)?;
+        self.sparse_set(ComponentId::of::<T>())?
+            .get(entity)
+            .map(|(component, flags)| (component.cast::<T>(), flags))
+    }
+
+    /// The runtime borrow state of the `T` components of this archetype
+    pub(crate) fn component_borrow<T: Component>(&self) -> Option<&AtomicBorrow> {
+        match self.state.get(&ComponentId::of::<T>()) {
+            Some(type_state) => Some(&type_state.borrow),
+            None => self
+                .sparse_set(ComponentId::of::<T>())
+                .map(|set| set.borrow()),
+        }
+    }
+
     #[allow(missing_docs)]
-    pub fn get_type_state(&self, ty: TypeId) -> Option<&TypeState> {
-        self.state.get(&ty)
+    pub fn get_type_state(&self, ty: impl Into<ComponentId>) -> Option<&TypeState> {
+        self.state.get(&ty.into())
     }
 
     #[allow(missing_docs)]
-    pub fn get_type_state_mut(&mut self, ty: TypeId) -> Option<&mut TypeState> {
-        self.state.get_mut(&ty)
+    pub fn get_type_state_mut(&mut self, ty: impl Into<ComponentId>) -> Option<&mut TypeState> {
+        self.state.get_mut(&ty.into())
     }
 
     #[allow(missing_docs)]
@@ -155,7 +237,7 @@ impl Archetype {
     pub fn borrow<T: Component>(&self) {
         if self
             .state
-            .get(&TypeId::of::<T>())
+            .get(&ComponentId::of::<T>())
             .map_or(false, |x| !x.borrow.borrow())
         {
             panic!("{} already borrowed uniquely.", type_name::<T>());
@@ -167,7 +249,7 @@ impl Archetype {
     pub fn borrow_mut<T: Component>(&self) {
         if self
             .state
-            .get(&TypeId::of::<T>())
+            .get(&ComponentId::of::<T>())
             .map_or(false, |x| !x.borrow.borrow_mut())
         {
             panic!("{} already borrowed.", type_name::<T>());
@@ -177,7 +259,7 @@ impl Archetype {
     #[allow(missing_docs)]
     #[inline]
     pub fn release<T: Component>(&self) {
-        if let Some(x) = self.state.get(&TypeId::of::<T>()) {
+        if let Some(x) = self.state.get(&ComponentId::of::<T>()) {
             x.borrow.release();
         }
     }
@@ -185,7 +267,7 @@ impl Archetype {
     #[allow(missing_docs)]
     #[inline]
     pub fn release_mut<T: Component>(&self) {
-        if let Some(x) = self.state.get(&TypeId::of::<T>()) {
+        if let Some(x) = self.state.get(&ComponentId::of::<T>()) {
             x.borrow.release_mut();
         }
     }
@@ -225,7 +307,7 @@ impl Archetype {
     /// `index` must be in-bounds
     pub(crate) unsafe fn get_dynamic(
         &self,
-        ty: TypeId,
+        ty: ComponentId,
         size: usize,
         index: usize,
     ) -> Option<NonNull<u8>> {
@@ -365,7 +447,7 @@ impl Archetype {
     pub(crate) unsafe fn move_to(
         &mut self,
         index: usize,
-        mut f: impl FnMut(*mut u8, TypeId, usize, ComponentFlags),
+        mut f: impl FnMut(*mut u8, ComponentId, usize, ComponentFlags),
     ) -> Option<Entity> {
         let last = self.len - 1;
         for ty in &self.types {
@@ -407,7 +489,7 @@ impl Archetype {
     pub unsafe fn put_dynamic(
         &mut self,
         component: *mut u8,
-        ty: TypeId,
+        ty: ComponentId,
         size: usize,
         index: usize,
         flags: ComponentFlags,
@@ -476,10 +558,43 @@ impl TypeState {
     }
 }
 
+/// Identifies a kind of component. Rust types are identified by their [TypeId], while components whose layout is
+/// only known at runtime are identified by the id [World::register_component](crate::World::register_component)
+/// gave them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub enum ComponentId {
+    RustTypeId(TypeId),
+    /// A component registered at runtime. These ids are only meaningful to the [World](crate::World) that
+    /// registered them.
+    ExternalId(u64),
+}
+
+impl ComponentId {
+    #[inline]
+    pub fn of<T: 'static>() -> Self {
+        ComponentId::RustTypeId(TypeId::of::<T>())
+    }
+
+    /// The [TypeId] of the component, if it is a Rust type
+    #[inline]
+    pub fn type_id(&self) -> Option<TypeId> {
+        match self {
+            ComponentId::RustTypeId(type_id) => Some(*type_id),
+            ComponentId::ExternalId(_) => None,
+        }
+    }
+}
+
+impl From<TypeId> for ComponentId {
+    fn from(type_id: TypeId) -> Self {
+        ComponentId::RustTypeId(type_id)
+    }
+}
+
 /// Metadata required to store a component
 #[derive(Debug, Copy, Clone)]
 pub struct TypeInfo {
-    id: TypeId,
+    id: ComponentId,
     layout: Layout,
     drop: unsafe fn(*mut u8),
     type_name: &'static str,
@@ -493,16 +608,31 @@ impl TypeInfo {
         }
 
         Self {
-            id: TypeId::of::<T>(),
+            id: ComponentId::of::<T>(),
             layout: Layout::new::<T>(),
             drop: drop_ptr::<T>,
             type_name: core::any::type_name::<T>(),
         }
     }
 
+    /// Metadata for a component registered at runtime
+    pub(crate) fn external(
+        id: u64,
+        layout: Layout,
+        drop: unsafe fn(*mut u8),
+        type_name: &'static str,
+    ) -> Self {
+        Self {
+            id: ComponentId::ExternalId(id),
+            layout,
+            drop,
+            type_name,
+        }
+    }
+
     #[allow(missing_docs)]
     #[inline]
-    pub fn id(&self) -> TypeId {
+    pub fn id(&self) -> ComponentId {
         self.id
     }
 
@@ -530,7 +660,7 @@ impl PartialOrd for TypeInfo {
 }
 
 impl Ord for TypeInfo {
-    /// Order by alignment, descending. Ties broken with TypeId.
+    /// Order by alignment, descending. Ties broken with ComponentId.
     fn cmp(&self, other: &Self) -> core::cmp::Ordering {
         self.layout
             .align()
@@ -553,10 +683,11 @@ fn align(x: usize, alignment: usize) -> usize {
     (x + alignment - 1) & (!alignment + 1)
 }
 
-/// A hasher optimized for hashing a single TypeId.
+/// A hasher optimized for hashing a single ComponentId.
 ///
 /// We don't use RandomState from std or Random state from Ahash
 /// because fxhash is [proved to be faster](https://github.com/bevyengine/bevy/pull/1119#issuecomment-751361215)
 /// and we don't need Hash Dos attack protection here
-/// since TypeIds generated during compilation and there is no reason to user attack himself.
-pub(crate) type TypeIdMap<V> = HashMap<TypeId, V, fxhash::FxBuildHasher>;
+/// since ComponentIds are generated during compilation or by the world itself and there is no reason to user
+/// attack himself.
+pub(crate) type ComponentIdMap<V> = HashMap<ComponentId, V, fxhash::FxBuildHasher>;
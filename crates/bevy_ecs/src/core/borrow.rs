@@ -16,6 +16,7 @@
 
 use crate::{Archetype, Component, ComponentFlags, MissingComponent};
 use core::{
+    any::type_name,
     fmt::Debug,
     ops::{Deref, DerefMut},
     sync::atomic::{AtomicUsize, Ordering},
@@ -72,7 +73,7 @@ const UNIQUE_BIT: usize = !(usize::max_value() >> 1);
 /// Shared borrow of an entity's component
 #[derive(Clone)]
 pub struct Ref<'a, T: Component> {
-    archetype: &'a Archetype,
+    borrow: &'a AtomicBorrow,
     target: &'a T,
 }
 
@@ -83,13 +84,16 @@ impl<'a, T: Component> Ref<'a, T> {
     ///
     /// - the index of the component must be valid
     pub unsafe fn new(archetype: &'a Archetype, index: usize) -> Result<Self, MissingComponent> {
-        let target = archetype
-            .get::<T>()
+        let (target, _) = archetype
+            .get_component::<T>(index)
             .ok_or_else(MissingComponent::new::<T>)?;
-        archetype.borrow::<T>();
+        let borrow = archetype.component_borrow::<T>().unwrap();
+        if !borrow.borrow() {
+            panic!("{} already borrowed uniquely.", type_name::<T>());
+        }
         Ok(Self {
-            archetype,
-            target: &*target.as_ptr().add(index as usize),
+            borrow,
+            target: &*target.as_ptr(),
         })
     }
 }
@@ -99,7 +103,7 @@ unsafe impl<T: Component> Sync for Ref<'_, T> {}
 
 impl<'a, T: Component> Drop for Ref<'a, T> {
     fn drop(&mut self) {
-        self.archetype.release::<T>();
+        self.borrow.release();
     }
 }
 
@@ -122,7 +126,7 @@ where
 
 /// Unique borrow of an entity's component
 pub struct RefMut<'a, T: Component> {
-    archetype: &'a Archetype,
+    borrow: &'a AtomicBorrow,
     target: &'a mut T,
     flags: &'a mut ComponentFlags,
 }
@@ -134,14 +138,17 @@ impl<'a, T: Component> RefMut<'a, T> {
     ///
     /// - the index of the component must be valid
     pub unsafe fn new(archetype: &'a Archetype, index: usize) -> Result<Self, MissingComponent> {
-        let (target, type_state) = archetype
-            .get_with_type_state::<T>()
+        let (target, flags) = archetype
+            .get_component::<T>(index)
             .ok_or_else(MissingComponent::new::<T>)?;
-        archetype.borrow_mut::<T>();
+        let borrow = archetype.component_borrow::<T>().unwrap();
+        if !borrow.borrow_mut() {
+            panic!("{} already borrowed.", type_name::<T>());
+        }
         Ok(Self {
-            archetype,
-            target: &mut *target.as_ptr().add(index),
-            flags: &mut *type_state.component_flags().as_ptr().add(index),
+            borrow,
+            target: &mut *target.as_ptr(),
+            flags: &mut *flags.as_ptr(),
         })
     }
 }
@@ -151,7 +158,7 @@ unsafe impl<T: Component> Sync for RefMut<'_, T> {}
 
 impl<'a, T: Component> Drop for RefMut<'a, T> {
     fn drop(&mut self) {
-        self.archetype.release_mut::<T>();
+        self.borrow.release_mut();
     }
 }
 
@@ -0,0 +1,559 @@
+use crate::{
+    Archetype, ComponentError, ComponentFlags, ComponentId, Entity, EntityBuilder,
+    MissingComponent, NoSuchEntity, TypeInfo, World,
+};
+use bevy_utils::HashSet;
+use std::{alloc::Layout, ptr::NonNull, slice};
+
+use super::sparse_set::SparseRows;
+
+/// Describes a component whose layout is only known at runtime, for example because it is defined by a script.
+/// See [World::register_component].
+#[derive(Debug, Clone)]
+pub struct ComponentDescriptor {
+    name: String,
+    layout: Layout,
+    drop: Option<unsafe fn(*mut u8)>,
+}
+
+impl ComponentDescriptor {
+    /// Describes components named `name` with the given size and alignment. They are not dropped, use
+    /// [ComponentDescriptor::with_drop] if they own resources.
+    pub fn new(name: impl Into<String>, layout: Layout) -> Self {
+        Self {
+            name: name.into(),
+            layout,
+            drop: None,
+        }
+    }
+
+    /// Sets the function that drops a component in place when it is removed, replaced or despawned
+    pub fn with_drop(mut self, drop: unsafe fn(*mut u8)) -> Self {
+        self.drop = Some(drop);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn layout(&self) -> Layout {
+        self.layout
+    }
+}
+
+unsafe fn drop_nothing(_: *mut u8) {}
+
+impl World {
+    /// Registers a component whose layout is only known at runtime, and returns the id that identifies it in this
+    /// world. Such components are added with [World::insert_dynamic] or [EntityBuilder::add_dynamic], and iterated
+    /// over with a [DynamicQuery].
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_ecs::*;
+    /// use std::alloc::Layout;
+    ///
+    /// let mut world = World::new();
+    /// let health = world.register_component(ComponentDescriptor::new("Health", Layout::new::<u32>()));
+    /// let e = world.spawn((1.0f32,));
+    /// unsafe { world.insert_dynamic(e, health, &100u32.to_ne_bytes()).unwrap() };
+    ///
+    /// let query = DynamicQuery::new().read(health);
+    /// for (entity, components) in world.query_dynamic(&query) {
+    ///     let health = unsafe { *components[0].cast::<u32>().as_ptr() };
+    ///     assert_eq!((entity, health), (e, 100));
+    /// }
+    /// ```
+    pub fn register_component(&mut self, descriptor: ComponentDescriptor) -> ComponentId {
+        let id = self.external_components.len() as u64;
+        // type names are `&'static str` everywhere else. components are registered once and live as long as the
+        // world, so leaking the name is fine
+        let name: &'static str = Box::leak(descriptor.name.into_boxed_str());
+        self.external_components.push(TypeInfo::external(
+            id,
+            descriptor.layout,
+            descriptor.drop.unwrap_or(drop_nothing),
+            name,
+        ));
+        ComponentId::ExternalId(id)
+    }
+
+    /// Returns the metadata of the component `id`, if it was registered with [World::register_component] or is a
+    /// Rust type currently stored in this world
+    pub fn type_info(&self, id: ComponentId) -> Option<TypeInfo> {
+        match id {
+            ComponentId::ExternalId(index) => self.external_components.get(index as usize).copied(),
+            ComponentId::RustTypeId(_) => self
+                .archetypes
+                .iter()
+                .flat_map(|archetype| archetype.types())
+                .find(|info| info.id() == id)
+                .copied()
+                .or_else(|| self.sparse_sets.get(id).map(|set| *set.type_info())),
+        }
+    }
+
+    /// Adds the component `id` to `entity`, copying its value from `data`. See [World::insert].
+    ///
+    /// # Safety
+    /// `data` must hold a valid value of the component
+    ///
+    /// # Panics
+    /// Panics if the component is unknown to this world (see [World::type_info]), or if the length of `data` is not
+    /// its size
+    pub unsafe fn insert_dynamic(
+        &mut self,
+        entity: Entity,
+        id: ComponentId,
+        data: &[u8],
+    ) -> Result<(), NoSuchEntity> {
+        let info = self
+            .type_info(id)
+            .unwrap_or_else(|| panic!("Component {:?} is not registered in this world.", id));
+        let mut builder = EntityBuilder::new();
+        builder.add_dynamic(info, data);
+        self.insert(entity, builder.build())
+    }
+
+    /// Returns a pointer to the component `id` of `entity`. It may only be read or written while the world is not
+    /// otherwise borrowed.
+    pub fn get_dynamic(
+        &self,
+        entity: Entity,
+        id: ComponentId,
+    ) -> Result<NonNull<u8>, ComponentError> {
+        let location = self
+            .get_entity_location(entity)
+            .ok_or(ComponentError::NoSuchEntity)?;
+        let missing = || {
+            let name = self
+                .type_info(id)
+                .map_or("<unknown component>", |info| info.type_name());
+            ComponentError::MissingComponent(MissingComponent::named(name))
+        };
+        if location.index == usize::MAX {
+            // pending entities have no components
+            return Err(missing());
+        }
+        let archetype = &self.archetypes[location.archetype as usize];
+        if let Some(set) = archetype.sparse_set(id) {
+            return set
+                .get(entity)
+                .map(|(component, _)| component)
+                .ok_or_else(missing);
+        }
+        let info = archetype
+            .types()
+            .iter()
+            .find(|info| info.id() == id)
+            .ok_or_else(missing)?;
+        // SAFE: the location of a live entity is in bounds
+        Ok(unsafe {
+            archetype
+                .get_dynamic(id, info.layout().size(), location.index)
+                .unwrap()
+        })
+    }
+
+    /// Removes and drops the component `id` of `entity`. See [World::remove].
+    pub fn remove_dynamic(
+        &mut self,
+        entity: Entity,
+        id: ComponentId,
+    ) -> Result<(), ComponentError> {
+        self.flush();
+        self.get_dynamic(entity, id)?;
+        self.run_remove_hooks(entity, &[id]);
+        let component = self.get_dynamic(entity, id)?;
+        let mut to_remove = HashSet::default();
+        to_remove.insert(id);
+        self.remove_sparse_components(entity, &mut to_remove, true);
+        if to_remove.is_empty() {
+            return Ok(());
+        }
+        // the component is not moved to the new archetype of the entity, so it can be dropped right away
+        let info = self.type_info(id).unwrap();
+        unsafe { info.drop(component.as_ptr()) };
+        self.remove_bundle_internal(entity, to_remove)
+    }
+
+    /// Iterates over the entities that match `query`, along with pointers to their fetched components
+    pub fn query_dynamic<'w>(&'w mut self, query: &'w DynamicQuery) -> DynamicQueryIter<'w> {
+        // SAFE: unique mutable access
+        unsafe { self.query_dynamic_unchecked(query) }
+    }
+
+    /// Same as [World::query_dynamic], but only borrows the world immutably
+    ///
+    /// # Safety
+    /// The components written by `query` must not be accessed by anything else while they are iterated over
+    pub unsafe fn query_dynamic_unchecked<'w>(
+        &'w self,
+        query: &'w DynamicQuery,
+    ) -> DynamicQueryIter<'w> {
+        DynamicQueryIter {
+            query,
+            archetypes: self.archetypes.iter(),
+            current: None,
+        }
+    }
+}
+
+/// How a [DynamicQuery] accesses a component
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum DynamicFetch {
+    Read(ComponentId),
+    /// Components fetched for writing are flagged as mutated when they are fetched
+    Write(ComponentId),
+}
+
+impl DynamicFetch {
+    pub fn id(&self) -> ComponentId {
+        match self {
+            DynamicFetch::Read(id) | DynamicFetch::Write(id) => *id,
+        }
+    }
+}
+
+/// A query built at runtime from [ComponentId]s, for components registered with [World::register_component]
+/// as well as Rust types. Run it with [World::query_dynamic].
+///
+/// The iterator yields the pointers to the fetched components in the order they were added to the query. A
+/// `bevy_reflect` `ReflectFromPtr` can turn them into reflected values.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicQuery {
+    fetches: Vec<DynamicFetch>,
+    with: Vec<ComponentId>,
+    without: Vec<ComponentId>,
+}
+
+impl DynamicQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fetches the component `id` for reading
+    pub fn read(mut self, id: ComponentId) -> Self {
+        self.fetches.push(DynamicFetch::Read(id));
+        self
+    }
+
+    /// Fetches the component `id` for writing
+    pub fn write(mut self, id: ComponentId) -> Self {
+        self.fetches.push(DynamicFetch::Write(id));
+        self
+    }
+
+    /// Only matches entities that have the component `id`, without fetching it
+    pub fn with(mut self, id: ComponentId) -> Self {
+        self.with.push(id);
+        self
+    }
+
+    /// Only matches entities that don't have the component `id`
+    pub fn without(mut self, id: ComponentId) -> Self {
+        self.without.push(id);
+        self
+    }
+
+    pub fn fetches(&self) -> &[DynamicFetch] {
+        &self.fetches
+    }
+
+    fn matches_archetype(&self, archetype: &Archetype) -> bool {
+        let has = |id: &ComponentId| archetype.has_type(*id) || archetype.is_sparse(*id);
+        self.fetches.iter().map(DynamicFetch::id).all(|id| has(&id))
+            && self.with.iter().all(has)
+            && !self.without.iter().any(|id| archetype.has_type(*id))
+    }
+}
+
+/// Where the components of one [DynamicFetch] are stored for the current archetype
+enum DynamicColumn {
+    Table {
+        data: NonNull<u8>,
+        flags: NonNull<ComponentFlags>,
+        size: usize,
+    },
+    SparseSet(SparseRows),
+}
+
+struct DynamicArchetype<'w> {
+    archetype: &'w Archetype,
+    index: usize,
+    columns: Vec<DynamicColumn>,
+    /// Sparse sets that must contain the entity, and sparse sets that must not
+    with: Vec<SparseRows>,
+    without: Vec<SparseRows>,
+}
+
+impl<'w> DynamicArchetype<'w> {
+    fn new(archetype: &'w Archetype, query: &DynamicQuery) -> Self {
+        // SAFE: rows are only looked up for indices below the length of the archetype
+        let sparse_rows = |id| unsafe { archetype.sparse_rows(id, 0) };
+        let columns = query
+            .fetches
+            .iter()
+            .map(|fetch| match archetype.get_column(fetch.id()) {
+                Some((data, flags)) => DynamicColumn::Table {
+                    data,
+                    flags,
+                    size: archetype
+                        .types()
+                        .iter()
+                        .find(|info| info.id() == fetch.id())
+                        .unwrap()
+                        .layout()
+                        .size(),
+                },
+                None => DynamicColumn::SparseSet(sparse_rows(fetch.id()).unwrap()),
+            })
+            .collect();
+        Self {
+            archetype,
+            index: 0,
+            columns,
+            with: query
+                .with
+                .iter()
+                .filter_map(|id| sparse_rows(*id))
+                .collect(),
+            without: query
+                .without
+                .iter()
+                .filter_map(|id| sparse_rows(*id))
+                .collect(),
+        }
+    }
+
+    /// Returns the components fetched from row `index`, if the entity in that row matches
+    ///
+    /// # Safety
+    /// `index` must be in bounds
+    unsafe fn fetch(&self, fetches: &[DynamicFetch], index: usize) -> Option<Vec<NonNull<u8>>> {
+        if !self.with.iter().all(|rows| rows.contains(index))
+            || self.without.iter().any(|rows| rows.contains(index))
+        {
+            return None;
+        }
+        let mut components = Vec::with_capacity(self.columns.len());
+        for (fetch, column) in fetches.iter().zip(self.columns.iter()) {
+            let (component, flags) = match column {
+                DynamicColumn::Table { data, flags, size } => (
+                    NonNull::new_unchecked(data.as_ptr().add(index * size)),
+                    NonNull::new_unchecked(flags.as_ptr().add(index)),
+                ),
+                DynamicColumn::SparseSet(rows) => rows.get(index)?,
+            };
+            if let DynamicFetch::Write(_) = fetch {
+                (*flags.as_ptr()).insert(ComponentFlags::MUTATED);
+            }
+            components.push(component);
+        }
+        Some(components)
+    }
+}
+
+/// Iterator over the entities matched by a [DynamicQuery], see [World::query_dynamic]
+pub struct DynamicQueryIter<'w> {
+    query: &'w DynamicQuery,
+    archetypes: slice::Iter<'w, Archetype>,
+    current: Option<DynamicArchetype<'w>>,
+}
+
+impl<'w> Iterator for DynamicQueryIter<'w> {
+    type Item = (Entity, Vec<NonNull<u8>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let current = match &mut self.current {
+                Some(current) => current,
+                None => {
+                    let archetype = self.archetypes.next()?;
+                    if archetype.is_empty() || !self.query.matches_archetype(archetype) {
+                        continue;
+                    }
+                    self.current
+                        .get_or_insert(DynamicArchetype::new(archetype, self.query))
+                }
+            };
+            if current.index == current.archetype.len() {
+                self.current = None;
+                continue;
+            }
+            let index = current.index;
+            current.index += 1;
+            // SAFE: the index is below the length of the archetype
+            if let Some(components) = unsafe { current.fetch(&self.query.fetches, index) } {
+                return Some((current.archetype.get_entity(index), components));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ComponentDescriptor, DynamicQuery};
+    use crate::{ComponentError, ComponentId, EntityBuilder, StorageType, World};
+    use std::{
+        alloc::Layout,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    fn position() -> ComponentDescriptor {
+        ComponentDescriptor::new("Position", Layout::from_size_align(8, 4).unwrap())
+    }
+
+    fn position_bytes(x: f32, y: f32) -> Vec<u8> {
+        x.to_ne_bytes()
+            .iter()
+            .chain(y.to_ne_bytes().iter())
+            .copied()
+            .collect()
+    }
+
+    fn read_position(components: &[std::ptr::NonNull<u8>], index: usize) -> [f32; 2] {
+        unsafe {
+            components[index]
+                .cast::<[f32; 2]>()
+                .as_ptr()
+                .read_unaligned()
+        }
+    }
+
+    #[test]
+    fn dynamic_components() {
+        let mut world = World::new();
+        let position = world.register_component(position());
+        let a = world.spawn((1u32,));
+        let b = world.spawn((2u32, true));
+        unsafe {
+            world
+                .insert_dynamic(a, position, &position_bytes(1.0, 2.0))
+                .unwrap();
+            world
+                .insert_dynamic(b, position, &position_bytes(3.0, 4.0))
+                .unwrap();
+        }
+        assert_eq!(world.type_info(position).unwrap().type_name(), "Position");
+        assert!(world.has_component_type(a, position));
+        assert_eq!(*world.get::<u32>(a).unwrap(), 1);
+
+        let ptr = world.get_dynamic(b, position).unwrap();
+        assert_eq!(
+            unsafe { ptr.cast::<[f32; 2]>().as_ptr().read_unaligned() },
+            [3.0, 4.0]
+        );
+
+        world.remove_dynamic(a, position).unwrap();
+        assert!(!world.has_component_type(a, position));
+        assert!(matches!(
+            world.get_dynamic(a, position),
+            Err(ComponentError::MissingComponent(_))
+        ));
+        assert_eq!(*world.get::<u32>(a).unwrap(), 1);
+    }
+
+    #[test]
+    fn dynamic_query() {
+        let mut world = World::new();
+        let position = world.register_component(position());
+        let velocity = world.register_component(ComponentDescriptor::new(
+            "Velocity",
+            Layout::from_size_align(8, 4).unwrap(),
+        ));
+        world
+            .set_storage_type::<bool>(StorageType::SparseSet)
+            .unwrap();
+
+        let mut builder = EntityBuilder::new();
+        let info = world.type_info(position).unwrap();
+        let a =
+            world.spawn(unsafe { builder.add_dynamic(info, &position_bytes(0.0, 0.0)) }.build());
+        let b = world.spawn((1u32,));
+        let c = world.spawn((2u32, true));
+        unsafe {
+            world
+                .insert_dynamic(b, position, &position_bytes(1.0, 1.0))
+                .unwrap();
+            world
+                .insert_dynamic(b, velocity, &position_bytes(1.0, 0.0))
+                .unwrap();
+            world
+                .insert_dynamic(c, position, &position_bytes(2.0, 2.0))
+                .unwrap();
+            world
+                .insert_dynamic(c, velocity, &position_bytes(0.0, 1.0))
+                .unwrap();
+        }
+
+        let query = DynamicQuery::new().write(position).read(velocity);
+        for (_, components) in world.query_dynamic(&query) {
+            let [x, y] = read_position(&components, 0);
+            let [dx, dy] = read_position(&components, 1);
+            unsafe {
+                components[0]
+                    .cast::<[f32; 2]>()
+                    .as_ptr()
+                    .write_unaligned([x + dx, y + dy])
+            };
+        }
+
+        let query = DynamicQuery::new().read(position);
+        let mut positions = world
+            .query_dynamic(&query)
+            .map(|(entity, components)| (entity, read_position(&components, 0)))
+            .collect::<Vec<_>>();
+        positions.sort_by_key(|(entity, _)| entity.id());
+        assert_eq!(
+            positions,
+            vec![(a, [0.0, 0.0]), (b, [2.0, 1.0]), (c, [2.0, 3.0])]
+        );
+
+        // rust types and sparse components are supported too
+        let query = DynamicQuery::new()
+            .read(ComponentId::of::<u32>())
+            .with(velocity)
+            .without(ComponentId::of::<bool>());
+        let entities = world
+            .query_dynamic(&query)
+            .map(|(entity, components)| (entity, unsafe { *components[0].cast::<u32>().as_ptr() }))
+            .collect::<Vec<_>>();
+        assert_eq!(entities, vec![(b, 1)]);
+        let query = DynamicQuery::new().read(ComponentId::of::<bool>());
+        assert_eq!(
+            world
+                .query_dynamic(&query)
+                .map(|(entity, _)| entity)
+                .collect::<Vec<_>>(),
+            vec![c]
+        );
+    }
+
+    #[test]
+    fn dynamic_components_are_dropped() {
+        static DROPPED: AtomicUsize = AtomicUsize::new(0);
+        unsafe fn count_drop(_: *mut u8) {
+            DROPPED.fetch_add(1, Ordering::SeqCst);
+        }
+
+        let mut world = World::new();
+        let id = world.register_component(
+            ComponentDescriptor::new("Counted", Layout::new::<u16>()).with_drop(count_drop),
+        );
+        let a = world.spawn(());
+        let b = world.spawn(());
+        unsafe {
+            world.insert_dynamic(a, id, &[0, 0]).unwrap();
+            world.insert_dynamic(b, id, &[0, 0]).unwrap();
+            // replacing drops the old value
+            world.insert_dynamic(b, id, &[1, 0]).unwrap();
+        }
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 1);
+        world.remove_dynamic(a, id).unwrap();
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 2);
+        world.despawn(b).unwrap();
+        assert_eq!(DROPPED.load(Ordering::SeqCst), 3);
+    }
+}
@@ -230,12 +230,6 @@ impl Entities {
         if meta.generation != entity.generation {
             return Err(NoSuchEntity);
         }
-        if meta.location.archetype == 0 {
-            return Ok(Location {
-                archetype: 0,
-                index: usize::max_value(),
-            });
-        }
         Ok(meta.location)
     }
 
@@ -322,6 +316,45 @@ impl Entities {
         self.reserved = new_reserved.into();
     }
 
+    /// Captures the generation of every entity ID along with the free list. Reserved entities that were not
+    /// flushed yet are not captured.
+    pub fn snapshot_into(&self, snapshot: &mut EntitiesSnapshot) {
+        snapshot.generations.clear();
+        snapshot
+            .generations
+            .extend(self.meta.iter().map(|meta| meta.generation));
+        let free_cursor = self.free_cursor.load(Ordering::Relaxed);
+        snapshot.free.clear();
+        snapshot
+            .free
+            .extend_from_slice(&self.free[..free_cursor as usize]);
+    }
+
+    /// Restores the allocator to the state captured by [Entities::snapshot_into]. Every entity is left without a
+    /// location, the caller must write the locations of the live ones.
+    pub fn restore(&mut self, snapshot: &EntitiesSnapshot) {
+        let len = snapshot.generations.len();
+        self.meta.clear();
+        self.meta
+            .extend(snapshot.generations.iter().map(|generation| EntityMeta {
+                generation: *generation,
+                location: Location {
+                    archetype: 0,
+                    index: usize::max_value(),
+                },
+            }));
+        self.free.clear();
+        self.free.extend_from_slice(&snapshot.free);
+        self.free.resize(len, 0);
+        self.free_cursor
+            .store(snapshot.free.len() as u32, Ordering::Relaxed);
+        self.pending.store(0, Ordering::Relaxed);
+        if self.reserved.len() != len {
+            self.reserved = (0..len).map(|_| AtomicU32::new(0)).collect();
+        }
+        self.reserved_cursor.store(0, Ordering::Relaxed);
+    }
+
     pub fn get_reserver(&self) -> EntityReserver {
         // SAFE: reservers use atomics for anything write-related
         let entities: &'static Entities = unsafe { mem::transmute(self) };
@@ -342,6 +375,13 @@ impl EntityReserver {
     }
 }
 
+/// The state of [Entities], see [Entities::snapshot_into]
+#[derive(Debug, Default, Clone)]
+pub(crate) struct EntitiesSnapshot {
+    generations: Vec<u32>,
+    free: Vec<u32>,
+}
+
 #[derive(Copy, Clone, Debug)]
 pub(crate) struct EntityMeta {
     pub generation: u32,
@@ -17,12 +17,11 @@
 use bevy_utils::HashSet;
 use std::{
     alloc::{alloc, dealloc, Layout},
-    any::TypeId,
     mem::{self, MaybeUninit},
     ptr,
 };
 
-use crate::{Component, DynamicBundle, TypeInfo};
+use crate::{Component, ComponentId, DynamicBundle, TypeInfo};
 
 /// Helper for incrementally constructing a bundle of components with dynamic component types
 ///
@@ -41,8 +40,8 @@ pub struct EntityBuilder {
     storage: Box<[MaybeUninit<u8>]>,
     cursor: usize,
     info: Vec<(TypeInfo, usize)>,
-    ids: Vec<TypeId>,
-    id_set: HashSet<TypeId>,
+    ids: Vec<ComponentId>,
+    id_set: HashSet<ComponentId>,
 }
 
 impl EntityBuilder {
@@ -59,7 +58,7 @@ impl EntityBuilder {
 
     /// Add `component` to the entity
     pub fn add<T: Component>(&mut self, component: T) -> &mut Self {
-        if !self.id_set.insert(TypeId::of::<T>()) {
+        if !self.id_set.insert(ComponentId::of::<T>()) {
             return self;
         }
         let end = self.cursor + mem::size_of::<T>();
@@ -80,6 +79,40 @@ impl EntityBuilder {
         self
     }
 
+    /// Add a component described by `info` to the entity, copying its value from `data`. This is how components
+    /// registered with [World::register_component](crate::World::register_component) are added, see
+    /// [World::type_info](crate::World::type_info).
+    ///
+    /// # Safety
+    /// `data` must hold a valid value of the component, which is then owned by the builder
+    ///
+    /// # Panics
+    /// Panics if the length of `data` is not the size of the component
+    pub unsafe fn add_dynamic(&mut self, info: TypeInfo, data: &[u8]) -> &mut Self {
+        assert_eq!(
+            data.len(),
+            info.layout().size(),
+            "{} components are {} bytes long",
+            info.type_name(),
+            info.layout().size()
+        );
+        if !self.id_set.insert(info.id()) {
+            return self;
+        }
+        let end = self.cursor + data.len();
+        if end > self.storage.len() {
+            self.grow(end);
+        }
+        ptr::copy_nonoverlapping(
+            data.as_ptr(),
+            self.storage.as_mut_ptr().add(self.cursor).cast::<u8>(),
+            data.len(),
+        );
+        self.info.push((info, self.cursor));
+        self.cursor += data.len();
+        self
+    }
+
     fn grow(&mut self, min_size: usize) {
         let new_len = min_size.next_power_of_two().max(64);
         let mut new_storage = vec![MaybeUninit::uninit(); new_len].into_boxed_slice();
@@ -160,7 +193,7 @@ pub struct BuiltEntity<'a> {
 }
 
 impl DynamicBundle for BuiltEntity<'_> {
-    fn with_ids<T>(&self, f: impl FnOnce(&[TypeId]) -> T) -> T {
+    fn with_ids<T>(&self, f: impl FnOnce(&[ComponentId]) -> T) -> T {
         f(&self.builder.ids)
     }
 
@@ -169,7 +202,7 @@ impl DynamicBundle for BuiltEntity<'_> {
         self.builder.info.iter().map(|x| x.0).collect()
     }
 
-    unsafe fn put(self, mut f: impl FnMut(*mut u8, TypeId, usize) -> bool) {
+    unsafe fn put(self, mut f: impl FnMut(*mut u8, ComponentId, usize) -> bool) {
         for (ty, offset) in self.builder.info.drain(..) {
             let ptr = self.builder.storage.as_mut_ptr().add(offset).cast();
             if !f(ptr, ty.id(), ty.layout().size()) {
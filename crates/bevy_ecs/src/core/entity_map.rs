@@ -1,6 +1,10 @@
 use crate::Entity;
 use bevy_utils::HashMap;
-use std::collections::hash_map::Entry;
+use smallvec::{Array, SmallVec};
+use std::{
+    collections::{hash_map::Entry, HashSet, VecDeque},
+    hash::{BuildHasher, Hash},
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,10 +13,122 @@ pub enum MapEntitiesError {
     EntityNotFound(Entity),
 }
 
+/// Replaces the entities held by a component with the entities they are mapped to, for example when a scene is
+/// spawned. Can be derived with `#[derive(MapEntities)]`, which maps the fields whose type mentions `Entity`.
+///
+/// ```
+/// # use bevy_ecs::{Entity, EntityMap, MapEntities};
+/// use smallvec::{smallvec, SmallVec};
+/// use std::collections::HashMap;
+///
+/// #[derive(MapEntities)]
+/// struct Targets {
+///     main: Entity,
+///     backup: Option<Entity>,
+///     others: Vec<Entity>,
+///     nearest: SmallVec<[Entity; 4]>,
+///     // maps have no `MapEntities` impl, they are mapped by hand
+///     #[map_entities(ignore)]
+///     scores: HashMap<Entity, u32>,
+///     range: f32,
+/// }
+///
+/// let (a, b) = (Entity::new(0), Entity::new(1));
+/// let mut entity_map = EntityMap::default();
+/// entity_map.insert(a, b);
+/// entity_map.insert(b, a);
+///
+/// let mut targets = Targets {
+///     main: a,
+///     backup: Some(b),
+///     others: vec![a, b],
+///     nearest: smallvec![b],
+///     scores: HashMap::default(),
+///     range: 1.0,
+/// };
+/// targets.map_entities(&entity_map).unwrap();
+/// assert_eq!(targets.main, b);
+/// assert_eq!(targets.backup, Some(a));
+/// assert_eq!(targets.others, vec![b, a]);
+/// assert_eq!(&targets.nearest[..], &[a]);
+/// ```
 pub trait MapEntities {
     fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError>;
 }
 
+impl MapEntities for Entity {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        *self = entity_map.get(*self)?;
+        Ok(())
+    }
+}
+
+impl<T: MapEntities> MapEntities for Option<T> {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Some(value) = self {
+            value.map_entities(entity_map)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: MapEntities> MapEntities for [T] {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for value in self.iter_mut() {
+            value.map_entities(entity_map)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: MapEntities> MapEntities for Vec<T> {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        self.as_mut_slice().map_entities(entity_map)
+    }
+}
+
+impl<T: MapEntities> MapEntities for VecDeque<T> {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for value in self.iter_mut() {
+            value.map_entities(entity_map)?;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Array> MapEntities for SmallVec<A>
+where
+    A::Item: MapEntities,
+{
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        self.as_mut_slice().map_entities(entity_map)
+    }
+}
+
+impl<T: MapEntities + Eq + Hash, S: BuildHasher> MapEntities for HashSet<T, S> {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        // mapping changes the hashes of the values, so they are inserted again
+        let mut values = self.drain().collect::<Vec<_>>();
+        let result = values.map_entities(entity_map);
+        self.extend(values);
+        result
+    }
+}
+
+macro_rules! impl_map_entities_for_arrays {
+    ($($len: literal)*) => {
+        $(
+            impl<T: MapEntities> MapEntities for [T; $len] {
+                fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+                    self[..].map_entities(entity_map)
+                }
+            }
+        )*
+    };
+}
+
+impl_map_entities_for_arrays!(0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32);
+
 #[derive(Default, Debug)]
 pub struct EntityMap {
     map: HashMap<Entity, Entity>,
@@ -46,3 +162,39 @@ impl EntityMap {
         self.map.values().cloned()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{EntityMap, MapEntities};
+    use crate::Entity;
+    use smallvec::{smallvec, SmallVec};
+    use std::collections::{HashSet, VecDeque};
+
+    #[test]
+    fn map_nested_entities() {
+        let (a, b, c) = (Entity::new(0), Entity::new(1), Entity::new(2));
+        let mut entity_map = EntityMap::default();
+        entity_map.insert(a, b);
+        entity_map.insert(b, c);
+
+        let mut pair = [a, b];
+        pair.map_entities(&entity_map).unwrap();
+        assert_eq!(pair, [b, c]);
+
+        let mut queue: VecDeque<Entity> = vec![b, a].into();
+        queue.map_entities(&entity_map).unwrap();
+        assert_eq!(queue, vec![c, b]);
+
+        let mut optional: Option<SmallVec<[Entity; 2]>> = Some(smallvec![a]);
+        optional.map_entities(&entity_map).unwrap();
+        assert_eq!(&optional.unwrap()[..], &[b]);
+
+        let mut set = vec![a, b].into_iter().collect::<HashSet<_>>();
+        set.map_entities(&entity_map).unwrap();
+        assert_eq!(set, vec![b, c].into_iter().collect());
+
+        let mut set = vec![a, c].into_iter().collect::<HashSet<_>>();
+        assert!(set.map_entities(&entity_map).is_err());
+        assert_eq!(set.len(), 2);
+    }
+}
@@ -1,5 +1,8 @@
-use crate::{core::ComponentFlags, Archetype, Bundle, Component, QueryAccess};
-use std::{any::TypeId, marker::PhantomData, ptr::NonNull};
+use crate::{
+    core::{sparse_set::SparseRows, ComponentFlags},
+    Archetype, Bundle, Component, ComponentId, QueryAccess,
+};
+use std::{marker::PhantomData, ptr::NonNull};
 
 pub trait QueryFilter: Sized {
     type EntityFilter: EntityFilter;
@@ -29,15 +32,76 @@ impl EntityFilter for AnyEntityFilter {
 
 pub struct Or<T>(pub T);
 
+/// Where the flags of the components of an archetype are
+#[derive(Copy, Clone)]
+enum FlagsSource {
+    Table(NonNull<ComponentFlags>),
+    SparseSet(SparseRows),
+}
+
+impl FlagsSource {
+    fn get<T: Component>(archetype: &Archetype) -> Option<Self> {
+        match archetype.get_type_state(ComponentId::of::<T>()) {
+            Some(state) => Some(FlagsSource::Table(state.component_flags())),
+            None => unsafe {
+                archetype
+                    .sparse_rows(ComponentId::of::<T>(), 0)
+                    .map(FlagsSource::SparseSet)
+            },
+        }
+    }
+
+    /// Returns the flags of the `offset`th entity, if it has the component
+    #[inline]
+    unsafe fn flags(&self, offset: usize) -> Option<ComponentFlags> {
+        match self {
+            FlagsSource::Table(flags) => Some(*flags.as_ptr().add(offset)),
+            FlagsSource::SparseSet(rows) => rows.get(offset).map(|(_, flags)| *flags.as_ptr()),
+        }
+    }
+}
+
 /// Query transformer that retrieves components of type `T` that have been mutated since the start of the frame.
 /// Added components do not count as mutated.
-pub struct Mutated<T>(NonNull<ComponentFlags>, PhantomData<T>);
+pub struct Mutated<T>(FlagsSource, PhantomData<T>);
 
 /// Query transformer that retrieves components of type `T` that have been added since the start of the frame.
-pub struct Added<T>(NonNull<ComponentFlags>, PhantomData<T>);
+pub struct Added<T>(FlagsSource, PhantomData<T>);
 
 /// Query transformer that retrieves components of type `T` that have either been mutated or added since the start of the frame.
-pub struct Changed<T>(NonNull<ComponentFlags>, PhantomData<T>);
+pub struct Changed<T>(FlagsSource, PhantomData<T>);
+
+/// The [EntityFilter] of [With], [Without] and [WithType]. Components stored in archetypes are filtered per
+/// archetype, so only the components stored in sparse sets need to be checked for each entity.
+pub struct SparseEntityFilter {
+    rows: Vec<SparseRows>,
+    with: bool,
+}
+
+impl SparseEntityFilter {
+    fn new(archetype: &Archetype, types: impl Iterator<Item = ComponentId>, with: bool) -> Self {
+        Self {
+            rows: types
+                .filter_map(|ty| unsafe { archetype.sparse_rows(ty, 0) })
+                .collect(),
+            with,
+        }
+    }
+}
+
+impl EntityFilter for SparseEntityFilter {
+    const DANGLING: Self = SparseEntityFilter {
+        rows: Vec::new(),
+        with: true,
+    };
+
+    #[inline]
+    unsafe fn matches_entity(&self, offset: usize) -> bool {
+        self.rows
+            .iter()
+            .all(|rows| rows.contains(offset) == self.with)
+    }
+}
 
 impl QueryFilter for () {
     type EntityFilter = AnyEntityFilter;
@@ -61,18 +125,18 @@ impl<T: Component> QueryFilter for Added<T> {
 
     #[inline]
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
-        archetype
-            .get_type_state(TypeId::of::<T>())
-            .map(|state| Added(state.component_flags(), Default::default()))
+        FlagsSource::get::<T>(archetype).map(|flags| Added(flags, Default::default()))
     }
 }
 
 impl<T: Component> EntityFilter for Added<T> {
-    const DANGLING: Self = Added(NonNull::dangling(), PhantomData::<T>);
+    const DANGLING: Self = Added(FlagsSource::Table(NonNull::dangling()), PhantomData::<T>);
 
     #[inline]
     unsafe fn matches_entity(&self, offset: usize) -> bool {
-        (*self.0.as_ptr().add(offset)).contains(ComponentFlags::ADDED)
+        self.0
+            .flags(offset)
+            .map_or(false, |flags| flags.contains(ComponentFlags::ADDED))
     }
 }
 
@@ -85,17 +149,17 @@ impl<T: Component> QueryFilter for Mutated<T> {
 
     #[inline]
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
-        archetype
-            .get_type_state(TypeId::of::<T>())
-            .map(|state| Mutated(state.component_flags(), Default::default()))
+        FlagsSource::get::<T>(archetype).map(|flags| Mutated(flags, Default::default()))
     }
 }
 
 impl<T: Component> EntityFilter for Mutated<T> {
-    const DANGLING: Self = Mutated(NonNull::dangling(), PhantomData::<T>);
+    const DANGLING: Self = Mutated(FlagsSource::Table(NonNull::dangling()), PhantomData::<T>);
 
     unsafe fn matches_entity(&self, offset: usize) -> bool {
-        (*self.0.as_ptr().add(offset)).contains(ComponentFlags::MUTATED)
+        self.0
+            .flags(offset)
+            .map_or(false, |flags| flags.contains(ComponentFlags::MUTATED))
     }
 }
 
@@ -108,26 +172,25 @@ impl<T: Component> QueryFilter for Changed<T> {
 
     #[inline]
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
-        archetype
-            .get_type_state(TypeId::of::<T>())
-            .map(|state| Changed(state.component_flags(), Default::default()))
+        FlagsSource::get::<T>(archetype).map(|flags| Changed(flags, Default::default()))
     }
 }
 
 impl<T: Component> EntityFilter for Changed<T> {
-    const DANGLING: Self = Changed(NonNull::dangling(), PhantomData::<T>);
+    const DANGLING: Self = Changed(FlagsSource::Table(NonNull::dangling()), PhantomData::<T>);
 
     #[inline]
     unsafe fn matches_entity(&self, offset: usize) -> bool {
-        let flags = *self.0.as_ptr().add(offset);
-        flags.contains(ComponentFlags::ADDED) || flags.contains(ComponentFlags::MUTATED)
+        self.0.flags(offset).map_or(false, |flags| {
+            flags.contains(ComponentFlags::ADDED) || flags.contains(ComponentFlags::MUTATED)
+        })
     }
 }
 
 pub struct Without<T>(PhantomData<T>);
 
 impl<T: Component> QueryFilter for Without<T> {
-    type EntityFilter = AnyEntityFilter;
+    type EntityFilter = SparseEntityFilter;
 
     fn access() -> QueryAccess {
         QueryAccess::without::<T>(QueryAccess::None)
@@ -135,10 +198,14 @@ impl<T: Component> QueryFilter for Without<T> {
 
     #[inline]
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
-        if archetype.has_type(TypeId::of::<T>()) {
+        if archetype.has_type(ComponentId::of::<T>()) {
             None
         } else {
-            Some(AnyEntityFilter)
+            Some(SparseEntityFilter::new(
+                archetype,
+                std::iter::once(ComponentId::of::<T>()),
+                false,
+            ))
         }
     }
 }
@@ -146,7 +213,7 @@ impl<T: Component> QueryFilter for Without<T> {
 pub struct With<T>(PhantomData<T>);
 
 impl<T: Component> QueryFilter for With<T> {
-    type EntityFilter = AnyEntityFilter;
+    type EntityFilter = SparseEntityFilter;
 
     fn access() -> QueryAccess {
         QueryAccess::with::<T>(QueryAccess::None)
@@ -154,8 +221,13 @@ impl<T: Component> QueryFilter for With<T> {
 
     #[inline]
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
-        if archetype.has_type(TypeId::of::<T>()) {
-            Some(AnyEntityFilter)
+        let ty = ComponentId::of::<T>();
+        if archetype.has_type(ty) || archetype.is_sparse(ty) {
+            Some(SparseEntityFilter::new(
+                archetype,
+                std::iter::once(ty),
+                true,
+            ))
         } else {
             None
         }
@@ -165,7 +237,7 @@ impl<T: Component> QueryFilter for With<T> {
 pub struct WithType<T: Bundle>(PhantomData<T>);
 
 impl<T: Bundle> QueryFilter for WithType<T> {
-    type EntityFilter = AnyEntityFilter;
+    type EntityFilter = SparseEntityFilter;
 
     fn access() -> QueryAccess {
         QueryAccess::union(
@@ -178,11 +250,16 @@ impl<T: Bundle> QueryFilter for WithType<T> {
 
     #[inline]
     fn get_entity_filter(archetype: &Archetype) -> Option<Self::EntityFilter> {
-        if T::static_type_info()
+        let types = T::static_type_info();
+        if types
             .iter()
-            .all(|info| archetype.has_type(info.id()))
+            .all(|info| archetype.has_type(info.id()) || archetype.is_sparse(info.id()))
         {
-            Some(AnyEntityFilter)
+            Some(SparseEntityFilter::new(
+                archetype,
+                types.iter().map(|info| info.id()),
+                true,
+            ))
         } else {
             None
         }
@@ -0,0 +1,382 @@
+use crate::{Component, ComponentId, Entity, Resources, World};
+use bevy_utils::HashMap;
+use std::{collections::VecDeque, fmt, mem, sync::Arc};
+
+/// A function that runs when a component is added to, inserted into or removed from an entity. It is given the
+/// [Resources] lent with [World::with_resources], or `None` outside of it.
+pub type ComponentHook = Arc<dyn Fn(&mut World, Option<&mut Resources>, Entity) + Send + Sync>;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub(crate) enum HookKind {
+    Add,
+    Insert,
+    Remove,
+}
+
+#[derive(Default)]
+struct TypeHooks {
+    on_add: Vec<ComponentHook>,
+    on_insert: Vec<ComponentHook>,
+    on_remove: Vec<ComponentHook>,
+}
+
+/// The hooks registered on a [World], along with the hook calls of the structural change being applied
+#[derive(Default)]
+pub(crate) struct ComponentHooks {
+    hooks: HashMap<ComponentId, TypeHooks>,
+    pending: VecDeque<(HookKind, ComponentId, Entity)>,
+    /// The resources lent with [World::with_resources], taken out while a hook runs with them
+    resources: Option<Resources>,
+    running: bool,
+}
+
+impl fmt::Debug for ComponentHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ComponentHooks")
+            .field("hooked_types", &self.hooks.len())
+            .field("pending", &self.pending)
+            .field("running", &self.running)
+            .finish()
+    }
+}
+
+impl ComponentHooks {
+    /// Queues the hooks of kind `kind` registered for component type `ty`, if any. They run when the structural
+    /// change that queued them is done, see [World::run_queued_hooks].
+    #[inline]
+    pub(crate) fn queue(&mut self, kind: HookKind, ty: ComponentId, entity: Entity) {
+        if self.hooks.contains_key(&ty) {
+            self.pending.push_back((kind, ty, entity));
+        }
+    }
+
+    /// Queues both the "add" and the "insert" hooks of `ty`
+    #[inline]
+    pub(crate) fn queue_added(&mut self, ty: ComponentId, entity: Entity) {
+        self.queue(HookKind::Add, ty, entity);
+        self.queue(HookKind::Insert, ty, entity);
+    }
+
+    /// Whether removing a `ty` component runs any hook
+    #[inline]
+    pub(crate) fn has_remove_hooks(&self, ty: ComponentId) -> bool {
+        matches!(self.hooks.get(&ty), Some(type_hooks) if !type_hooks.on_remove.is_empty())
+    }
+
+    /// The resources lent with [World::with_resources], unless a hook is running with them
+    #[inline]
+    pub(crate) fn lent_resources(&self) -> Option<&Resources> {
+        self.resources.as_ref()
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Hooks can't run while a hook runs with the resources, unless that hook lent them again
+    fn can_run(&self) -> bool {
+        !self.running || self.resources.is_some()
+    }
+
+    fn add(&mut self, kind: HookKind, ty: ComponentId, hook: ComponentHook) {
+        let type_hooks = self.hooks.entry(ty).or_default();
+        match kind {
+            HookKind::Add => type_hooks.on_add.push(hook),
+            HookKind::Insert => type_hooks.on_insert.push(hook),
+            HookKind::Remove => type_hooks.on_remove.push(hook),
+        }
+    }
+
+    fn get(&self, kind: HookKind, ty: ComponentId) -> Vec<ComponentHook> {
+        self.hooks
+            .get(&ty)
+            .map(|type_hooks| match kind {
+                HookKind::Add => type_hooks.on_add.clone(),
+                HookKind::Insert => type_hooks.on_insert.clone(),
+                HookKind::Remove => type_hooks.on_remove.clone(),
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl World {
+    /// Registers a hook that runs when a `T` component is added to an entity that did not have one. This covers
+    /// [World::spawn], [World::spawn_batch], [World::insert] and the matching [Commands](crate::Commands).
+    ///
+    /// Hooks run synchronously: the structural change that triggers them only returns once they ran, and they run
+    /// in the order the components were added. The hooks of changes made by a hook run once it returns.
+    ///
+    /// Hooks are given the [Resources] lent to the world with [World::with_resources], which [Commands](crate::Commands)
+    /// and the engine's stages do. Outside of it, they are given `None`, so a hook keeping an index in a resource
+    /// should report the changes it can't record rather than lose them.
+    pub fn on_add<T: Component>(
+        &mut self,
+        hook: impl Fn(&mut World, Option<&mut Resources>, Entity) + Send + Sync + 'static,
+    ) {
+        self.hooks
+            .add(HookKind::Add, ComponentId::of::<T>(), Arc::new(hook));
+    }
+
+    /// Registers a hook that runs whenever a `T` component is inserted into an entity, including when it replaces
+    /// an existing `T`. See [World::on_add].
+    pub fn on_insert<T: Component>(
+        &mut self,
+        hook: impl Fn(&mut World, Option<&mut Resources>, Entity) + Send + Sync + 'static,
+    ) {
+        self.hooks
+            .add(HookKind::Insert, ComponentId::of::<T>(), Arc::new(hook));
+    }
+
+    /// Registers a hook that runs when a `T` component is about to be removed from an entity, either by
+    /// [World::remove] or because the entity is despawned. See [World::on_add].
+    ///
+    /// The component can still be read by the hook, which makes it possible to remove the entity from indices keyed
+    /// by the value of the component.
+    pub fn on_remove<T: Component>(
+        &mut self,
+        hook: impl Fn(&mut World, Option<&mut Resources>, Entity) + Send + Sync + 'static,
+    ) {
+        self.hooks
+            .add(HookKind::Remove, ComponentId::of::<T>(), Arc::new(hook));
+    }
+
+    /// Lends `resources` to the component hooks that run while `f` changes the world
+    ///
+    /// ```
+    /// # use bevy_ecs::{Resources, World};
+    /// let mut world = World::new();
+    /// let mut resources = Resources::default();
+    /// resources.insert(0usize);
+    /// world.on_add::<u32>(|_, resources, _| {
+    ///     *resources.unwrap().get_mut::<usize>().unwrap() += 1;
+    /// });
+    ///
+    /// world.with_resources(&mut resources, |world| {
+    ///     world.spawn((1u32,));
+    ///     world.spawn((2u32,));
+    /// });
+    /// assert_eq!(*resources.get::<usize>().unwrap(), 2);
+    /// ```
+    pub fn with_resources<T>(
+        &mut self,
+        resources: &mut Resources,
+        f: impl FnOnce(&mut World) -> T,
+    ) -> T {
+        let previous = self.hooks.resources.replace(mem::take(resources));
+        let result = f(self);
+        *resources = mem::replace(&mut self.hooks.resources, previous)
+            .expect("The lent resources are given back by the hooks that use them.");
+        result
+    }
+
+    /// Runs the hooks queued by the last structural change, including the hooks of the changes they make
+    pub(crate) fn run_queued_hooks(&mut self) {
+        if !self.hooks.can_run() {
+            // the hook that is running has the resources, the hooks run once it returns
+            return;
+        }
+        while let Some((kind, ty, entity)) = self.hooks.pending.pop_front() {
+            self.call_hooks(kind, ty, entity);
+        }
+    }
+
+    /// Runs the "remove" hooks of the components of `entity` that have types in `types`. This must happen before the
+    /// components are removed, so the hooks can read them.
+    pub(crate) fn run_remove_hooks(&mut self, entity: Entity, types: &[ComponentId]) {
+        for ty in types.iter().copied() {
+            if !self.hooks.has_remove_hooks(ty) {
+                continue;
+            }
+            if !self.hooks.can_run() {
+                self.hooks.pending.push_back((HookKind::Remove, ty, entity));
+            } else if self.has_component_type(entity, ty) {
+                self.call_hooks(HookKind::Remove, ty, entity);
+            }
+        }
+        self.run_queued_hooks();
+    }
+
+    fn call_hooks(&mut self, kind: HookKind, ty: ComponentId, entity: Entity) {
+        let hooks = self.hooks.get(kind, ty);
+        if hooks.is_empty() {
+            return;
+        }
+        let mut resources = self.hooks.resources.take();
+        let was_running = mem::replace(&mut self.hooks.running, true);
+        for hook in hooks {
+            hook(self, resources.as_mut(), entity);
+        }
+        self.hooks.running = was_running;
+        self.hooks.resources = resources;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Commands, Entity, Resources, StorageType, World};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Add(Entity),
+        Insert(Entity),
+        Remove(Entity),
+    }
+
+    fn record_hooks(world: &mut World) -> Arc<Mutex<Vec<Event>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let add_events = events.clone();
+        world.on_add::<u32>(move |_, _, entity| add_events.lock().push(Event::Add(entity)));
+        let insert_events = events.clone();
+        world
+            .on_insert::<u32>(move |_, _, entity| insert_events.lock().push(Event::Insert(entity)));
+        let remove_events = events.clone();
+        world
+            .on_remove::<u32>(move |_, _, entity| remove_events.lock().push(Event::Remove(entity)));
+        events
+    }
+
+    fn take(events: &Mutex<Vec<Event>>) -> Vec<Event> {
+        std::mem::take(&mut *events.lock())
+    }
+
+    #[test]
+    fn world_hooks() {
+        let mut world = World::new();
+        let events = record_hooks(&mut world);
+
+        let a = world.spawn((1u32, true));
+        assert_eq!(take(&events), vec![Event::Add(a), Event::Insert(a)]);
+        let b = world.spawn((false,));
+        assert_eq!(take(&events), vec![]);
+        world.insert_one(b, 2u32).unwrap();
+        assert_eq!(take(&events), vec![Event::Add(b), Event::Insert(b)]);
+        world.insert_one(b, 3u32).unwrap();
+        assert_eq!(take(&events), vec![Event::Insert(b)]);
+        world.insert_one(a, 1u64).unwrap();
+        assert_eq!(take(&events), vec![]);
+        world.remove_one::<u32>(b).unwrap();
+        assert_eq!(take(&events), vec![Event::Remove(b)]);
+        world.despawn(a).unwrap();
+        assert_eq!(take(&events), vec![Event::Remove(a)]);
+
+        let c = world.spawn_batch(vec![(4u32,)]).next().unwrap();
+        assert_eq!(take(&events), vec![Event::Add(c), Event::Insert(c)]);
+        world.clear();
+        assert_eq!(take(&events), vec![Event::Remove(c)]);
+    }
+
+    #[test]
+    fn sparse_set_hooks() {
+        let mut world = World::new();
+        world
+            .set_storage_type::<u32>(StorageType::SparseSet)
+            .unwrap();
+        let events = record_hooks(&mut world);
+
+        let a = world.spawn((true,));
+        world.insert_one(a, 1u32).unwrap();
+        assert_eq!(take(&events), vec![Event::Add(a), Event::Insert(a)]);
+        world.insert_one(a, 2u32).unwrap();
+        assert_eq!(take(&events), vec![Event::Insert(a)]);
+        world.despawn(a).unwrap();
+        assert_eq!(take(&events), vec![Event::Remove(a)]);
+    }
+
+    #[test]
+    fn remove_hooks_read_removed_components() {
+        let mut world = World::new();
+        let removed = Arc::new(Mutex::new(Vec::new()));
+        let hook_removed = removed.clone();
+        world.on_remove::<u32>(move |world, _, entity| {
+            hook_removed.lock().push(*world.get::<u32>(entity).unwrap());
+        });
+
+        let a = world.spawn((1u32,));
+        let b = world.spawn((2u32, true));
+        let c = world.spawn((3u32, true));
+        world.remove_one::<u32>(a).unwrap();
+        world.despawn(b).unwrap();
+        world.remove_one_by_one::<(u32, u64)>(c).unwrap();
+        assert_eq!(*removed.lock(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn hooks_of_hook_changes_run_before_returning() {
+        let mut world = World::new();
+        world.on_add::<u32>(|world, _, entity| world.insert_one(entity, true).unwrap());
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let add_events = events.clone();
+        world.on_add::<bool>(move |_, _, entity| add_events.lock().push(Event::Add(entity)));
+
+        let a = world.spawn((1u32,));
+        assert_eq!(take(&events), vec![Event::Add(a)]);
+        assert!(*world.get::<bool>(a).unwrap());
+    }
+
+    #[test]
+    fn hooks_access_world_and_resources() {
+        struct Name(&'static str);
+        #[derive(Default)]
+        struct NameIndex(Vec<(&'static str, Entity)>);
+
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(NameIndex::default());
+        world.on_add::<Name>(|world, resources, entity| {
+            let name = world.get::<Name>(entity).unwrap().0;
+            resources
+                .expect("the names are indexed with resources")
+                .get_mut::<NameIndex>()
+                .unwrap()
+                .0
+                .push((name, entity));
+        });
+        world.on_remove::<Name>(|world, resources, entity| {
+            let name = world.get::<Name>(entity).unwrap().0;
+            let resources = resources.expect("the names are indexed with resources");
+            let mut index = resources.get_mut::<NameIndex>().unwrap();
+            index.0.retain(|indexed| *indexed != (name, entity));
+        });
+
+        let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
+        commands.spawn((Name("player"),));
+        let player = commands.current_entity().unwrap();
+        commands.spawn((Name("enemy"),));
+        let enemy = commands.current_entity().unwrap();
+        commands.apply(&mut world, &mut resources);
+        assert_eq!(
+            resources.get::<NameIndex>().unwrap().0,
+            vec![("player", player), ("enemy", enemy)]
+        );
+
+        commands.despawn(player);
+        commands.apply(&mut world, &mut resources);
+        assert_eq!(
+            resources.get::<NameIndex>().unwrap().0,
+            vec![("enemy", enemy)]
+        );
+
+        world.with_resources(&mut resources, |world| {
+            world.remove_one::<Name>(enemy).unwrap();
+            world.spawn((Name("ally"),))
+        });
+        assert_eq!(resources.get::<NameIndex>().unwrap().0.len(), 1);
+        assert_eq!(resources.get::<NameIndex>().unwrap().0[0].0, "ally");
+    }
+
+    #[test]
+    fn hooks_know_when_resources_are_lent() {
+        let mut world = World::new();
+        let lent = Arc::new(Mutex::new(Vec::new()));
+        let hook_lent = lent.clone();
+        world.on_add::<u32>(move |_, resources, _| hook_lent.lock().push(resources.is_some()));
+
+        world.spawn((1u32,));
+        world.with_resources(&mut Resources::default(), |world| world.spawn((2u32,)));
+        assert_eq!(*lent.lock(), vec![false, true]);
+    }
+}
@@ -34,31 +34,41 @@ mod access;
 mod archetype;
 mod borrow;
 mod bundle;
+mod dynamic;
 mod entities;
 mod entity_builder;
 mod entity_map;
 mod filter;
+mod hooks;
 mod query;
+mod relation;
+mod required;
 mod serde;
+mod snapshot;
+mod sparse_set;
 mod world;
 mod world_builder;
 
 pub use access::{ArchetypeComponent, QueryAccess, TypeAccess};
-pub use archetype::{Archetype, ComponentFlags, TypeState};
+pub use archetype::{Archetype, ComponentFlags, ComponentId, TypeInfo, TypeState};
 pub use borrow::{AtomicBorrow, Ref, RefMut};
 pub use bundle::{Bundle, DynamicBundle, MissingComponent};
+pub use dynamic::{ComponentDescriptor, DynamicFetch, DynamicQuery, DynamicQueryIter};
 pub use entities::{Entity, EntityReserver, Location, NoSuchEntity};
 pub use entity_builder::{BuiltEntity, EntityBuilder};
 pub use entity_map::*;
 pub use filter::{Added, Changed, EntityFilter, Mutated, Or, QueryFilter, With, Without};
+pub use hooks::ComponentHook;
 pub use query::{Batch, BatchedIter, Flags, Mut, QueryIter, ReadOnlyFetch, WorldQuery};
+pub use relation::{Relation, RelationSources, RelationTargets};
+pub use required::RequiredComponents;
+pub use snapshot::{SnapshotRegistry, WorldSnapshot};
+pub use sparse_set::{StorageType, StorageTypeError};
 pub use world::{ArchetypesGeneration, Component, ComponentError, SpawnBatchIter, World};
 pub use world_builder::*;
 
 // Unstable implementation details needed by the macros
 #[doc(hidden)]
-pub use archetype::TypeInfo;
-#[doc(hidden)]
 pub use bevy_utils;
 #[doc(hidden)]
 pub use query::Fetch;
@@ -14,8 +14,12 @@
 
 // modified by Bevy contributors
 
-use super::{Archetype, Component, Entity, MissingComponent, QueryAccess, QueryFilter};
-use crate::{ComponentFlags, EntityFilter};
+use super::{
+    sparse_set::SparseRows, Archetype, Component, Entity, MissingComponent, QueryAccess,
+    QueryFilter,
+};
+use crate::{ComponentFlags, ComponentId, EntityFilter};
+use bevy_tasks::BatchSize;
 use std::{
     marker::PhantomData,
     ops::{Deref, DerefMut},
@@ -58,6 +62,22 @@ pub trait Fetch<'a>: Sized {
     /// - Bounds-checking must be performed externally
     /// - Any resulting borrows must be legal (e.g. no &mut to something another iterator might access)
     unsafe fn fetch(&self, n: usize) -> Self::Item;
+
+    /// Whether the `n`th entity of this archetype has the fetched components. This is always the case for
+    /// components stored in archetypes, but components stored in sparse sets must be checked for each entity.
+    ///
+    /// # Safety
+    /// Bounds-checking must be performed externally
+    #[inline]
+    unsafe fn matches(&self, _n: usize) -> bool {
+        true
+    }
+
+    /// Whether [Fetch::matches] has to be checked for each entity
+    #[inline]
+    fn is_sparse(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -96,24 +116,43 @@ impl<'a, T: Component> WorldQuery for &'a T {
 }
 
 #[doc(hidden)]
-pub struct FetchRead<T>(NonNull<T>);
+pub struct FetchRead<T>(NonNull<T>, Option<SparseRows>);
 
 unsafe impl<T> ReadOnlyFetch for FetchRead<T> {}
 
 impl<'a, T: Component> Fetch<'a> for FetchRead<T> {
     type Item = &'a T;
 
-    const DANGLING: Self = Self(NonNull::dangling());
+    const DANGLING: Self = Self(NonNull::dangling(), None);
 
     unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
+        if let Some(components) = archetype.get::<T>() {
+            return Some(Self(
+                NonNull::new_unchecked(components.as_ptr().add(offset)),
+                None,
+            ));
+        }
         archetype
-            .get::<T>()
-            .map(|x| Self(NonNull::new_unchecked(x.as_ptr().add(offset))))
+            .sparse_rows(ComponentId::of::<T>(), offset)
+            .map(|rows| Self(NonNull::dangling(), Some(rows)))
     }
 
     #[inline]
     unsafe fn fetch(&self, n: usize) -> &'a T {
-        &*self.0.as_ptr().add(n)
+        match &self.1 {
+            None => &*self.0.as_ptr().add(n),
+            Some(rows) => &*rows.get(n).unwrap().0.cast::<T>().as_ptr(),
+        }
+    }
+
+    #[inline]
+    unsafe fn matches(&self, n: usize) -> bool {
+        self.1.as_ref().map_or(true, |rows| rows.contains(n))
+    }
+
+    #[inline]
+    fn is_sparse(&self) -> bool {
+        self.1.is_some()
     }
 
     #[inline]
@@ -177,12 +216,12 @@ impl<'a, T: Component> Mut<'a, T> {
     /// # Safety
     /// This doesn't check the bounds of index in archetype
     pub unsafe fn new(archetype: &'a Archetype, index: usize) -> Result<Self, MissingComponent> {
-        let (target, type_state) = archetype
-            .get_with_type_state::<T>()
+        let (target, flags) = archetype
+            .get_component::<T>(index)
             .ok_or_else(MissingComponent::new::<T>)?;
         Ok(Self {
-            value: &mut *target.as_ptr().add(index),
-            flags: &mut *type_state.component_flags().as_ptr().add(index),
+            value: &mut *target.as_ptr(),
+            flags: &mut *flags.as_ptr(),
         })
     }
 }
@@ -217,32 +256,53 @@ impl<'a, T: Component> WorldQuery for Mut<'a, T> {
     type Fetch = FetchMut<T>;
 }
 #[doc(hidden)]
-pub struct FetchMut<T>(NonNull<T>, NonNull<ComponentFlags>);
+pub struct FetchMut<T>(NonNull<T>, NonNull<ComponentFlags>, Option<SparseRows>);
 
 impl<'a, T: Component> Fetch<'a> for FetchMut<T> {
     type Item = Mut<'a, T>;
 
-    const DANGLING: Self = Self(NonNull::dangling(), NonNull::dangling());
+    const DANGLING: Self = Self(NonNull::dangling(), NonNull::dangling(), None);
 
     unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
+        if let Some((components, type_state)) = archetype.get_with_type_state::<T>() {
+            return Some(Self(
+                NonNull::new_unchecked(components.as_ptr().add(offset)),
+                NonNull::new_unchecked(type_state.component_flags().as_ptr().add(offset)),
+                None,
+            ));
+        }
         archetype
-            .get_with_type_state::<T>()
-            .map(|(components, type_state)| {
-                Self(
-                    NonNull::new_unchecked(components.as_ptr().add(offset)),
-                    NonNull::new_unchecked(type_state.component_flags().as_ptr().add(offset)),
-                )
-            })
+            .sparse_rows(ComponentId::of::<T>(), offset)
+            .map(|rows| Self(NonNull::dangling(), NonNull::dangling(), Some(rows)))
     }
 
     #[inline]
     unsafe fn fetch(&self, n: usize) -> Mut<'a, T> {
-        Mut {
-            value: &mut *self.0.as_ptr().add(n),
-            flags: &mut *self.1.as_ptr().add(n),
+        match &self.2 {
+            None => Mut {
+                value: &mut *self.0.as_ptr().add(n),
+                flags: &mut *self.1.as_ptr().add(n),
+            },
+            Some(rows) => {
+                let (value, flags) = rows.get(n).unwrap();
+                Mut {
+                    value: &mut *value.cast::<T>().as_ptr(),
+                    flags: &mut *flags.as_ptr(),
+                }
+            }
         }
     }
 
+    #[inline]
+    unsafe fn matches(&self, n: usize) -> bool {
+        self.2.as_ref().map_or(true, |rows| rows.contains(n))
+    }
+
+    #[inline]
+    fn is_sparse(&self) -> bool {
+        self.2.is_some()
+    }
+
     #[inline]
     fn access() -> QueryAccess {
         QueryAccess::write::<T>()
@@ -268,18 +328,27 @@ impl<'a, T: Fetch<'a>> Fetch<'a> for TryFetch<T> {
     }
 
     unsafe fn fetch(&self, n: usize) -> Option<T::Item> {
-        Some(self.0.as_ref()?.fetch(n))
+        let fetch = self.0.as_ref()?;
+        if fetch.matches(n) {
+            Some(fetch.fetch(n))
+        } else {
+            None
+        }
     }
 }
 
 #[doc(hidden)]
-pub struct FlagsFetch<T>(Option<NonNull<ComponentFlags>>, PhantomData<T>);
+pub struct FlagsFetch<T>(
+    Option<NonNull<ComponentFlags>>,
+    Option<SparseRows>,
+    PhantomData<T>,
+);
 unsafe impl<T> ReadOnlyFetch for FlagsFetch<T> {}
 
 impl<'a, T: Component> Fetch<'a> for FlagsFetch<T> {
     type Item = Flags<T>;
 
-    const DANGLING: Self = Self(None, PhantomData::<T>);
+    const DANGLING: Self = Self(None, None, PhantomData::<T>);
 
     #[inline]
     fn access() -> QueryAccess {
@@ -289,17 +358,22 @@ impl<'a, T: Component> Fetch<'a> for FlagsFetch<T> {
     unsafe fn get(archetype: &'a Archetype, offset: usize) -> Option<Self> {
         Some(Self(
             archetype
-                .get_type_state(std::any::TypeId::of::<T>())
+                .get_type_state(ComponentId::of::<T>())
                 .map(|type_state| {
                     NonNull::new_unchecked(type_state.component_flags().as_ptr().add(offset))
                 }),
+            archetype.sparse_rows(ComponentId::of::<T>(), offset),
             PhantomData::<T>,
         ))
     }
 
     unsafe fn fetch(&self, n: usize) -> Self::Item {
-        if let Some(flags) = self.0.as_ref() {
-            let flags = *flags.as_ptr().add(n);
+        let flags = match (&self.0, &self.1) {
+            (Some(flags), _) => Some(*flags.as_ptr().add(n)),
+            (None, Some(rows)) => rows.get(n).map(|(_, flags)| *flags.as_ptr()),
+            (None, None) => None,
+        };
+        if let Some(flags) = flags {
             Self::Item {
                 _marker: PhantomData::<T>,
                 with: true,
@@ -377,6 +451,7 @@ impl<'w, Q: WorldQuery, F: QueryFilter> Iterator for QueryIter<'w, Q, F> {
                     .chunk_info
                     .filter
                     .matches_entity(self.chunk_position as usize)
+                    || !self.chunk_info.fetch.matches(self.chunk_position as usize)
                 {
                     self.chunk_position += 1;
                     continue;
@@ -396,8 +471,18 @@ impl<'w, Q: WorldQuery> ExactSizeIterator for QueryIter<'w, Q, ()> {
     fn len(&self) -> usize {
         self.archetypes
             .iter()
-            .filter(|&archetype| unsafe { Q::Fetch::get(archetype, 0).is_some() })
-            .map(|x| x.len())
+            .filter_map(|archetype| unsafe {
+                Q::Fetch::get(archetype, 0).map(|fetch| (archetype, fetch))
+            })
+            .map(|(archetype, fetch)| {
+                if fetch.is_sparse() {
+                    (0..archetype.len())
+                        .filter(|&n| unsafe { fetch.matches(n) })
+                        .count()
+                } else {
+                    archetype.len()
+                }
+            })
             .sum()
     }
 }
@@ -416,7 +501,9 @@ impl<Q: WorldQuery, F: QueryFilter> ChunkIter<Q, F> {
                 return None;
             }
 
-            if !self.filter.matches_entity(self.position as usize) {
+            if !self.filter.matches_entity(self.position as usize)
+                || !self.fetch.matches(self.position as usize)
+            {
                 self.position += 1;
                 continue;
             }
@@ -432,21 +519,42 @@ impl<Q: WorldQuery, F: QueryFilter> ChunkIter<Q, F> {
 pub struct BatchedIter<'w, Q: WorldQuery, F: QueryFilter> {
     archetypes: &'w [Archetype],
     archetype_index: usize,
-    batch_size: usize,
+    batch_size: BatchSize,
+    resolved_batch_size: Option<usize>,
     batch: usize,
     _marker: PhantomData<(Q, F)>,
 }
 
 impl<'w, Q: WorldQuery, F: QueryFilter> BatchedIter<'w, Q, F> {
-    pub(crate) fn new(archetypes: &'w [Archetype], batch_size: usize) -> Self {
+    pub(crate) fn new(archetypes: &'w [Archetype], batch_size: BatchSize) -> Self {
         Self {
             archetypes,
             archetype_index: 0,
             batch_size,
+            resolved_batch_size: None,
             batch: 0,
             _marker: Default::default(),
         }
     }
+
+    /// Sizes the batches for `thread_num` threads when using [BatchSize::Auto]. This must be called before
+    /// the first batch is taken, otherwise batches are sized for [bevy_tasks::logical_core_count] threads.
+    pub fn set_thread_num(&mut self, thread_num: usize) {
+        let len = match self.batch_size {
+            BatchSize::Fixed(_) => 0,
+            BatchSize::Auto => self
+                .archetypes
+                .iter()
+                .filter(|archetype| {
+                    // SAFE: the fetch is only used to check that the archetype matches the query
+                    unsafe { Q::Fetch::get(archetype, 0) }.is_some()
+                        && F::get_entity_filter(archetype).is_some()
+                })
+                .map(|archetype| archetype.len())
+                .sum(),
+        };
+        self.resolved_batch_size = Some(self.batch_size.get(len, thread_num));
+    }
 }
 
 unsafe impl<'w, Q: WorldQuery, F: QueryFilter> Send for BatchedIter<'w, Q, F> {}
@@ -456,9 +564,16 @@ impl<'w, Q: WorldQuery, F: QueryFilter> Iterator for BatchedIter<'w, Q, F> {
     type Item = Batch<'w, Q, F>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        let batch_size = match self.resolved_batch_size {
+            Some(batch_size) => batch_size,
+            None => {
+                self.set_thread_num(bevy_tasks::logical_core_count());
+                self.resolved_batch_size.unwrap()
+            }
+        };
         loop {
             let archetype = self.archetypes.get(self.archetype_index)?;
-            let offset = self.batch_size * self.batch;
+            let offset = batch_size * self.batch;
             if offset >= archetype.len() {
                 self.archetype_index += 1;
                 self.batch = 0;
@@ -474,7 +589,7 @@ impl<'w, Q: WorldQuery, F: QueryFilter> Iterator for BatchedIter<'w, Q, F> {
                     state: ChunkIter {
                         fetch,
                         position: 0,
-                        len: self.batch_size.min(archetype.len() - offset),
+                        len: batch_size.min(archetype.len() - offset),
                         filter,
                     },
                 });
@@ -532,6 +647,19 @@ macro_rules! tuple_impl {
                 let ($($name,)*) = self;
                 ($($name.fetch(n),)*)
             }
+
+            #[allow(unused_variables)]
+            unsafe fn matches(&self, n: usize) -> bool {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = self;
+                true $(&& $name.matches(n))*
+            }
+
+            fn is_sparse(&self) -> bool {
+                #[allow(non_snake_case)]
+                let ($($name,)*) = self;
+                false $(|| $name.is_sparse())*
+            }
         }
 
         impl<$($name: WorldQuery),*> WorldQuery for ($($name,)*) {
@@ -550,6 +678,8 @@ mod tests {
     use std::{vec, vec::Vec};
 
     use super::Mut;
+    use crate::ParIter;
+    use bevy_tasks::{BatchSize, ParallelIterator};
 
     struct A(usize);
     struct B(usize);
@@ -763,4 +893,24 @@ mod tests {
         // the following example shouldn't compile because Changed<A> is not an UnfilteredFetch
         // assert_eq!(world.query::<(Changed<A>, &B)>().len(), 2);
     }
+
+    #[test]
+    fn auto_batched_query() {
+        let mut world = World::default();
+        world.spawn_batch((0..100).map(|i| (A(i),)));
+        world.spawn_batch((0..60).map(|i| (A(i), B(i))));
+        world.spawn_batch((0..50).map(|i| (B(i),)));
+
+        let mut batches = world.query_batched::<&A>(BatchSize::Auto);
+        batches.set_thread_num(2);
+        let lens = batches.map(|batch| batch.count()).collect::<Vec<_>>();
+        assert_eq!(lens, vec![20, 20, 20, 20, 20, 20, 20, 20]);
+
+        let pool = bevy_tasks::TaskPoolBuilder::new().num_threads(2).build();
+        // SAFE: read only query
+        let par_iter =
+            unsafe { ParIter::new(world.query_batched_unchecked::<&A, ()>(BatchSize::Auto)) };
+        let sum = par_iter.map(|a| a.0).sum::<usize, usize>(&pool);
+        assert_eq!(sum, (0..100).sum::<usize>() + (0..60).sum::<usize>());
+    }
 }
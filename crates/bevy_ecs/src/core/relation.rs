@@ -0,0 +1,331 @@
+use crate::{Component, Entity, NoSuchEntity, World};
+use std::{any::TypeId, marker::PhantomData, ops::Deref};
+
+/// A kind of link between entities, like "owns" or "targets". Links are directed: a source entity relates
+/// to a target entity. Both ends are tracked, see [RelationTargets] and [RelationSources]. Links to despawned
+/// entities are removed along with them.
+///
+/// ```
+/// use bevy_ecs::{Relation, RelationSources, World};
+///
+/// struct Owns;
+/// impl Relation for Owns {}
+///
+/// let mut world = World::new();
+/// let player = world.spawn(());
+/// let sword = world.spawn(());
+/// world.relate::<Owns>(player, sword).unwrap();
+///
+/// // all entities that own the sword
+/// let owners = world.get::<RelationSources<Owns>>(sword).unwrap();
+/// assert_eq!(&owners[..], &[player]);
+/// ```
+pub trait Relation: Send + Sync + 'static {
+    /// If true, a source relates to at most one target at a time, and relating it to a new target replaces the
+    /// old one. This makes the relation one-to-many. Otherwise it is many-to-many.
+    const EXCLUSIVE: bool = false;
+}
+
+/// The entities a source entity relates to with relation `R`. It is added and removed along with the links, use
+/// [World::relate] and [World::unrelate] (or the matching [Commands](crate::Commands)) to change it.
+#[derive(Debug)]
+pub struct RelationTargets<R: Relation> {
+    targets: Vec<Entity>,
+    marker: PhantomData<R>,
+}
+
+/// The entities that relate to a target entity with relation `R`. It is maintained along with
+/// [RelationTargets].
+#[derive(Debug)]
+pub struct RelationSources<R: Relation> {
+    sources: Vec<Entity>,
+    marker: PhantomData<R>,
+}
+
+impl<R: Relation> Deref for RelationTargets<R> {
+    type Target = [Entity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.targets
+    }
+}
+
+impl<R: Relation> Deref for RelationSources<R> {
+    type Target = [Entity];
+
+    fn deref(&self) -> &Self::Target {
+        &self.sources
+    }
+}
+
+impl World {
+    /// Links `source` to `target` with relation `R`. If `R` is [exclusive](Relation::EXCLUSIVE), the previous
+    /// target of `source` is unlinked first. Relating the same entities twice has no effect.
+    pub fn relate<R: Relation>(
+        &mut self,
+        source: Entity,
+        target: Entity,
+    ) -> Result<(), NoSuchEntity> {
+        if !self.contains(source) || !self.contains(target) {
+            return Err(NoSuchEntity);
+        }
+        self.register_relation::<R>();
+
+        if R::EXCLUSIVE {
+            let previous_targets = self
+                .get::<RelationTargets<R>>(source)
+                .map(|targets| targets.targets.clone())
+                .unwrap_or_default();
+            for previous_target in previous_targets {
+                if previous_target != target {
+                    self.unrelate::<R>(source, previous_target);
+                }
+            }
+        }
+
+        if self.add_to_list::<RelationTargets<R>>(source, target) {
+            self.add_to_list::<RelationSources<R>>(target, source);
+        }
+        Ok(())
+    }
+
+    /// Removes the link from `source` to `target` with relation `R`. Returns false if there was no such link.
+    pub fn unrelate<R: Relation>(&mut self, source: Entity, target: Entity) -> bool {
+        let removed = self.remove_from_list::<RelationTargets<R>>(source, target);
+        self.remove_from_list::<RelationSources<R>>(target, source);
+        removed
+    }
+
+    /// Removes every link of relation `R` that starts at `source`
+    pub fn unrelate_all<R: Relation>(&mut self, source: Entity) {
+        let targets = self
+            .get::<RelationTargets<R>>(source)
+            .map(|targets| targets.targets.clone())
+            .unwrap_or_default();
+        for target in targets {
+            self.unrelate::<R>(source, target);
+        }
+    }
+
+    /// Unlinks entities from each other when one end of a link of relation `R` is despawned, or when its
+    /// [RelationTargets] or [RelationSources] component is removed. This happens in the "remove" hooks of those
+    /// components, see [World::on_remove].
+    ///
+    /// [World::relate] registers `R` when it first links entities, and registering it again has no effect.
+    pub fn register_relation<R: Relation>(&mut self) {
+        if !self.relations.insert(TypeId::of::<R>()) {
+            return;
+        }
+        self.on_remove::<RelationTargets<R>>(|world, _, source| {
+            let targets = world
+                .get::<RelationTargets<R>>(source)
+                .map(|targets| targets.targets.clone())
+                .unwrap_or_default();
+            for target in targets {
+                world.remove_from_list::<RelationSources<R>>(target, source);
+            }
+        });
+        self.on_remove::<RelationSources<R>>(|world, _, target| {
+            let sources = world
+                .get::<RelationSources<R>>(target)
+                .map(|sources| sources.sources.clone())
+                .unwrap_or_default();
+            for source in sources {
+                world.remove_from_list::<RelationTargets<R>>(source, target);
+            }
+        });
+    }
+
+    /// Adds `entity` to the list in component `C` of `owner`, inserting the component if needed. Returns false
+    /// if `entity` was already in the list.
+    fn add_to_list<C: RelationList>(&mut self, owner: Entity, entity: Entity) -> bool {
+        if let Ok(mut component) = self.get_mut::<C>(owner) {
+            let entities = component.entities_mut();
+            if entities.contains(&entity) {
+                return false;
+            }
+            entities.push(entity);
+        } else {
+            let mut component = C::default();
+            component.entities_mut().push(entity);
+            // the caller checked that `owner` exists
+            self.insert_one(owner, component).unwrap();
+        }
+        true
+    }
+
+    /// Removes `entity` from the list in component `C` of `owner`, and removes the component once the list is
+    /// empty. Returns false if `entity` was not in the list.
+    fn remove_from_list<C: RelationList>(&mut self, owner: Entity, entity: Entity) -> bool {
+        let is_empty = match self.get_mut::<C>(owner) {
+            Ok(mut component) => {
+                let entities = component.entities_mut();
+                match entities.iter().position(|e| *e == entity) {
+                    Some(index) => {
+                        entities.remove(index);
+                        entities.is_empty()
+                    }
+                    None => return false,
+                }
+            }
+            Err(_) => return false,
+        };
+        if is_empty {
+            self.remove_one::<C>(owner).unwrap();
+        }
+        true
+    }
+}
+
+/// Components that store one end of a relation
+trait RelationList: Component + Default {
+    fn entities_mut(&mut self) -> &mut Vec<Entity>;
+}
+
+impl<R: Relation> Default for RelationTargets<R> {
+    fn default() -> Self {
+        Self {
+            targets: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> Default for RelationSources<R> {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<R: Relation> RelationList for RelationTargets<R> {
+    fn entities_mut(&mut self) -> &mut Vec<Entity> {
+        &mut self.targets
+    }
+}
+
+impl<R: Relation> RelationList for RelationSources<R> {
+    fn entities_mut(&mut self) -> &mut Vec<Entity> {
+        &mut self.sources
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Relation, RelationSources, RelationTargets};
+    use crate::{Entity, World};
+
+    struct Owns;
+    impl Relation for Owns {}
+
+    struct ChildOf;
+    impl Relation for ChildOf {
+        const EXCLUSIVE: bool = true;
+    }
+
+    fn targets<R: Relation>(world: &World, source: Entity) -> Vec<Entity> {
+        world
+            .get::<RelationTargets<R>>(source)
+            .map_or_else(|_| Vec::new(), |targets| targets.to_vec())
+    }
+
+    fn sources<R: Relation>(world: &World, target: Entity) -> Vec<Entity> {
+        world
+            .get::<RelationSources<R>>(target)
+            .map_or_else(|_| Vec::new(), |sources| sources.to_vec())
+    }
+
+    #[test]
+    fn many_to_many() {
+        let mut world = World::new();
+        let a = world.spawn(());
+        let b = world.spawn(());
+        let x = world.spawn(());
+        let y = world.spawn(());
+
+        world.relate::<Owns>(a, x).unwrap();
+        world.relate::<Owns>(a, y).unwrap();
+        world.relate::<Owns>(b, x).unwrap();
+        world.relate::<Owns>(b, x).unwrap();
+        assert_eq!(targets::<Owns>(&world, a), vec![x, y]);
+        assert_eq!(sources::<Owns>(&world, x), vec![a, b]);
+        assert_eq!(sources::<Owns>(&world, y), vec![a]);
+
+        assert!(world.unrelate::<Owns>(a, y));
+        assert!(!world.unrelate::<Owns>(a, y));
+        assert!(world.get::<RelationSources<Owns>>(y).is_err());
+        assert_eq!(targets::<Owns>(&world, a), vec![x]);
+
+        world.unrelate_all::<Owns>(b);
+        assert!(world.get::<RelationTargets<Owns>>(b).is_err());
+        assert_eq!(sources::<Owns>(&world, x), vec![a]);
+    }
+
+    #[test]
+    fn exclusive_relation() {
+        let mut world = World::new();
+        let child = world.spawn(());
+        let first = world.spawn(());
+        let second = world.spawn(());
+
+        world.relate::<ChildOf>(child, first).unwrap();
+        world.relate::<ChildOf>(child, second).unwrap();
+        assert_eq!(targets::<ChildOf>(&world, child), vec![second]);
+        assert!(sources::<ChildOf>(&world, first).is_empty());
+        assert_eq!(sources::<ChildOf>(&world, second), vec![child]);
+    }
+
+    #[test]
+    fn despawned_entities_are_cleaned_up() {
+        let mut world = World::new();
+        world.register_relation::<Owns>();
+        let a = world.spawn(());
+        let b = world.spawn(());
+        let x = world.spawn(());
+        world.relate::<Owns>(a, x).unwrap();
+        world.relate::<Owns>(b, x).unwrap();
+        world.relate::<Owns>(x, a).unwrap();
+        world.relate::<Owns>(x, x).unwrap();
+
+        world.despawn(a).unwrap();
+        assert!(world.relate::<Owns>(a, x).is_err());
+        assert_eq!(sources::<Owns>(&world, x), vec![b, x]);
+        assert_eq!(targets::<Owns>(&world, x), vec![x]);
+
+        world.despawn(x).unwrap();
+        assert!(world.get::<RelationTargets<Owns>>(b).is_err());
+        assert!(world.get::<RelationSources<Owns>>(b).is_err());
+    }
+
+    #[test]
+    fn removing_one_end_unlinks_the_other() {
+        let mut world = World::new();
+        world.register_relation::<Owns>();
+        let a = world.spawn(());
+        let x = world.spawn(());
+        let y = world.spawn(());
+        world.relate::<Owns>(a, x).unwrap();
+        world.relate::<Owns>(a, y).unwrap();
+
+        world.remove_one::<RelationTargets<Owns>>(a).unwrap();
+        assert!(sources::<Owns>(&world, x).is_empty());
+        assert!(sources::<Owns>(&world, y).is_empty());
+    }
+
+    #[test]
+    fn relating_registers_the_cleanup_once() {
+        let mut world = World::new();
+        world.register_relation::<Owns>();
+        let a = world.spawn(());
+        let x = world.spawn(());
+        world.relate::<Owns>(a, x).unwrap();
+        world.relate::<ChildOf>(a, x).unwrap();
+
+        world.despawn(x).unwrap();
+        assert!(world.get::<RelationTargets<Owns>>(a).is_err());
+        assert!(world.get::<RelationTargets<ChildOf>>(a).is_err());
+        assert_eq!(world.relations.len(), 2);
+    }
+}
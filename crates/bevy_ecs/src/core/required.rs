@@ -0,0 +1,259 @@
+use crate::{
+    Component, ComponentId, DynamicBundle, Entity, EntityBuilder, FromResources, Resources,
+    TypeInfo, World,
+};
+use bevy_utils::{tracing::warn, HashSet};
+
+use super::archetype::ComponentIdMap;
+
+/// A component that only works next to other components on the same entity. Once registered with
+/// [World::register_required_components], adding it to an entity that lacks its required components inserts them,
+/// using their [FromResources] impl (which every `Default` type has).
+///
+/// The missing components are stored by the same [World::spawn] or [World::insert] as the component requiring them,
+/// so the entity only moves to a new archetype once. They are built from the [Resources] lent with
+/// [World::with_resources], which [Commands](crate::Commands) do. Outside of it, they are built from empty
+/// [Resources] and a warning is logged.
+///
+/// ```
+/// use bevy_ecs::{RequiredComponents, Resources, World};
+///
+/// #[derive(Default)]
+/// struct Velocity(f32);
+///
+/// #[derive(RequiredComponents)]
+/// #[require(Velocity)]
+/// struct Position(f32);
+///
+/// let mut world = World::new();
+/// world.register_required_components::<Position>();
+///
+/// let entity = world.with_resources(&mut Resources::default(), |world| world.spawn((Position(1.0),)));
+/// assert_eq!(world.get::<Velocity>(entity).unwrap().0, 0.0);
+/// ```
+pub trait RequiredComponents: Component {
+    /// Registers the required components of `Self` on `world` with [World::require]
+    fn register_required(world: &mut World);
+}
+
+/// A component registered with [World::require], and how to build it
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RequiredComponent {
+    info: TypeInfo,
+    /// The name of the component requiring this one
+    required_by: &'static str,
+    add: fn(&mut EntityBuilder, &Resources),
+}
+
+/// The components required by each component type
+pub(crate) type Requirements = ComponentIdMap<Vec<RequiredComponent>>;
+
+impl World {
+    /// Inserts an `R` component built with [FromResources] whenever a `T` component is added to an entity without an
+    /// `R`. Requirements of `R` itself are inserted too. See [RequiredComponents].
+    pub fn require<T: Component, R: Component + FromResources>(&mut self) {
+        let requirements = self.requirements.entry(ComponentId::of::<T>()).or_default();
+        if requirements
+            .iter()
+            .all(|required| required.info.id() != ComponentId::of::<R>())
+        {
+            requirements.push(RequiredComponent {
+                info: TypeInfo::of::<R>(),
+                required_by: std::any::type_name::<T>(),
+                add: |builder, resources| {
+                    builder.add(R::from_resources(resources));
+                },
+            });
+        }
+    }
+
+    /// Registers the required components of `T`, see [RequiredComponents]
+    pub fn register_required_components<T: RequiredComponents>(&mut self) {
+        T::register_required(self);
+    }
+
+    /// Builds the components required by the components of `bundle` that `entity` is missing. `entity` is `None` when
+    /// it is being spawned. Returns `None` if nothing is missing.
+    pub(crate) fn build_required(
+        &self,
+        entity: Option<Entity>,
+        bundle: &impl DynamicBundle,
+    ) -> Option<(EntityBuilder, Vec<TypeInfo>)> {
+        if self.requirements.is_empty() {
+            return None;
+        }
+        let has_component =
+            |ty| matches!(entity, Some(entity) if self.has_component_type(entity, ty));
+        let mut present = bundle.with_ids(|ids| ids.iter().copied().collect::<HashSet<_>>());
+        // only the components that are added bring their requirements
+        let mut added = present
+            .iter()
+            .copied()
+            .filter(|ty| !has_component(*ty))
+            .collect::<Vec<_>>();
+
+        let lent_resources = self.hooks.lent_resources();
+        let empty_resources;
+        let resources = match lent_resources {
+            Some(resources) => resources,
+            None => {
+                empty_resources = Resources::default();
+                &empty_resources
+            }
+        };
+        let mut builder = EntityBuilder::new();
+        let mut infos = Vec::new();
+        while let Some(ty) = added.pop() {
+            for required in self.requirements.get(&ty).into_iter().flatten() {
+                let id = required.info.id();
+                if has_component(id) || !present.insert(id) {
+                    continue;
+                }
+                if lent_resources.is_none() {
+                    warn!(
+                        "{} requires {}, which is built from empty resources because none are lent to the world. \
+                        Add {} within World::with_resources to build it from your resources.",
+                        required.required_by,
+                        required.info.type_name(),
+                        required.required_by,
+                    );
+                }
+                (required.add)(&mut builder, resources);
+                infos.push(required.info);
+                added.push(id);
+            }
+        }
+        if infos.is_empty() {
+            None
+        } else {
+            Some((builder, infos))
+        }
+    }
+}
+
+/// A bundle along with the components it requires, see [World::build_required]
+pub(crate) struct WithRequired<B> {
+    bundle: B,
+    required: EntityBuilder,
+    info: Vec<TypeInfo>,
+    ids: Vec<ComponentId>,
+}
+
+impl<B: DynamicBundle> WithRequired<B> {
+    pub(crate) fn new(
+        bundle: B,
+        (required, required_info): (EntityBuilder, Vec<TypeInfo>),
+    ) -> Self {
+        let mut info = bundle.type_info();
+        info.extend(required_info);
+        info.sort();
+        let ids = info.iter().map(|info| info.id()).collect();
+        Self {
+            bundle,
+            required,
+            info,
+            ids,
+        }
+    }
+}
+
+impl<B: DynamicBundle> DynamicBundle for WithRequired<B> {
+    fn with_ids<T>(&self, f: impl FnOnce(&[ComponentId]) -> T) -> T {
+        f(&self.ids)
+    }
+
+    fn type_info(&self) -> Vec<TypeInfo> {
+        self.info.clone()
+    }
+
+    unsafe fn put(self, mut f: impl FnMut(*mut u8, ComponentId, usize) -> bool) {
+        self.bundle.put(&mut f);
+        let mut required = self.required;
+        required.build().put(f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Commands, FromResources, Resources, World};
+
+    #[derive(Debug, PartialEq)]
+    struct Scale(f32);
+
+    impl FromResources for Scale {
+        fn from_resources(resources: &Resources) -> Self {
+            Scale(*resources.get::<f32>().unwrap())
+        }
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Visible(bool);
+
+    struct Sprite;
+
+    fn setup() -> (World, Resources) {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(2.0f32);
+        world.require::<Sprite, Scale>();
+        world.require::<Scale, Visible>();
+        (world, resources)
+    }
+
+    #[test]
+    fn inserts_missing_components() {
+        let (mut world, mut resources) = setup();
+
+        let (a, b, c) = world.with_resources(&mut resources, |world| {
+            let a = world.spawn((Sprite,));
+            let b = world.spawn((Sprite, Scale(1.0)));
+            let c = world.spawn(());
+            world.insert_one(c, Sprite).unwrap();
+            (a, b, c)
+        });
+
+        assert_eq!(*world.get::<Scale>(a).unwrap(), Scale(2.0));
+        assert_eq!(*world.get::<Visible>(a).unwrap(), Visible(false));
+        assert_eq!(*world.get::<Scale>(b).unwrap(), Scale(1.0));
+        assert_eq!(*world.get::<Visible>(b).unwrap(), Visible(false));
+        assert_eq!(*world.get::<Scale>(c).unwrap(), Scale(2.0));
+    }
+
+    #[test]
+    fn stores_required_components_with_the_bundle() {
+        let (mut world, mut resources) = setup();
+        world.on_add::<Sprite>(|world, _, entity| {
+            assert_eq!(*world.get::<Scale>(entity).unwrap(), Scale(2.0));
+            assert!(world.get::<Visible>(entity).is_ok());
+        });
+
+        let entity = world.spawn(());
+        world.with_resources(&mut resources, |world| {
+            world.insert_one(entity, Sprite).unwrap();
+            world.spawn((Sprite,));
+            world.spawn_batch(vec![(Sprite,), (Sprite,)]).for_each(drop);
+        });
+
+        assert_eq!(world.query::<(&Sprite, &Scale, &Visible)>().count(), 4);
+        // no archetype was made for the components without their requirements
+        let archetypes = world
+            .archetypes()
+            .filter(|archetype| archetype.has::<Sprite>() || archetype.has::<Scale>())
+            .count();
+        assert_eq!(archetypes, 1);
+    }
+
+    #[test]
+    fn commands_insert_required_components() {
+        let (mut world, mut resources) = setup();
+
+        let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
+        commands.spawn((Sprite,)).with(Visible(true));
+        let entity = commands.current_entity().unwrap();
+        commands.apply(&mut world, &mut resources);
+
+        assert_eq!(*world.get::<Scale>(entity).unwrap(), Scale(2.0));
+        assert_eq!(*world.get::<Visible>(entity).unwrap(), Visible(true));
+    }
+}
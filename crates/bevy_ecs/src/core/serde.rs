@@ -3,12 +3,14 @@
 use crate::Entity;
 use serde::{de::Visitor, Deserialize, Serialize, Serializer};
 
+/// Entities are serialized as a `u64` holding the generation in its high bits and the id in its low bits, so an
+/// entity with a generation of 0 serializes to its id.
 impl Serialize for Entity {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_u32(self.id())
+        serializer.serialize_u64(u64::from(self.generation) << 32 | u64::from(self.id))
     }
 }
 
@@ -17,7 +19,7 @@ impl<'de> Deserialize<'de> for Entity {
     where
         D: serde::Deserializer<'de>,
     {
-        Ok(deserializer.deserialize_u32(EntityVisitor)?)
+        Ok(deserializer.deserialize_u64(EntityVisitor)?)
     }
 }
 
@@ -36,4 +38,34 @@ impl<'de> Visitor<'de> for EntityVisitor {
     {
         Ok(Entity::new(v))
     }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+    where
+        E: serde::de::Error,
+    {
+        Ok(Entity {
+            generation: (v >> 32) as u32,
+            id: v as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Entity, World};
+
+    #[test]
+    fn entity_round_trip() {
+        let mut world = World::new();
+        let despawned = world.spawn(());
+        world.despawn(despawned).unwrap();
+        let entity = world.spawn(());
+        assert_eq!(entity.id(), despawned.id());
+        assert_ne!(entity, despawned);
+
+        for entity in [Entity::new(7), entity, Entity::from_bits(u64::MAX)].iter() {
+            let serialized = ron::ser::to_string(entity).unwrap();
+            assert_eq!(ron::de::from_str::<Entity>(&serialized).unwrap(), *entity);
+        }
+    }
 }
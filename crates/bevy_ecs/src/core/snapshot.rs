@@ -0,0 +1,552 @@
+use crate::{
+    Archetype, Component, ComponentFlags, ComponentId, Entity, EntityBuilder, Resource, Resources,
+    World,
+};
+use bevy_utils::HashSet;
+use std::{any::type_name, ptr::NonNull};
+
+use super::{entities::EntitiesSnapshot, sparse_set::SparseRows};
+
+/// Lists the components and resources captured by [WorldSnapshot]s. Only `Clone` types can be registered.
+///
+/// Snapshots are meant to be taken often, for example every frame for rollback networking. Taking a snapshot into
+/// an existing one with [SnapshotRegistry::snapshot_into] reuses its storage, and clones values with
+/// [Clone::clone_from].
+///
+/// ```
+/// # use bevy_ecs::*;
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Position(f32);
+///
+/// let mut world = World::new();
+/// let mut resources = Resources::default();
+/// let mut registry = SnapshotRegistry::default();
+/// registry.register_component::<Position>();
+///
+/// let e = world.spawn((Position(0.0),));
+/// let snapshot = registry.snapshot(&world, &resources);
+/// world.get_mut::<Position>(e).unwrap().0 = 10.0;
+/// world.spawn((Position(5.0),));
+///
+/// snapshot.restore(&mut world, &mut resources);
+/// assert_eq!(world.query::<&Position>().collect::<Vec<_>>(), vec![&Position(0.0)]);
+/// ```
+#[derive(Default)]
+pub struct SnapshotRegistry {
+    components: Vec<(ComponentId, ColumnSnapshotFactory)>,
+    resources: Vec<ResourceSnapshotFactory>,
+}
+
+/// Creates the empty storage of a registered component type in a [WorldSnapshot]
+type ColumnSnapshotFactory = fn() -> Box<dyn ColumnSnapshot>;
+
+/// Creates the empty storage of a registered resource in a [WorldSnapshot]
+type ResourceSnapshotFactory = fn() -> Box<dyn ResourceSnapshot>;
+
+impl SnapshotRegistry {
+    /// Captures `T` components in snapshots. Components that are not registered are left untouched when a snapshot
+    /// is restored, except on the entities that are despawned or spawned back by the restore.
+    pub fn register_component<T: Component + Clone>(&mut self) -> &mut Self {
+        let id = ComponentId::of::<T>();
+        if !self
+            .components
+            .iter()
+            .any(|(registered, _)| *registered == id)
+        {
+            self.components
+                .push((id, || Box::new(ComponentColumn::<T>::default())));
+        }
+        self
+    }
+
+    /// Captures the `T` resource in snapshots
+    pub fn register_resource<T: Resource + Clone>(&mut self) -> &mut Self {
+        self.resources
+            .push(|| Box::new(ResourceSlot::<T>::default()));
+        self
+    }
+
+    pub fn snapshot(&self, world: &World, resources: &Resources) -> WorldSnapshot {
+        let mut snapshot = WorldSnapshot::default();
+        self.snapshot_into(world, resources, &mut snapshot);
+        snapshot
+    }
+
+    /// Captures the registered components and resources into `snapshot`, reusing its storage when it was taken
+    /// from the same world with the same registry
+    ///
+    /// # Panics
+    /// Panics if a registered component or resource is borrowed mutably
+    pub fn snapshot_into(
+        &self,
+        world: &World,
+        resources: &Resources,
+        snapshot: &mut WorldSnapshot,
+    ) {
+        world.entities.snapshot_into(&mut snapshot.entities);
+
+        snapshot.archetypes.truncate(world.archetypes.len());
+        // every archetype gets a column for every registered component, so restoring it also removes the components
+        // that were added since
+        let components = &self.components;
+        for (index, archetype) in world.archetypes.iter().enumerate() {
+            if snapshot.archetypes.len() == index {
+                snapshot.archetypes.push(ArchetypeSnapshot::default());
+            }
+            let archetype_snapshot = &mut snapshot.archetypes[index];
+            let same_components = archetype_snapshot.columns.len() == components.len()
+                && archetype_snapshot
+                    .columns
+                    .iter()
+                    .zip(components.iter())
+                    .all(|(column, (id, _))| column.id() == *id);
+            if !same_components {
+                archetype_snapshot.columns = components
+                    .iter()
+                    .map(|(_, new_column)| new_column())
+                    .collect();
+            }
+            archetype_snapshot.entities.clear();
+            archetype_snapshot
+                .entities
+                .extend(archetype.iter_entities().copied());
+            for column in archetype_snapshot.columns.iter_mut() {
+                column.capture(archetype);
+            }
+        }
+
+        snapshot.removed.truncate(self.components.len());
+        for (index, (id, _)) in self.components.iter().enumerate() {
+            if snapshot.removed.len() == index {
+                snapshot.removed.push((*id, Vec::new()));
+            }
+            let (removed_id, removed) = &mut snapshot.removed[index];
+            *removed_id = *id;
+            removed.clear();
+            if let Some(entities) = world.removed_components.get(id) {
+                removed.extend_from_slice(entities);
+            }
+        }
+
+        if snapshot.resources.len() != self.resources.len() {
+            snapshot.resources = self.resources.iter().map(|new_slot| new_slot()).collect();
+        }
+        for slot in snapshot.resources.iter_mut() {
+            slot.capture(resources);
+        }
+    }
+}
+
+/// The state of a [World] and of some [Resources] at some point, created by a [SnapshotRegistry]. It includes
+/// entity ids and generations, the registered components with their change trackers, and the registered
+/// resources.
+#[derive(Default)]
+pub struct WorldSnapshot {
+    entities: EntitiesSnapshot,
+    /// Indexed like the archetypes of the world
+    archetypes: Vec<ArchetypeSnapshot>,
+    removed: Vec<(ComponentId, Vec<Entity>)>,
+    resources: Vec<Box<dyn ResourceSnapshot>>,
+}
+
+impl WorldSnapshot {
+    /// Restores `world` and `resources` to the captured state, in place. Entities spawned since the snapshot was taken
+    /// are despawned, and entities despawned since are spawned back with the same ids, generations, registered
+    /// components and change trackers, so entities spawned afterwards get the same ids as they did after the
+    /// snapshot was taken. The registered components of the other entities are restored where they are stored,
+    /// and their other components are left untouched.
+    ///
+    /// Resources are flagged as mutated when they are restored. Resources that did not exist when the snapshot was
+    /// taken are left as they are. Component hooks only run for the components that are added or removed by the
+    /// restore, with `resources` lent to them.
+    pub fn restore(&self, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| self.restore_entities(world));
+
+        world.removed_components.clear();
+        for (id, removed) in self.removed.iter() {
+            if !removed.is_empty() {
+                world.removed_components.insert(*id, removed.clone());
+            }
+        }
+
+        for slot in self.resources.iter() {
+            slot.restore(resources);
+        }
+    }
+
+    fn restore_entities(&self, world: &mut World) {
+        world.flush();
+        let snapshot_entities = self
+            .archetypes
+            .iter()
+            .flat_map(|archetype_snapshot| archetype_snapshot.entities.iter().copied())
+            .collect::<HashSet<_>>();
+        let spawned = world
+            .archetypes
+            .iter()
+            .flat_map(|archetype| archetype.iter_entities().copied())
+            .filter(|entity| !snapshot_entities.contains(entity))
+            .collect::<Vec<_>>();
+        for entity in spawned {
+            // a hook may have despawned it already
+            let _ = world.despawn(entity);
+        }
+
+        // every entity left existed when the snapshot was taken, so it keeps its location
+        let kept = world
+            .archetypes
+            .iter()
+            .flat_map(|archetype| archetype.iter_entities().copied())
+            .map(|entity| (entity, world.entities.get(entity).unwrap()))
+            .collect::<Vec<_>>();
+        world.entities.restore(&self.entities);
+        for (entity, location) in kept.iter() {
+            world.entities.meta[entity.id as usize].location = *location;
+        }
+        let kept = kept
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect::<HashSet<_>>();
+
+        let mut builder = EntityBuilder::new();
+        for archetype_snapshot in self.archetypes.iter() {
+            for (row, entity) in archetype_snapshot.entities.iter().enumerate() {
+                if kept.contains(entity) {
+                    for column in archetype_snapshot.columns.iter() {
+                        column.restore_to(row, world, *entity);
+                    }
+                    continue;
+                }
+                for column in archetype_snapshot.columns.iter() {
+                    column.add_to(row, &mut builder);
+                }
+                world.spawn_allocated(*entity, builder.build());
+                world.run_queued_hooks();
+                for column in archetype_snapshot.columns.iter() {
+                    column.restore_flags(row, world, *entity);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct ArchetypeSnapshot {
+    entities: Vec<Entity>,
+    columns: Vec<Box<dyn ColumnSnapshot>>,
+}
+
+/// The `T` components of the entities of an archetype
+trait ColumnSnapshot: Send + Sync {
+    fn id(&self) -> ComponentId;
+
+    /// Replaces the content of the column with the components of `archetype`. The column is left empty if the
+    /// archetype has none.
+    fn capture(&mut self, archetype: &Archetype);
+
+    /// Adds a clone of the component in `row` to `builder`, if the entity had one
+    fn add_to(&self, row: usize, builder: &mut EntityBuilder);
+
+    /// Restores the change trackers of the component of `entity` to the ones in `row`
+    fn restore_flags(&self, row: usize, world: &mut World, entity: Entity);
+
+    /// Restores the component of `entity` to the one in `row`. It is cloned into the existing component if there is
+    /// one, otherwise it is inserted, or removed if the entity had none.
+    fn restore_to(&self, row: usize, world: &mut World, entity: Entity);
+}
+
+struct ComponentColumn<T> {
+    /// `None` for the entities that don't have a `T`, which happens when `T` is stored in sparse sets
+    rows: Vec<Option<(T, ComponentFlags)>>,
+}
+
+impl<T> ComponentColumn<T> {
+    fn row(&self, row: usize) -> Option<&(T, ComponentFlags)> {
+        self.rows.get(row).and_then(Option::as_ref)
+    }
+}
+
+impl<T> Default for ComponentColumn<T> {
+    fn default() -> Self {
+        Self { rows: Vec::new() }
+    }
+}
+
+enum ColumnSource<T> {
+    Table(NonNull<T>, NonNull<ComponentFlags>),
+    SparseSet(SparseRows),
+}
+
+impl<T: Component + Clone> ColumnSnapshot for ComponentColumn<T> {
+    fn id(&self) -> ComponentId {
+        ComponentId::of::<T>()
+    }
+
+    fn capture(&mut self, archetype: &Archetype) {
+        let borrow = match archetype.component_borrow::<T>() {
+            Some(borrow) => borrow,
+            None => {
+                self.rows.clear();
+                return;
+            }
+        };
+        if !borrow.borrow() {
+            panic!("{} already borrowed uniquely.", type_name::<T>());
+        }
+
+        // SAFE: the components are borrowed, and rows are only looked up below the length of the archetype
+        unsafe {
+            let source = match archetype.get_with_type_state::<T>() {
+                Some((components, type_state)) => {
+                    ColumnSource::Table(components, type_state.component_flags())
+                }
+                None => ColumnSource::SparseSet(
+                    archetype.sparse_rows(ComponentId::of::<T>(), 0).unwrap(),
+                ),
+            };
+            self.rows.truncate(archetype.len());
+            for index in 0..archetype.len() {
+                let component = match &source {
+                    ColumnSource::Table(components, flags) => {
+                        Some((&*components.as_ptr().add(index), *flags.as_ptr().add(index)))
+                    }
+                    ColumnSource::SparseSet(rows) => rows.get(index).map(|(component, flags)| {
+                        (&*component.cast::<T>().as_ptr(), *flags.as_ptr())
+                    }),
+                };
+                match (self.rows.get_mut(index), component) {
+                    (Some(Some((value, flags))), Some((component, component_flags))) => {
+                        value.clone_from(component);
+                        *flags = component_flags;
+                    }
+                    (Some(row), component) => {
+                        *row = component.map(|(component, flags)| (component.clone(), flags))
+                    }
+                    (None, component) => self
+                        .rows
+                        .push(component.map(|(component, flags)| (component.clone(), flags))),
+                }
+            }
+        }
+
+        borrow.release();
+    }
+
+    fn add_to(&self, row: usize, builder: &mut EntityBuilder) {
+        if let Some((component, _)) = self.row(row) {
+            builder.add(component.clone());
+        }
+    }
+
+    fn restore_flags(&self, row: usize, world: &mut World, entity: Entity) {
+        if let Some((_, flags)) = self.row(row) {
+            // SAFE: the world is borrowed mutably
+            if let Some((_, component_flags)) = unsafe { get_component::<T>(world, entity) } {
+                unsafe { *component_flags.as_ptr() = *flags };
+            }
+        }
+    }
+
+    fn restore_to(&self, row: usize, world: &mut World, entity: Entity) {
+        // SAFE: the world is borrowed mutably
+        let component = unsafe { get_component::<T>(world, entity) };
+        match (self.row(row), component) {
+            (Some((value, flags)), Some((component, component_flags))) => unsafe {
+                (*component.as_ptr()).clone_from(value);
+                *component_flags.as_ptr() = *flags;
+            },
+            (Some((value, _)), None) => {
+                // hooks may have despawned the entity
+                if world.insert_one(entity, value.clone()).is_ok() {
+                    self.restore_flags(row, world, entity);
+                }
+            }
+            (None, Some(_)) => {
+                let _ = world.remove_one::<T>(entity);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Returns the `T` component of `entity` along with its flags, whatever its storage
+///
+/// # Safety
+/// The component must not be borrowed elsewhere while the returned pointers are used
+unsafe fn get_component<T: Component>(
+    world: &World,
+    entity: Entity,
+) -> Option<(NonNull<T>, NonNull<ComponentFlags>)> {
+    let location = world.get_entity_location(entity)?;
+    world.archetypes[location.archetype as usize].get_component::<T>(location.index)
+}
+
+/// The value of a resource
+trait ResourceSnapshot: Send + Sync {
+    fn capture(&mut self, resources: &Resources);
+
+    fn restore(&self, resources: &mut Resources);
+}
+
+struct ResourceSlot<T> {
+    value: Option<T>,
+}
+
+impl<T> Default for ResourceSlot<T> {
+    fn default() -> Self {
+        Self { value: None }
+    }
+}
+
+impl<T: Resource + Clone> ResourceSnapshot for ResourceSlot<T> {
+    fn capture(&mut self, resources: &Resources) {
+        match (&mut self.value, resources.get::<T>()) {
+            (Some(value), Some(resource)) => value.clone_from(&resource),
+            (value, resource) => *value = resource.map(|resource| (*resource).clone()),
+        }
+    }
+
+    fn restore(&self, resources: &mut Resources) {
+        let value = match &self.value {
+            Some(value) => value,
+            None => return,
+        };
+        if let Some(mut resource) = resources.get_mut::<T>() {
+            resource.clone_from(value);
+            return;
+        }
+        resources.insert(value.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SnapshotRegistry;
+    use crate::{Changed, Entity, Resources, StorageType, World};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Position(f32);
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Selected;
+
+    struct NotRegistered;
+
+    fn positions(world: &World) -> Vec<(Entity, f32)> {
+        let mut positions = world
+            .query::<(Entity, &Position)>()
+            .map(|(entity, position)| (entity, position.0))
+            .collect::<Vec<_>>();
+        positions.sort_by_key(|(entity, _)| *entity);
+        positions
+    }
+
+    #[test]
+    fn snapshot_and_restore() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        world
+            .set_storage_type::<Selected>(StorageType::SparseSet)
+            .unwrap();
+        resources.insert(vec![1u32, 2]);
+        let mut registry = SnapshotRegistry::default();
+        registry
+            .register_component::<Position>()
+            .register_component::<Selected>()
+            .register_resource::<Vec<u32>>();
+
+        let a = world.spawn((Position(1.0), NotRegistered));
+        let b = world.spawn((Position(2.0), Selected));
+        let c = world.spawn((3u8,));
+        world.despawn(c).unwrap();
+        world.clear_trackers();
+        world.get_mut::<Position>(b).unwrap().0 = 20.0;
+
+        let snapshot = registry.snapshot(&world, &resources);
+
+        let d = world.spawn((Position(4.0),));
+        world.despawn(a).unwrap();
+        world.get_mut::<Position>(b).unwrap().0 = 200.0;
+        world.remove_one::<Selected>(b).unwrap();
+        resources.get_mut::<Vec<u32>>().unwrap().push(3);
+
+        snapshot.restore(&mut world, &mut resources);
+        assert_eq!(positions(&world), vec![(a, 1.0), (b, 20.0)]);
+        assert!(world.get::<NotRegistered>(a).is_err());
+        assert!(world.get::<Selected>(b).is_ok());
+        assert!(!world.contains(c));
+        assert_eq!(*resources.get::<Vec<u32>>().unwrap(), vec![1, 2]);
+        // change trackers are restored
+        assert_eq!(
+            world
+                .query_filtered::<Entity, Changed<Position>>()
+                .collect::<Vec<_>>(),
+            vec![b]
+        );
+        // entity ids are allocated the same way again
+        assert_eq!(world.spawn((Position(4.0),)), d);
+    }
+
+    #[test]
+    fn restore_in_place() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        let mut registry = SnapshotRegistry::default();
+        registry
+            .register_component::<Position>()
+            .register_component::<Selected>();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let add_events = events.clone();
+        world.on_add::<Position>(move |_, _, entity| add_events.lock().push(("add", entity)));
+        let remove_events = events.clone();
+        world.on_remove::<Selected>(move |_, _, entity| {
+            remove_events.lock().push(("remove", entity))
+        });
+
+        let a = world.spawn((Position(1.0), NotRegistered));
+        let b = world.spawn((Position(2.0),));
+        let snapshot = registry.snapshot(&world, &resources);
+
+        world.get_mut::<Position>(a).unwrap().0 = 10.0;
+        world.insert_one(a, Selected).unwrap();
+        world.remove_one::<Position>(b).unwrap();
+        world.insert_one(b, NotRegistered).unwrap();
+        events.lock().clear();
+
+        snapshot.restore(&mut world, &mut resources);
+        assert_eq!(positions(&world), vec![(a, 1.0), (b, 2.0)]);
+        assert!(world.get::<Selected>(a).is_err());
+        // components that are not registered are left as they are
+        assert!(world.get::<NotRegistered>(a).is_ok());
+        assert!(world.get::<NotRegistered>(b).is_ok());
+        // hooks only run for the components that were added or removed
+        assert_eq!(*events.lock(), vec![("remove", a), ("add", b)]);
+    }
+
+    #[test]
+    fn repeated_snapshots() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        let mut registry = SnapshotRegistry::default();
+        registry.register_component::<Position>();
+
+        let mut snapshot = registry.snapshot(&world, &resources);
+        let mut entities = Vec::new();
+        for frame in 0..3 {
+            entities.push(world.spawn((Position(frame as f32),)));
+            for (_, mut position) in world.query_mut::<(Entity, &mut Position)>() {
+                position.0 += 1.0;
+            }
+            registry.snapshot_into(&world, &resources, &mut snapshot);
+        }
+        let expected = positions(&world);
+
+        world.despawn(entities[0]).unwrap();
+        world.spawn((Position(0.0), 1u8));
+        snapshot.restore(&mut world, &mut resources);
+        assert_eq!(positions(&world), expected);
+        assert_eq!(expected.len(), 3);
+    }
+}
@@ -0,0 +1,549 @@
+use crate::{AtomicBorrow, ComponentFlags, ComponentId, Entity, TypeInfo};
+use std::{
+    alloc::{alloc, dealloc, Layout},
+    cell::UnsafeCell,
+    fmt,
+    ptr::{self, NonNull},
+};
+use thiserror::Error;
+
+use super::archetype::ComponentIdMap;
+
+/// How the components of a type are stored in a [World](crate::World). See [World::set_storage_type](crate::World::set_storage_type).
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum StorageType {
+    /// Components are stored in the tables of the [Archetype](crate::Archetype) of their entity. Iterating over
+    /// them is as fast as it gets, but adding or removing one moves all the components of the entity to another
+    /// archetype.
+    Table,
+    /// Components are stored in a sparse set indexed by entity. Adding and removing them is cheap because the
+    /// entity stays in its archetype, but iterating over them is slower. Prefer this storage for components that
+    /// are added and removed often, like markers.
+    SparseSet,
+}
+
+impl Default for StorageType {
+    fn default() -> Self {
+        StorageType::Table
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum StorageTypeError {
+    #[error("The storage type of {0} cannot change while components of that type exist.")]
+    ComponentsExist(&'static str),
+}
+
+const EMPTY: u32 = u32::MAX;
+
+/// The components of a single type, packed densely and indexed by entity id
+pub(crate) struct ComponentSparseSet {
+    type_info: TypeInfo,
+    // UnsafeCell allows unique references into `data` and `flags` to be constructed while shared references
+    // containing the set exist, like in archetypes
+    data: UnsafeCell<NonNull<u8>>,
+    capacity: usize,
+    entities: Vec<Entity>,
+    flags: Vec<UnsafeCell<ComponentFlags>>,
+    sparse: Vec<u32>,
+    borrow: AtomicBorrow,
+}
+
+impl ComponentSparseSet {
+    pub(crate) fn new(type_info: TypeInfo) -> Self {
+        let align = type_info.layout().align();
+        Self {
+            type_info,
+            // aligned, as the data of zero sized components is never allocated
+            data: UnsafeCell::new(NonNull::new(align as *mut u8).unwrap()),
+            capacity: 0,
+            entities: Vec::new(),
+            flags: Vec::new(),
+            sparse: Vec::new(),
+            borrow: AtomicBorrow::new(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    #[inline]
+    pub(crate) fn type_info(&self) -> &TypeInfo {
+        &self.type_info
+    }
+
+    #[inline]
+    pub(crate) fn borrow(&self) -> &AtomicBorrow {
+        &self.borrow
+    }
+
+    #[inline]
+    pub(crate) fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    #[inline]
+    fn dense_index(&self, entity: Entity) -> Option<usize> {
+        let index = *self.sparse.get(entity.id as usize)?;
+        if index != EMPTY && self.entities[index as usize] == entity {
+            Some(index as usize)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, entity: Entity) -> bool {
+        self.dense_index(entity).is_some()
+    }
+
+    #[inline]
+    unsafe fn get_unchecked(&self, index: usize) -> *mut u8 {
+        (*self.data.get())
+            .as_ptr()
+            .add(index * self.type_info.layout().size())
+    }
+
+    /// Returns the component of `entity` along with its flags
+    #[inline]
+    pub(crate) fn get(&self, entity: Entity) -> Option<(NonNull<u8>, NonNull<ComponentFlags>)> {
+        let index = self.dense_index(entity)?;
+        unsafe {
+            Some((
+                NonNull::new_unchecked(self.get_unchecked(index)),
+                NonNull::new_unchecked(self.flags[index].get()),
+            ))
+        }
+    }
+
+    /// Moves the component pointed to by `component` into the set. If `entity` already had a component, it is
+    /// dropped and replaced, and true is returned.
+    ///
+    /// # Safety
+    /// `component` must point to a valid component of the type of this set, which must not be used afterwards
+    pub(crate) unsafe fn insert(&mut self, entity: Entity, component: *mut u8) -> bool {
+        let size = self.type_info.layout().size();
+        if let Some(index) = self.dense_index(entity) {
+            let old = self.get_unchecked(index);
+            self.type_info.drop(old);
+            ptr::copy_nonoverlapping(component, old, size);
+            *self.flags[index].get_mut() = ComponentFlags::MUTATED;
+            return true;
+        }
+
+        let index = self.len();
+        if index == self.capacity {
+            self.grow();
+        }
+        ptr::copy_nonoverlapping(component, self.get_unchecked(index), size);
+        self.entities.push(entity);
+        self.flags.push(UnsafeCell::new(ComponentFlags::ADDED));
+        if self.sparse.len() <= entity.id as usize {
+            self.sparse.resize(entity.id as usize + 1, EMPTY);
+        }
+        self.sparse[entity.id as usize] = index as u32;
+        false
+    }
+
+    /// Takes the component of `entity` out of the set and passes it to `f`, which becomes responsible for dropping
+    /// it. Returns false if `entity` had no component.
+    pub(crate) fn remove_with(&mut self, entity: Entity, f: impl FnOnce(*mut u8)) -> bool {
+        let index = match self.dense_index(entity) {
+            Some(index) => index,
+            None => return false,
+        };
+        let last = self.len() - 1;
+        unsafe {
+            let removed = self.get_unchecked(index);
+            f(removed);
+            if index != last {
+                ptr::copy_nonoverlapping(
+                    self.get_unchecked(last),
+                    removed,
+                    self.type_info.layout().size(),
+                );
+            }
+        }
+        self.entities.swap_remove(index);
+        self.flags.swap_remove(index);
+        if index != last {
+            self.sparse[self.entities[index].id as usize] = index as u32;
+        }
+        self.sparse[entity.id as usize] = EMPTY;
+        true
+    }
+
+    /// Drops the component of `entity`. Returns false if `entity` had no component.
+    pub(crate) fn remove_and_drop(&mut self, entity: Entity) -> bool {
+        let type_info = self.type_info;
+        self.remove_with(entity, |component| unsafe { type_info.drop(component) })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for index in 0..self.len() {
+            unsafe {
+                self.type_info.drop(self.get_unchecked(index));
+            }
+        }
+        self.entities.clear();
+        self.flags.clear();
+        self.sparse.clear();
+    }
+
+    pub(crate) fn clear_trackers(&mut self) {
+        for flags in self.flags.iter_mut() {
+            *flags.get_mut() = ComponentFlags::empty();
+        }
+    }
+
+    fn grow(&mut self) {
+        let layout = self.type_info.layout();
+        let new_capacity = (self.capacity * 2).max(64);
+        if layout.size() != 0 {
+            unsafe {
+                let new_data = alloc(
+                    Layout::from_size_align(layout.size() * new_capacity, layout.align()).unwrap(),
+                );
+                let new_data = NonNull::new(new_data).unwrap();
+                if self.capacity != 0 {
+                    ptr::copy_nonoverlapping(
+                        self.data.get_mut().as_ptr(),
+                        new_data.as_ptr(),
+                        layout.size() * self.len(),
+                    );
+                    dealloc(self.data.get_mut().as_ptr(), self.data_layout());
+                }
+                *self.data.get_mut() = new_data;
+            }
+        }
+        self.capacity = new_capacity;
+    }
+
+    fn data_layout(&self) -> Layout {
+        let layout = self.type_info.layout();
+        Layout::from_size_align(layout.size() * self.capacity, layout.align()).unwrap()
+    }
+}
+
+impl Drop for ComponentSparseSet {
+    fn drop(&mut self) {
+        self.clear();
+        if self.capacity != 0 && self.type_info.layout().size() != 0 {
+            unsafe {
+                dealloc(self.data.get_mut().as_ptr(), self.data_layout());
+            }
+        }
+    }
+}
+
+/// The sparse sets of a [World](crate::World). They are shared with its archetypes, so queries can look up the
+/// components of the entities they iterate over.
+#[derive(Default)]
+pub(crate) struct SparseSets {
+    sets: UnsafeCell<ComponentIdMap<ComponentSparseSet>>,
+}
+
+impl SparseSets {
+    #[inline]
+    pub(crate) fn get(&self, ty: ComponentId) -> Option<&ComponentSparseSet> {
+        unsafe { (*self.sets.get()).get(&ty) }
+    }
+
+    #[inline]
+    pub(crate) fn contains(&self, ty: ComponentId) -> bool {
+        unsafe { (*self.sets.get()).contains_key(&ty) }
+    }
+
+    #[inline]
+    pub(crate) fn is_empty(&self) -> bool {
+        unsafe { (*self.sets.get()).is_empty() }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (ComponentId, &ComponentSparseSet)> {
+        unsafe { (*self.sets.get()).iter().map(|(ty, set)| (*ty, set)) }
+    }
+
+    /// The types of the components of `entity` that are stored in sparse sets
+    pub(crate) fn types_of(&self, entity: Entity) -> impl Iterator<Item = ComponentId> + '_ {
+        self.iter()
+            .filter(move |(_, set)| set.contains(entity))
+            .map(|(ty, _)| ty)
+    }
+
+    /// # Safety
+    /// Nothing else may access the sparse sets while the returned reference is live. The [World](crate::World)
+    /// ensures this by only calling this method when it is borrowed mutably.
+    #[allow(clippy::mut_from_ref)]
+    #[inline]
+    pub(crate) unsafe fn sets_mut(&self) -> &mut ComponentIdMap<ComponentSparseSet> {
+        &mut *self.sets.get()
+    }
+}
+
+impl fmt::Debug for SparseSets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sets = unsafe { &*self.sets.get() };
+        f.debug_map()
+            .entries(
+                sets.values()
+                    .map(|set| (set.type_info.type_name(), set.len())),
+            )
+            .finish()
+    }
+}
+
+/// The rows of an archetype, starting at some offset, looked up in a sparse set. Used by queries and filters on
+/// components stored in sparse sets.
+#[derive(Copy, Clone)]
+pub(crate) struct SparseRows {
+    entities: NonNull<Entity>,
+    set: NonNull<ComponentSparseSet>,
+}
+
+impl SparseRows {
+    /// # Safety
+    /// `offset` must be in bounds of the entities of the archetype
+    #[inline]
+    pub(crate) unsafe fn new(
+        entities: NonNull<Entity>,
+        offset: usize,
+        set: &ComponentSparseSet,
+    ) -> Self {
+        Self {
+            entities: NonNull::new_unchecked(entities.as_ptr().add(offset)),
+            set: NonNull::from(set),
+        }
+    }
+
+    #[inline]
+    unsafe fn entity(&self, n: usize) -> Entity {
+        *self.entities.as_ptr().add(n)
+    }
+
+    /// # Safety
+    /// `n` must be in bounds
+    #[inline]
+    pub(crate) unsafe fn contains(&self, n: usize) -> bool {
+        self.set.as_ref().contains(self.entity(n))
+    }
+
+    /// # Safety
+    /// `n` must be in bounds
+    #[inline]
+    pub(crate) unsafe fn get(&self, n: usize) -> Option<(NonNull<u8>, NonNull<ComponentFlags>)> {
+        self.set.as_ref().get(self.entity(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{StorageType, StorageTypeError};
+    use crate::{Added, Changed, Entity, Flags, Mut, Mutated, With, Without, World};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    #[derive(Debug, PartialEq)]
+    struct A(usize);
+    #[derive(Debug, PartialEq)]
+    struct Sparse(usize);
+    #[derive(Debug, PartialEq)]
+    struct Marker;
+
+    fn sparse_world() -> World {
+        let mut world = World::new();
+        world
+            .set_storage_type::<Sparse>(StorageType::SparseSet)
+            .unwrap();
+        world
+            .set_storage_type::<Marker>(StorageType::SparseSet)
+            .unwrap();
+        world
+    }
+
+    fn sorted<T: Ord>(mut values: Vec<T>) -> Vec<T> {
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn insert_and_remove_without_moving() {
+        let mut world = sparse_world();
+        let a = world.spawn((A(0), Sparse(0)));
+        let b = world.spawn((A(1),));
+        let c = world.spawn((Sparse(2),));
+        let archetype = world.get_entity_location(a).unwrap().archetype;
+        assert_eq!(world.get_entity_location(b).unwrap().archetype, archetype);
+
+        world.insert_one(b, Sparse(1)).unwrap();
+        world.insert(b, (Marker,)).unwrap();
+        assert_eq!(world.get_entity_location(b).unwrap().archetype, archetype);
+        assert_eq!(*world.get::<Sparse>(b).unwrap(), Sparse(1));
+        assert_eq!(*world.get::<Sparse>(c).unwrap(), Sparse(2));
+        assert!(world.has_component_type(b, std::any::TypeId::of::<Marker>()));
+
+        world.get_mut::<Sparse>(a).unwrap().0 = 10;
+        assert_eq!(world.remove_one::<Sparse>(a), Ok(Sparse(10)));
+        assert!(world.get::<Sparse>(a).is_err());
+        assert!(world.remove_one::<Sparse>(a).is_err());
+        assert_eq!(world.get_entity_location(a).unwrap().archetype, archetype);
+        assert_eq!(world.removed::<Sparse>(), &[a]);
+
+        // sparse and table components can be removed together
+        assert_eq!(world.remove::<(A, Marker)>(b), Ok((A(1), Marker)));
+        assert_eq!(*world.get::<Sparse>(b).unwrap(), Sparse(1));
+        world.remove_one_by_one::<(Sparse, Marker)>(c).unwrap();
+        assert!(world.get::<Sparse>(c).is_err());
+
+        world.despawn(b).unwrap();
+        let d = world.spawn((A(3),));
+        assert_eq!(d.id(), b.id());
+        assert!(world.get::<Sparse>(d).is_err());
+        assert_eq!(world.query::<&Sparse>().count(), 0);
+    }
+
+    #[test]
+    fn sparse_queries() {
+        let mut world = sparse_world();
+        let a = world.spawn((A(0), Sparse(0)));
+        let b = world.spawn((A(1),));
+        let c = world.spawn((Sparse(2), Marker));
+        let d = world.spawn((A(3), Sparse(3)));
+
+        assert_eq!(
+            sorted(
+                world
+                    .query::<(Entity, &Sparse)>()
+                    .map(|(e, s)| (e, s.0))
+                    .collect()
+            ),
+            vec![(a, 0), (c, 2), (d, 3)]
+        );
+        assert_eq!(world.query::<(&A, &Sparse)>().len(), 2);
+        assert_eq!(
+            sorted(
+                world
+                    .query::<(&A, Option<&Sparse>)>()
+                    .map(|(a, s)| (a.0, s.map(|s| s.0)))
+                    .collect()
+            ),
+            vec![(0, Some(0)), (1, None), (3, Some(3))]
+        );
+        assert_eq!(
+            world
+                .query_filtered::<Entity, With<Marker>>()
+                .collect::<Vec<_>>(),
+            vec![c]
+        );
+        assert_eq!(
+            sorted(
+                world
+                    .query_filtered::<Entity, (With<A>, Without<Sparse>)>()
+                    .collect()
+            ),
+            vec![b]
+        );
+        assert_eq!(*world.query_one::<&Sparse>(c).unwrap(), Sparse(2));
+        assert!(world.query_one::<&Sparse>(b).is_err());
+        assert_eq!(
+            world.entity(d).unwrap().get::<Sparse>().map(|s| s.0),
+            Some(3)
+        );
+
+        // change tracking
+        assert_eq!(
+            sorted(world.query_filtered::<Entity, Added<Sparse>>().collect()),
+            vec![a, c, d]
+        );
+        world.clear_trackers();
+        for mut sparse in world.query_mut::<Mut<Sparse>>() {
+            if sparse.0 == 2 {
+                sparse.0 = 20;
+            }
+        }
+        world.insert_one(b, Sparse(1)).unwrap();
+        assert_eq!(
+            world
+                .query_filtered::<Entity, Added<Sparse>>()
+                .collect::<Vec<_>>(),
+            vec![b]
+        );
+        assert_eq!(
+            world
+                .query_filtered::<Entity, Mutated<Sparse>>()
+                .collect::<Vec<_>>(),
+            vec![c]
+        );
+        assert_eq!(
+            sorted(world.query_filtered::<Entity, Changed<Sparse>>().collect()),
+            vec![b, c]
+        );
+        let flags = world
+            .query::<(&A, Flags<Sparse>)>()
+            .map(|(a, flags)| (a.0, flags.with(), flags.added()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sorted(flags),
+            vec![(0, true, false), (1, true, true), (3, true, false)]
+        );
+    }
+
+    #[test]
+    fn storage_type_changes() {
+        let mut world = World::new();
+        world.spawn((A(0),));
+        assert!(matches!(
+            world.set_storage_type::<A>(StorageType::SparseSet),
+            Err(StorageTypeError::ComponentsExist(_))
+        ));
+
+        world
+            .set_storage_type::<Sparse>(StorageType::SparseSet)
+            .unwrap();
+        let entity = world.spawn((Sparse(0),));
+        assert!(world
+            .set_storage_type::<Sparse>(StorageType::Table)
+            .is_err());
+        world.despawn(entity).unwrap();
+        world
+            .set_storage_type::<Sparse>(StorageType::Table)
+            .unwrap();
+        assert_eq!(
+            world.storage_type(std::any::TypeId::of::<Sparse>()),
+            StorageType::Table
+        );
+    }
+
+    #[test]
+    fn sparse_components_are_dropped() {
+        struct DropCounter(Arc<AtomicUsize>);
+        impl Drop for DropCounter {
+            fn drop(&mut self) {
+                self.0.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let drops = Arc::new(AtomicUsize::new(0));
+        let mut world = World::new();
+        world
+            .set_storage_type::<DropCounter>(StorageType::SparseSet)
+            .unwrap();
+        let entities = (0..100)
+            .map(|_| world.spawn((DropCounter(drops.clone()),)))
+            .collect::<Vec<_>>();
+        world
+            .insert_one(entities[0], DropCounter(drops.clone()))
+            .unwrap();
+        assert_eq!(drops.load(Ordering::Relaxed), 1);
+        world.despawn(entities[1]).unwrap();
+        world.remove_one::<DropCounter>(entities[2]).unwrap();
+        assert_eq!(drops.load(Ordering::Relaxed), 3);
+        world.clear();
+        assert_eq!(drops.load(Ordering::Relaxed), 101);
+        drop(world);
+        assert_eq!(drops.load(Ordering::Relaxed), 101);
+    }
+}
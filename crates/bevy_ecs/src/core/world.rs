@@ -15,14 +15,22 @@
 // modified by Bevy contributors
 
 use crate::{
-    core::entities::Entities, Archetype, BatchedIter, Bundle, ComponentFlags, DynamicBundle,
-    Entity, EntityFilter, EntityReserver, Fetch, Location, MissingComponent, Mut, NoSuchEntity,
-    QueryFilter, QueryIter, ReadOnlyFetch, Ref, RefMut, WorldQuery,
+    core::entities::Entities, Archetype, BatchedIter, Bundle, ComponentFlags, ComponentId,
+    DynamicBundle, Entity, EntityFilter, EntityReserver, Fetch, Location, MissingComponent, Mut,
+    NoSuchEntity, QueryFilter, QueryIter, ReadOnlyFetch, Ref, RefMut, StorageType,
+    StorageTypeError, TypeInfo, WorldQuery,
 };
+use bevy_tasks::BatchSize;
 use bevy_utils::{HashMap, HashSet};
-use std::{any::TypeId, fmt, mem, ptr};
-
-use super::borrow::EntityRef;
+use std::{any::TypeId, fmt, mem, ptr, sync::Arc};
+
+use super::{
+    archetype::ComponentIdMap,
+    borrow::EntityRef,
+    hooks::{ComponentHooks, HookKind},
+    required::{Requirements, WithRequired},
+    sparse_set::{ComponentSparseSet, SparseSets},
+};
 
 /// An unordered collection of entities, each having any number of distinctly typed components
 ///
@@ -30,23 +38,36 @@ use super::borrow::EntityRef;
 /// type, but far more efficient to traverse.
 ///
 /// The components of entities who have the same set of component types are stored in contiguous
-/// runs, allowing for extremely fast, cache-friendly iteration.
+/// runs, allowing for extremely fast, cache-friendly iteration. Components of the types given the
+/// [StorageType::SparseSet] storage are stored separately, see [World::set_storage_type].
+///
+/// Besides Rust types, components can have a layout that is only known at runtime, see
+/// [World::register_component].
 #[derive(Debug)]
 pub struct World {
-    entities: Entities,
-    index: HashMap<Vec<TypeId>, u32>,
-    removed_components: HashMap<TypeId, Vec<Entity>>,
+    pub(crate) entities: Entities,
+    index: HashMap<Vec<ComponentId>, u32>,
+    pub(crate) removed_components: HashMap<ComponentId, Vec<Entity>>,
     #[allow(missing_docs)]
     pub archetypes: Vec<Archetype>,
     archetype_generation: u64,
+    pub(crate) hooks: ComponentHooks,
+    /// Components registered with [World::require], indexed by the type of the component requiring them
+    pub(crate) requirements: Requirements,
+    /// The relations whose links are cleaned up by component hooks, see [World::register_relation]
+    pub(crate) relations: HashSet<TypeId>,
+    pub(crate) sparse_sets: Arc<SparseSets>,
+    /// Components registered with [World::register_component], indexed by their id
+    pub(crate) external_components: Vec<TypeInfo>,
 }
 
 impl World {
     /// Create an empty world
     pub fn new() -> Self {
         // `flush` assumes archetype 0 always exists, representing entities with no components.
+        let sparse_sets = Arc::new(SparseSets::default());
         let mut archetypes = Vec::new();
-        archetypes.push(Archetype::new(Vec::new()));
+        archetypes.push(Archetype::with_sparse_sets(Vec::new(), sparse_sets.clone()));
         let mut index = HashMap::default();
         index.insert(Vec::new(), 0);
         Self {
@@ -55,7 +76,108 @@ impl World {
             archetypes,
             archetype_generation: 0,
             removed_components: HashMap::default(),
+            hooks: ComponentHooks::default(),
+            requirements: Requirements::default(),
+            relations: HashSet::default(),
+            sparse_sets,
+            external_components: Vec::new(),
+        }
+    }
+
+    /// Chooses how components of type `T` are stored. It fails if `T` components were already stored in the
+    /// other storage.
+    ///
+    /// Components stored in a [StorageType::SparseSet] can be added and removed without moving the other
+    /// components of their entity, at the cost of slower iteration. Queries, filters and change tracking work the
+    /// same with both storages.
+    ///
+    /// # Example
+    /// ```
+    /// # use bevy_ecs::*;
+    /// struct Selected;
+    ///
+    /// let mut world = World::new();
+    /// world.set_storage_type::<Selected>(StorageType::SparseSet).unwrap();
+    /// let a = world.spawn((123,));
+    /// world.insert_one(a, Selected).unwrap();
+    /// assert_eq!(world.query_filtered::<&i32, With<Selected>>().count(), 1);
+    /// ```
+    pub fn set_storage_type<T: Component>(
+        &mut self,
+        storage_type: StorageType,
+    ) -> Result<(), StorageTypeError> {
+        let ty = ComponentId::of::<T>();
+        if self.storage_type(ty) == storage_type {
+            return Ok(());
         }
+        // SAFE: the world is borrowed mutably
+        let sparse_sets = unsafe { self.sparse_sets.sets_mut() };
+        match storage_type {
+            StorageType::Table => {
+                if sparse_sets[&ty].len() != 0 {
+                    return Err(StorageTypeError::ComponentsExist(std::any::type_name::<T>()));
+                }
+                sparse_sets.remove(&ty);
+            }
+            StorageType::SparseSet => {
+                if self
+                    .archetypes
+                    .iter()
+                    .any(|archetype| archetype.has_type(ty))
+                {
+                    return Err(StorageTypeError::ComponentsExist(std::any::type_name::<T>()));
+                }
+                sparse_sets.insert(ty, ComponentSparseSet::new(TypeInfo::of::<T>()));
+            }
+        }
+        // queries now match other archetypes
+        self.archetype_generation += 1;
+        Ok(())
+    }
+
+    /// Returns how components of type `ty` are stored
+    pub fn storage_type(&self, ty: impl Into<ComponentId>) -> StorageType {
+        if self.sparse_sets.contains(ty.into()) {
+            StorageType::SparseSet
+        } else {
+            StorageType::Table
+        }
+    }
+
+    /// Returns the archetype with the component types `ids`, creating it if needed
+    fn get_or_insert_archetype(
+        &mut self,
+        ids: &[ComponentId],
+        types: impl FnOnce() -> Vec<TypeInfo>,
+    ) -> u32 {
+        if let Some(archetype_id) = self.index.get(ids) {
+            return *archetype_id;
+        }
+        let archetype_id = self.archetypes.len() as u32;
+        self.archetypes.push(Archetype::with_sparse_sets(
+            types(),
+            self.sparse_sets.clone(),
+        ));
+        self.index.insert(ids.to_vec(), archetype_id);
+        self.archetype_generation += 1;
+        archetype_id
+    }
+
+    /// Same as [World::get_or_insert_archetype], but ignores the component types stored in sparse sets
+    fn get_or_insert_table_archetype(
+        &mut self,
+        ids: &[ComponentId],
+        types: impl FnOnce() -> Vec<TypeInfo>,
+    ) -> u32 {
+        if self.sparse_sets.is_empty() || !ids.iter().any(|id| self.sparse_sets.contains(*id)) {
+            return self.get_or_insert_archetype(ids, types);
+        }
+        let types = types()
+            .into_iter()
+            .filter(|info| !self.sparse_sets.contains(info.id()))
+            .collect::<Vec<_>>();
+        let ids = types.iter().map(|info| info.id()).collect::<Vec<_>>();
+        self.get_or_insert_archetype(&ids, move || types)
     }
 
     /// Create an entity with certain components
@@ -82,21 +204,29 @@ impl World {
         self.flush();
 
         let entity = self.entities.alloc();
-        let archetype_id = bundle.with_ids(|ids| {
-            self.index.get(ids).copied().unwrap_or_else(|| {
-                let x = self.archetypes.len() as u32;
-                self.archetypes.push(Archetype::new(bundle.type_info()));
-                self.index.insert(ids.to_vec(), x);
-                self.archetype_generation += 1;
-                x
-            })
-        });
+        match self.build_required(None, &bundle) {
+            Some(required) => self.spawn_allocated(entity, WithRequired::new(bundle, required)),
+            None => self.spawn_allocated(entity, bundle),
+        }
+        self.run_queued_hooks();
+        entity
+    }
+
+    /// Stores the components of `entity`, which was just allocated and has no location yet. Their hooks are queued.
+    pub(crate) fn spawn_allocated(&mut self, entity: Entity, bundle: impl DynamicBundle) {
+        let archetype_id =
+            bundle.with_ids(|ids| self.get_or_insert_table_archetype(ids, || bundle.type_info()));
 
         let archetype = &mut self.archetypes[archetype_id as usize];
         unsafe {
             let index = archetype.allocate(entity);
+            let sparse_sets = self.sparse_sets.sets_mut();
+            let hooks = &mut self.hooks;
             bundle.put(|ptr, ty, size| {
-                archetype.put_dynamic(ptr, ty, size, index, ComponentFlags::ADDED);
+                if !put_sparse(sparse_sets, hooks, entity, ptr, ty) {
+                    archetype.put_dynamic(ptr, ty, size, index, ComponentFlags::ADDED);
+                    hooks.queue_added(ty, entity);
+                }
                 true
             });
             self.entities.meta[entity.id as usize].location = Location {
@@ -104,8 +234,6 @@ impl World {
                 index,
             };
         }
-
-        entity
     }
 
     /// Efficiently spawn a large number of entities with the same components
@@ -132,13 +260,20 @@ impl World {
 
         let iter = iter.into_iter();
         let (lower, upper) = iter.size_hint();
-        let archetype_id = self.reserve_inner::<I::Item>(upper.unwrap_or(lower) as u32);
+        let has_requirements =
+            I::Item::with_static_ids(|ids| ids.iter().any(|id| self.requirements.contains_key(id)));
+        // bundles that require other components are spawned one by one
+        let archetype_id = if has_requirements {
+            0
+        } else {
+            self.reserve_inner::<I::Item>(upper.unwrap_or(lower) as u32)
+        };
 
         SpawnBatchIter {
             inner: iter,
-            entities: &mut self.entities,
+            world: self,
             archetype_id,
-            archetype: &mut self.archetypes[archetype_id as usize],
+            has_requirements,
         }
     }
 
@@ -146,6 +281,17 @@ impl World {
     pub fn despawn(&mut self, entity: Entity) -> Result<(), NoSuchEntity> {
         self.flush();
 
+        if !self.hooks.is_empty() {
+            let loc = self.entities.get(entity)?;
+            let types = self.archetypes[loc.archetype as usize]
+                .types()
+                .iter()
+                .map(|ty| ty.id())
+                .chain(self.sparse_sets.types_of(entity))
+                .collect::<Vec<_>>();
+            self.run_remove_hooks(entity, &types);
+        }
+
         let loc = self.entities.free(entity)?;
         let archetype = &mut self.archetypes[loc.archetype as usize];
         if let Some(moved) = unsafe { archetype.remove(loc.index) } {
@@ -158,6 +304,13 @@ impl World {
                 .or_insert_with(Vec::new);
             removed_entities.push(entity);
         }
+        // SAFE: the world is borrowed mutably
+        for (ty, set) in unsafe { self.sparse_sets.sets_mut() } {
+            if set.remove_and_drop(entity) {
+                let removed_entities = self.removed_components.entry(*ty).or_insert_with(Vec::new);
+                removed_entities.push(entity);
+            }
+        }
         Ok(())
     }
 
@@ -175,15 +328,8 @@ impl World {
         self.flush();
         self.entities.reserve(additional);
 
-        let archetype_id = T::with_static_ids(|ids| {
-            self.index.get(ids).copied().unwrap_or_else(|| {
-                let x = self.archetypes.len() as u32;
-                self.archetypes.push(Archetype::new(T::static_type_info()));
-                self.index.insert(ids.to_vec(), x);
-                self.archetype_generation += 1;
-                x
-            })
-        });
+        let archetype_id =
+            T::with_static_ids(|ids| self.get_or_insert_table_archetype(ids, T::static_type_info));
 
         self.archetypes[archetype_id as usize].reserve(additional as usize);
         archetype_id
@@ -193,6 +339,25 @@ impl World {
     ///
     /// Preserves allocated storage for reuse.
     pub fn clear(&mut self) {
+        if !self.hooks.is_empty() {
+            let mut hooked = Vec::new();
+            for archetype in &self.archetypes {
+                for ty in archetype.types() {
+                    if self.hooks.has_remove_hooks(ty.id()) {
+                        hooked.extend(archetype.iter_entities().map(|entity| (*entity, ty.id())));
+                    }
+                }
+            }
+            for (ty, set) in self.sparse_sets.iter() {
+                if self.hooks.has_remove_hooks(ty) {
+                    hooked.extend(set.entities().iter().map(|entity| (*entity, ty)));
+                }
+            }
+            for (entity, ty) in hooked {
+                self.run_remove_hooks(entity, &[ty]);
+            }
+        }
+
         for archetype in &mut self.archetypes {
             for ty in archetype.types() {
                 let removed_entities = self
@@ -203,6 +368,12 @@ impl World {
             }
             archetype.clear();
         }
+        // SAFE: the world is borrowed mutably
+        for (ty, set) in unsafe { self.sparse_sets.sets_mut() } {
+            let removed_entities = self.removed_components.entry(*ty).or_insert_with(Vec::new);
+            removed_entities.extend(set.entities().iter().copied());
+            set.clear();
+        }
         self.entities.clear();
     }
 
@@ -212,13 +383,25 @@ impl World {
     }
 
     /// Returns true if the given entity has a component with the given type id.
-    pub fn has_component_type(&self, entity: Entity, ty: TypeId) -> bool {
+    pub fn has_component_type(&self, entity: Entity, ty: impl Into<ComponentId>) -> bool {
+        let ty = ty.into();
+        if let Some(set) = self.sparse_sets.get(ty) {
+            return set.contains(entity);
+        }
         self.get_entity_location(entity)
             .map(|location| &self.archetypes[location.archetype as usize])
             .map(|archetype| archetype.has_type(ty))
             .unwrap_or(false)
     }
 
+    /// The types of `entity`'s components that are stored in sparse sets rather than in its archetype
+    pub fn sparse_component_types(&self, entity: Entity) -> impl Iterator<Item = &TypeInfo> + '_ {
+        self.sparse_sets
+            .iter()
+            .filter(move |(_, set)| set.contains(entity))
+            .map(|(_, set)| set.type_info())
+    }
+
     /// Efficiently iterate over all entities that have certain components
     ///
     /// Calling `iter` on the returned value yields `(Entity, Q)` tuples, where `Q` is some query
@@ -298,9 +481,13 @@ impl World {
     }
 
     /// Like `query`, but instead of returning a single iterator it returns a "batched iterator",
-    /// where each batch is `batch_size`. This is generally used for parallel iteration.
+    /// where each batch is `batch_size`, or sized from the matching entity count with [BatchSize::Auto].
+    /// This is generally used for parallel iteration.
     #[inline]
-    pub fn query_batched<Q: WorldQuery>(&self, batch_size: usize) -> BatchedIter<'_, Q, ()>
+    pub fn query_batched<Q: WorldQuery>(
+        &self,
+        batch_size: impl Into<BatchSize>,
+    ) -> BatchedIter<'_, Q, ()>
     where
         Q::Fetch: ReadOnlyFetch,
     {
@@ -311,7 +498,7 @@ impl World {
     #[inline]
     pub fn query_batched_filtered<Q: WorldQuery, F: QueryFilter>(
         &self,
-        batch_size: usize,
+        batch_size: impl Into<BatchSize>,
     ) -> BatchedIter<'_, Q, F>
     where
         Q::Fetch: ReadOnlyFetch,
@@ -321,11 +508,12 @@ impl World {
     }
 
     /// Like `query`, but instead of returning a single iterator it returns a "batched iterator",
-    /// where each batch is `batch_size`. This is generally used for parallel iteration.
+    /// where each batch is `batch_size`, or sized from the matching entity count with [BatchSize::Auto].
+    /// This is generally used for parallel iteration.
     #[inline]
     pub fn query_batched_mut<Q: WorldQuery>(
         &mut self,
-        batch_size: usize,
+        batch_size: impl Into<BatchSize>,
     ) -> BatchedIter<'_, Q, ()> {
         // SAFE: unique mutable access
         unsafe { self.query_batched_unchecked(batch_size) }
@@ -334,7 +522,7 @@ impl World {
     #[inline]
     pub fn query_batched_filtered_mut<Q: WorldQuery, F: QueryFilter>(
         &mut self,
-        batch_size: usize,
+        batch_size: impl Into<BatchSize>,
     ) -> BatchedIter<'_, Q, F> {
         // SAFE: unique mutable access
         unsafe { self.query_batched_unchecked(batch_size) }
@@ -359,7 +547,8 @@ impl World {
     }
 
     /// Like `query`, but instead of returning a single iterator it returns a "batched iterator",
-    /// where each batch is `batch_size`. This is generally used for parallel iteration.
+    /// where each batch is `batch_size`, or sized from the matching entity count with [BatchSize::Auto].
+    /// This is generally used for parallel iteration.
     ///
     /// # Safety
     /// This does not check for mutable query correctness. To be safe, make sure mutable queries
@@ -367,9 +556,9 @@ impl World {
     #[inline]
     pub unsafe fn query_batched_unchecked<Q: WorldQuery, F: QueryFilter>(
         &self,
-        batch_size: usize,
+        batch_size: impl Into<BatchSize>,
     ) -> BatchedIter<'_, Q, F> {
-        BatchedIter::new(&self.archetypes, batch_size)
+        BatchedIter::new(&self.archetypes, batch_size.into())
     }
 
     /// Prepare a read only query against a single entity
@@ -454,12 +643,17 @@ impl World {
         entity: Entity,
     ) -> Result<<Q::Fetch as Fetch>::Item, NoSuchEntity> {
         let loc = self.entities.get(entity)?;
+        if loc.index == usize::max_value() {
+            // pending entities have no components
+            return Err(NoSuchEntity);
+        }
         let archetype = &self.archetypes[loc.archetype as usize];
         let matches_filter = F::get_entity_filter(archetype)
             .map(|entity_filter| entity_filter.matches_entity(loc.index))
             .unwrap_or(false);
         if matches_filter {
             <Q::Fetch as Fetch>::get(archetype, 0)
+                .filter(|fetch| fetch.matches(loc.index))
                 .map(|fetch| fetch.fetch(loc.index))
                 .ok_or(NoSuchEntity)
         } else {
@@ -472,14 +666,11 @@ impl World {
     pub fn get<T: Component>(&self, entity: Entity) -> Result<&'_ T, ComponentError> {
         unsafe {
             let loc = self.entities.get(entity)?;
-            if loc.archetype == 0 {
-                return Err(MissingComponent::new::<T>().into());
-            }
             Ok(&*self.archetypes[loc.archetype as usize]
-                .get::<T>()
+                .get_component::<T>(loc.index)
                 .ok_or_else(MissingComponent::new::<T>)?
-                .as_ptr()
-                .add(loc.index as usize))
+                .0
+                .as_ptr())
         }
     }
 
@@ -495,7 +686,8 @@ impl World {
     /// Does not immediately borrow any component.
     pub fn entity(&mut self, entity: Entity) -> Result<EntityRef<'_>, NoSuchEntity> {
         Ok(match self.entities.get(entity)? {
-            Location { archetype: 0, .. } => EntityRef::empty(),
+            // pending entities have no components
+            Location { index, .. } if index == usize::max_value() => EntityRef::empty(),
             loc => unsafe { EntityRef::new(&self.archetypes[loc.archetype as usize], loc.index) },
         })
     }
@@ -517,9 +709,6 @@ impl World {
         entity: Entity,
     ) -> Result<Mut<'_, T>, ComponentError> {
         let loc = self.entities.get(entity)?;
-        if loc.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(Mut::new(
             &self.archetypes[loc.archetype as usize],
             loc.index,
@@ -549,7 +738,7 @@ impl World {
     #[allow(missing_docs)]
     pub fn removed<C: Component>(&self) -> &[Entity] {
         self.removed_components
-            .get(&TypeId::of::<C>())
+            .get(&ComponentId::of::<C>())
             .map_or(&[], |entities| entities.as_slice())
     }
 
@@ -573,6 +762,20 @@ impl World {
         &mut self,
         entity: Entity,
         bundle: impl DynamicBundle,
+    ) -> Result<(), NoSuchEntity> {
+        match self.build_required(Some(entity), &bundle) {
+            Some(required) => self.insert_inner(entity, WithRequired::new(bundle, required))?,
+            None => self.insert_inner(entity, bundle)?,
+        }
+        self.run_queued_hooks();
+        Ok(())
+    }
+
+    /// Same as [World::insert], but only queues the hooks of the inserted components
+    fn insert_inner(
+        &mut self,
+        entity: Entity,
+        bundle: impl DynamicBundle,
     ) -> Result<(), NoSuchEntity> {
         use std::collections::hash_map::Entry;
 
@@ -580,9 +783,14 @@ impl World {
         let loc = self.entities.get_mut(entity)?;
         unsafe {
             // Assemble Vec<TypeInfo> for the final entity
+            let sparse_sets = self.sparse_sets.sets_mut();
             let arch = &mut self.archetypes[loc.archetype as usize];
             let mut info = arch.types().to_vec();
             for ty in bundle.type_info() {
+                if sparse_sets.contains_key(&ty.id()) {
+                    // the entity stays in its archetype
+                    continue;
+                }
                 if let Some(ptr) = arch.get_dynamic(ty.id(), ty.layout().size(), loc.index) {
                     ty.drop(ptr.as_ptr());
                 } else {
@@ -597,7 +805,8 @@ impl World {
                 Entry::Occupied(x) => *x.get(),
                 Entry::Vacant(x) => {
                     let index = self.archetypes.len() as u32;
-                    self.archetypes.push(Archetype::new(info));
+                    self.archetypes
+                        .push(Archetype::with_sparse_sets(info, self.sparse_sets.clone()));
                     x.insert(index);
                     self.archetype_generation += 1;
                     index
@@ -607,8 +816,12 @@ impl World {
             if target == loc.archetype {
                 // Update components in the current archetype
                 let arch = &mut self.archetypes[loc.archetype as usize];
+                let hooks = &mut self.hooks;
                 bundle.put(|ptr, ty, size| {
-                    arch.put_dynamic(ptr, ty, size, loc.index, ComponentFlags::MUTATED);
+                    if !put_sparse(sparse_sets, hooks, entity, ptr, ty) {
+                        arch.put_dynamic(ptr, ty, size, loc.index, ComponentFlags::MUTATED);
+                        hooks.queue(HookKind::Insert, ty, entity);
+                    }
                     true
                 });
                 return Ok(());
@@ -631,11 +844,17 @@ impl World {
                 self.entities.get_mut(moved).unwrap().index = old_index;
             }
 
+            let hooks = &mut self.hooks;
             bundle.put(|ptr, ty, size| {
+                if put_sparse(sparse_sets, hooks, entity, ptr, ty) {
+                    return true;
+                }
                 let had_component = source_arch.has_dynamic(ty);
                 let flags = if had_component {
+                    hooks.queue(HookKind::Insert, ty, entity);
                     ComponentFlags::MUTATED
                 } else {
+                    hooks.queue_added(ty, entity);
                     ComponentFlags::ADDED
                 };
                 target_arch.put_dynamic(ptr, ty, size, target_index, flags);
@@ -677,13 +896,30 @@ impl World {
     /// ```
     pub fn remove<T: Bundle>(&mut self, entity: Entity) -> Result<T, ComponentError> {
         self.flush();
+        if !self.hooks.is_empty() {
+            let ids = T::with_static_ids(|ids| ids.to_vec());
+            // nothing is removed if a component is missing
+            if ids.iter().all(|ty| self.has_component_type(entity, *ty)) {
+                self.run_remove_hooks(entity, &ids);
+            }
+        }
         let loc = self.entities.get_mut(entity)?;
         unsafe {
-            let to_remove = T::with_static_ids(|ids| ids.iter().copied().collect::<HashSet<_>>());
+            let mut to_remove =
+                T::with_static_ids(|ids| ids.iter().copied().collect::<HashSet<_>>());
 
             let old_index = loc.index;
             let source_arch = &self.archetypes[loc.archetype as usize];
-            let bundle = T::get(|ty, size| source_arch.get_dynamic(ty, size, old_index))?;
+            let sparse_sets = &self.sparse_sets;
+            let bundle = T::get(|ty, size| match sparse_sets.get(ty) {
+                Some(set) => set.get(entity).map(|(component, _)| component),
+                None => source_arch.get_dynamic(ty, size, old_index),
+            })?;
+            // the removed components belong to `bundle` now
+            self.remove_sparse_components(entity, &mut to_remove, false);
+            if to_remove.is_empty() {
+                return Ok(bundle);
+            }
             match self.remove_bundle_internal(entity, to_remove) {
                 Ok(_) => Ok(bundle),
                 Err(err) => Err(err),
@@ -691,10 +927,10 @@ impl World {
         }
     }
 
-    fn remove_bundle_internal<S: core::hash::BuildHasher>(
+    pub(crate) fn remove_bundle_internal<S: core::hash::BuildHasher>(
         &mut self,
         entity: Entity,
-        to_remove: std::collections::HashSet<TypeId, S>,
+        to_remove: std::collections::HashSet<ComponentId, S>,
     ) -> Result<(), ComponentError> {
         use std::collections::hash_map::Entry;
 
@@ -709,7 +945,8 @@ impl World {
         let target = match self.index.entry(elements) {
             Entry::Occupied(x) => *x.get(),
             Entry::Vacant(x) => {
-                self.archetypes.push(Archetype::new(info));
+                self.archetypes
+                    .push(Archetype::with_sparse_sets(info, self.sparse_sets.clone()));
                 let index = (self.archetypes.len() - 1) as u32;
                 x.insert(index);
                 self.archetype_generation += 1;
@@ -744,6 +981,40 @@ impl World {
         Ok(())
     }
 
+    /// Takes the components of `entity` that are stored in sparse sets out of their sets, and removes their types
+    /// from `to_remove`. The components are dropped if `drop_components` is true, otherwise the caller must have
+    /// moved them out already.
+    pub(crate) fn remove_sparse_components<S: core::hash::BuildHasher>(
+        &mut self,
+        entity: Entity,
+        to_remove: &mut std::collections::HashSet<ComponentId, S>,
+        drop_components: bool,
+    ) {
+        if self.sparse_sets.is_empty() {
+            return;
+        }
+        // SAFE: the world is borrowed mutably
+        let sparse_sets = unsafe { self.sparse_sets.sets_mut() };
+        let removed_components = &mut self.removed_components;
+        to_remove.retain(|ty| {
+            let set = match sparse_sets.get_mut(ty) {
+                Some(set) => set,
+                None => return true,
+            };
+            let type_info = *set.type_info();
+            let removed = set.remove_with(entity, |component| {
+                if drop_components {
+                    unsafe { type_info.drop(component) }
+                }
+            });
+            if removed {
+                let removed_entities = removed_components.entry(*ty).or_insert_with(Vec::new);
+                removed_entities.push(entity);
+            }
+            false
+        });
+    }
+
     /// Remove components from `entity`
     ///
     /// Fallback method for `remove` when one of the component in `T` is not present in `entity`.
@@ -753,7 +1024,14 @@ impl World {
     pub fn remove_one_by_one<T: Bundle>(&mut self, entity: Entity) -> Result<(), ComponentError> {
         self.flush();
 
-        let to_remove = T::with_static_ids(|ids| ids.iter().copied().collect::<HashSet<_>>());
+        self.entities.get(entity)?;
+        if !self.hooks.is_empty() {
+            let ids = T::with_static_ids(|ids| ids.to_vec());
+            self.run_remove_hooks(entity, &ids);
+            self.entities.get(entity)?;
+        }
+        let mut to_remove = T::with_static_ids(|ids| ids.iter().copied().collect::<HashSet<_>>());
+        self.remove_sparse_components(entity, &mut to_remove, true);
         for component_to_remove in to_remove.into_iter() {
             let loc = self.entities.get(entity)?;
             if loc.archetype == 0 {
@@ -786,9 +1064,6 @@ impl World {
         &self,
         location: Location,
     ) -> Result<Ref<T>, ComponentError> {
-        if location.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(Ref::new(
             &self.archetypes[location.archetype as usize],
             location.index,
@@ -805,9 +1080,6 @@ impl World {
         &self,
         location: Location,
     ) -> Result<RefMut<T>, ComponentError> {
-        if location.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(RefMut::new(
             &self.archetypes[location.archetype as usize],
             location.index,
@@ -821,14 +1093,11 @@ impl World {
         &self,
         location: Location,
     ) -> Result<&T, ComponentError> {
-        if location.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(&*self.archetypes[location.archetype as usize]
-            .get::<T>()
+            .get_component::<T>(location.index)
             .ok_or_else(MissingComponent::new::<T>)?
-            .as_ptr()
-            .add(location.index as usize))
+            .0
+            .as_ptr())
     }
 
     /// Borrow the `T` component at the given location, without safety checks
@@ -840,9 +1109,6 @@ impl World {
         &self,
         location: Location,
     ) -> Result<Mut<T>, ComponentError> {
-        if location.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(Mut::new(
             &self.archetypes[location.archetype as usize],
             location.index,
@@ -862,14 +1128,11 @@ impl World {
         entity: Entity,
     ) -> Result<&mut T, ComponentError> {
         let loc = self.entities.get(entity)?;
-        if loc.archetype == 0 {
-            return Err(MissingComponent::new::<T>().into());
-        }
         Ok(&mut *self.archetypes[loc.archetype as usize]
-            .get::<T>()
+            .get_component::<T>(loc.index)
             .ok_or_else(MissingComponent::new::<T>)?
-            .as_ptr()
-            .add(loc.index as usize))
+            .0
+            .as_ptr())
     }
 
     /// Convert all reserved entities into empty entities that can be iterated and accessed
@@ -937,6 +1200,10 @@ impl World {
         for archetype in self.archetypes.iter_mut() {
             archetype.clear_trackers();
         }
+        // SAFE: the world is borrowed mutably
+        for set in unsafe { self.sparse_sets.sets_mut() }.values_mut() {
+            set.clear_trackers();
+        }
 
         self.removed_components.clear();
     }
@@ -965,6 +1232,34 @@ impl<'a> IntoIterator for &'a mut World {
     }
 }
 
+/// Moves `component` into its sparse set and queues its hooks, if components of type `ty` are stored in one.
+/// Returns false if they are stored in archetypes.
+///
+/// # Safety
+/// `component` must point to a valid component of type `ty`, which must not be used afterwards if true is returned
+unsafe fn put_sparse(
+    sparse_sets: &mut ComponentIdMap<ComponentSparseSet>,
+    hooks: &mut ComponentHooks,
+    entity: Entity,
+    component: *mut u8,
+    ty: ComponentId,
+) -> bool {
+    if sparse_sets.is_empty() {
+        return false;
+    }
+    match sparse_sets.get_mut(&ty) {
+        Some(set) => {
+            if set.insert(entity, component) {
+                hooks.queue(HookKind::Insert, ty, entity);
+            } else {
+                hooks.queue_added(ty, entity);
+            }
+            true
+        }
+        None => false,
+    }
+}
+
 fn index2<T>(x: &mut [T], i: usize, j: usize) -> (&mut T, &mut T) {
     assert!(i != j);
     assert!(i < x.len());
@@ -1095,9 +1390,10 @@ where
     I::Item: Bundle,
 {
     inner: I,
-    entities: &'a mut Entities,
+    world: &'a mut World,
     archetype_id: u32,
-    archetype: &'a mut Archetype,
+    /// Whether the bundles require other components, in which case they don't all fit in `archetype_id`
+    has_requirements: bool,
 }
 
 impl<I> Drop for SpawnBatchIter<'_, I>
@@ -1119,19 +1415,32 @@ where
 
     fn next(&mut self) -> Option<Entity> {
         let components = self.inner.next()?;
-        let entity = self.entities.alloc();
+        if self.has_requirements {
+            return Some(self.world.spawn(components));
+        }
+        let world = &mut *self.world;
+        // hooks may have spawned entities, or reserved them through an EntityReserver
+        world.flush();
+        let entity = world.entities.alloc();
         unsafe {
-            let index = self.archetype.allocate(entity);
+            let archetype = &mut world.archetypes[self.archetype_id as usize];
+            let index = archetype.allocate(entity);
+            // SAFE: the world is borrowed mutably by the iterator
+            let sparse_sets = world.sparse_sets.sets_mut();
+            let hooks = &mut world.hooks;
             components.put(|ptr, ty, size| {
-                self.archetype
-                    .put_dynamic(ptr, ty, size, index, ComponentFlags::ADDED);
+                if !put_sparse(sparse_sets, hooks, entity, ptr, ty) {
+                    archetype.put_dynamic(ptr, ty, size, index, ComponentFlags::ADDED);
+                    hooks.queue_added(ty, entity);
+                }
                 true
             });
-            self.entities.meta[entity.id as usize].location = Location {
+            world.entities.meta[entity.id as usize].location = Location {
                 archetype: self.archetype_id,
                 index,
             };
         }
+        world.run_queued_hooks();
         Some(entity)
     }
 
@@ -13,10 +13,19 @@ pub use system::{Query, *};
 pub mod prelude {
     pub use crate::{
         core::WorldBuilderSource,
-        resource::{ChangedRes, FromResources, Local, Res, ResMut, Resource, Resources},
-        schedule::{Schedule, State, StateStage, SystemStage},
-        system::{Commands, IntoSystem, Query, System},
+        resource::{
+            AddedRes, ChangedRes, FromResources, Local, MutatedRes, Res, ResMut, Resource,
+            Resources,
+        },
+        schedule::{
+            Schedule, ShouldRun, State, StateScoped, StateStage, SystemDescriptorCoercion,
+            SystemLabel, SystemSet, SystemStage,
+        },
+        system::{
+            log_system_error, panic_on_system_error, CommandFlush, Commands, IntoPipeErrorSystem,
+            IntoSystem, Query, System, SystemError, SystemRegistry,
+        },
         Added, Bundle, Changed, Component, Entity, Flags, In, IntoChainSystem, Mut, Mutated, Or,
-        QuerySet, Ref, RefMut, With, Without, World,
+        QuerySet, Ref, RefMut, Relation, RelationSources, RelationTargets, With, Without, World,
     };
 }
@@ -35,6 +35,60 @@ impl<'a, T: Resource> Deref for ChangedRes<'a, T> {
     }
 }
 
+/// A shared borrow of a Resource
+/// that will only return in a query if the Resource has been added since the last tracker reset
+#[derive(Debug)]
+pub struct AddedRes<'a, T: Resource> {
+    value: &'a T,
+}
+
+impl<'a, T: Resource> AddedRes<'a, T> {
+    /// Creates a reference cell to a Resource from a pointer
+    ///
+    /// # Safety
+    /// The pointer must have correct lifetime / storage
+    pub unsafe fn new(value: NonNull<T>) -> Self {
+        Self {
+            value: &*value.as_ptr(),
+        }
+    }
+}
+
+impl<'a, T: Resource> Deref for AddedRes<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
+/// A shared borrow of a Resource
+/// that will only return in a query if the Resource has been mutated since the last tracker reset
+#[derive(Debug)]
+pub struct MutatedRes<'a, T: Resource> {
+    value: &'a T,
+}
+
+impl<'a, T: Resource> MutatedRes<'a, T> {
+    /// Creates a reference cell to a Resource from a pointer
+    ///
+    /// # Safety
+    /// The pointer must have correct lifetime / storage
+    pub unsafe fn new(value: NonNull<T>) -> Self {
+        Self {
+            value: &*value.as_ptr(),
+        }
+    }
+}
+
+impl<'a, T: Resource> Deref for MutatedRes<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value
+    }
+}
+
 /// Shared borrow of a Resource
 #[derive(Debug)]
 pub struct Res<'a, T: Resource> {
@@ -1,5 +1,5 @@
 use crate::{system::SystemId, AtomicBorrow, TypeInfo};
-use bevy_utils::HashMap;
+use bevy_utils::{HashMap, HashSet};
 use core::any::TypeId;
 use downcast_rs::{impl_downcast, Downcast};
 use std::{
@@ -18,6 +18,7 @@ pub(crate) struct ResourceData {
     storage: Box<dyn ResourceStorage>,
     default_index: Option<usize>,
     system_id_to_archetype_index: HashMap<usize, usize>,
+    type_name: &'static str,
 }
 
 #[derive(Debug)]
@@ -74,6 +75,10 @@ impl<T: 'static> VecResourceStorage<T> {
         self.stored[index].mutated = UnsafeCell::new(true);
     }
 
+    fn swap_remove(&mut self, index: usize) -> T {
+        self.stored.swap_remove(index).value.into_inner()
+    }
+
     fn is_empty(&self) -> bool {
         self.stored.is_empty()
     }
@@ -100,6 +105,7 @@ impl<T: 'static> ResourceStorage for VecResourceStorage<T> {
 pub struct Resources {
     pub(crate) resource_data: HashMap<TypeId, ResourceData>,
     thread_local_data: HashMap<TypeId, Box<dyn ResourceStorage>>,
+    removed: HashSet<TypeId>,
     main_thread_id: ThreadId,
 }
 
@@ -108,6 +114,7 @@ impl Default for Resources {
         Resources {
             resource_data: Default::default(),
             thread_local_data: Default::default(),
+            removed: Default::default(),
             main_thread_id: std::thread::current().id(),
         }
     }
@@ -138,10 +145,42 @@ impl Resources {
         }
     }
 
+    /// Returns the type name of the resource with the given [TypeId], if such a resource was ever inserted
+    pub fn get_type_name(&self, type_id: TypeId) -> Option<&'static str> {
+        self.resource_data.get(&type_id).map(|data| data.type_name)
+    }
+
     pub fn contains<T: Resource>(&self) -> bool {
         self.get_resource::<T>(ResourceIndex::Global).is_some()
     }
 
+    /// Removes the resource of type `T` and returns it. Until the next [Resources::clear_trackers],
+    /// [Resources::is_removed] returns true for `T`.
+    pub fn remove<T: Resource>(&mut self) -> Option<T> {
+        let data = self.resource_data.get_mut(&TypeId::of::<T>())?;
+        let index = data.default_index.take()?;
+        let storage = data
+            .storage
+            .downcast_mut::<VecResourceStorage<T>>()
+            .unwrap();
+        let moved_index = storage.stored.len() - 1;
+        let resource = storage.swap_remove(index);
+        if let Some(system_index) = data
+            .system_id_to_archetype_index
+            .values_mut()
+            .find(|system_index| **system_index == moved_index)
+        {
+            *system_index = index;
+        }
+        self.removed.insert(TypeId::of::<T>());
+        Some(resource)
+    }
+
+    /// Returns true if a resource of type `T` was removed since the last [Resources::clear_trackers]
+    pub fn is_removed<T: Resource>(&self) -> bool {
+        self.removed.contains(&TypeId::of::<T>())
+    }
+
     pub fn get<T: Resource>(&self) -> Option<ResourceRef<'_, T>> {
         self.get_resource(ResourceIndex::Global)
     }
@@ -211,6 +250,7 @@ impl Resources {
                 storage: Box::new(VecResourceStorage::<T>::default()),
                 default_index: None,
                 system_id_to_archetype_index: HashMap::default(),
+                type_name: std::any::type_name::<T>(),
             }
         });
 
@@ -288,6 +328,17 @@ impl Resources {
         &self,
         resource_index: ResourceIndex,
     ) -> (NonNull<T>, NonNull<bool>, NonNull<bool>) {
+        self.try_get_unsafe_ref_with_added_and_mutated(resource_index)
+            .unwrap_or_else(|| panic!("Resource does not exist {}.", std::any::type_name::<T>()))
+    }
+
+    /// Like [Resources::get_unsafe_ref_with_added_and_mutated], returning `None` if the resource does not exist
+    #[inline]
+    #[allow(clippy::missing_safety_doc)]
+    pub unsafe fn try_get_unsafe_ref_with_added_and_mutated<T: Resource>(
+        &self,
+        resource_index: ResourceIndex,
+    ) -> Option<(NonNull<T>, NonNull<bool>, NonNull<bool>)> {
         self.get_resource_data_index::<T>(resource_index)
             .map(|(data, index)| {
                 let resources = data
@@ -301,7 +352,6 @@ impl Resources {
                     NonNull::new_unchecked(resources.stored[index].mutated.get()),
                 )
             })
-            .unwrap_or_else(|| panic!("Resource does not exist {}.", std::any::type_name::<T>()))
     }
 
     #[inline]
@@ -326,6 +376,7 @@ impl Resources {
         for (_, resource_data) in self.resource_data.iter_mut() {
             resource_data.storage.clear_trackers();
         }
+        self.removed.clear();
     }
 }
 
@@ -518,6 +569,28 @@ mod tests {
         assert_eq!(*resources.get::<i32>().expect("resource exists"), 123);
     }
 
+    #[test]
+    fn remove_resource() {
+        let mut resources = Resources::default();
+        resources.insert_local(SystemId(0), 1);
+        resources.insert(2);
+        resources.insert_local(SystemId(1), 3);
+        assert!(!resources.is_removed::<i32>());
+
+        assert_eq!(resources.remove::<i32>(), Some(2));
+        assert!(resources.get::<i32>().is_none());
+        assert!(resources.is_removed::<i32>());
+        assert_eq!(*resources.get_local::<i32>(SystemId(0)).unwrap(), 1);
+        assert_eq!(*resources.get_local::<i32>(SystemId(1)).unwrap(), 3);
+        assert_eq!(resources.remove::<i32>(), None);
+
+        resources.clear_trackers();
+        assert!(!resources.is_removed::<i32>());
+        resources.insert(4);
+        assert_eq!(*resources.get::<i32>().unwrap(), 4);
+        assert_eq!(*resources.get_local::<i32>(SystemId(1)).unwrap(), 3);
+    }
+
     #[test]
     #[should_panic(expected = "Failed to acquire exclusive lock on resource: i32")]
     fn resource_double_mut_panic() {
@@ -0,0 +1,120 @@
+use std::{borrow::Cow, fmt};
+
+use crate::{ArchetypeComponent, BoxedSystem, Resources, ThreadLocalExecution, World};
+use fixedbitset::FixedBitSet;
+
+/// Two systems of a stage whose data accesses conflict, but whose relative execution order was never specified.
+/// Such systems never run at the same time, but which one runs first only depends on the order they were added in.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SystemAmbiguity {
+    pub first: Cow<'static, str>,
+    pub second: Cow<'static, str>,
+    /// The components and resources that one of the systems writes and the other reads or writes
+    pub conflicts: Vec<&'static str>,
+}
+
+impl fmt::Display for SystemAmbiguity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Systems {} and {} have an ambiguous execution order. Conflicting accesses: {}",
+            self.first,
+            self.second,
+            self.conflicts.join(", ")
+        )
+    }
+}
+
+/// When this resource exists, every [SystemStage](crate::SystemStage) logs a warning for each [SystemAmbiguity]
+/// it finds the first time it runs (and again after systems are added to it).
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ReportExecutionOrderAmbiguities;
+
+/// Finds the ambiguities among `systems`, which must be sorted in execution order. `ordering_dependencies[i]`
+/// contains the indices of the systems that system `i` was explicitly ordered after.
+///
+/// Accesses are compared as of the last time each system was updated, so components are only taken into
+/// account for archetypes that existed back then.
+pub(crate) fn find_ambiguities(
+    systems: &[BoxedSystem],
+    ordering_dependencies: &[Vec<usize>],
+    world: &World,
+    resources: &Resources,
+) -> Vec<SystemAmbiguity> {
+    // systems that are transitively ordered before each system. because systems are sorted, dependencies always
+    // come first
+    let mut ancestors = Vec::<FixedBitSet>::with_capacity(systems.len());
+    for index in 0..systems.len() {
+        let mut system_ancestors = FixedBitSet::with_capacity(systems.len());
+        if let Some(dependencies) = ordering_dependencies.get(index) {
+            for &dependency in dependencies.iter() {
+                system_ancestors.insert(dependency);
+                system_ancestors.union_with(&ancestors[dependency]);
+            }
+        }
+        ancestors.push(system_ancestors);
+    }
+
+    let archetypes = world.archetypes().collect::<Vec<_>>();
+    let component_name = |archetype_component: &ArchetypeComponent| {
+        archetypes
+            .get(archetype_component.archetype_index as usize)
+            .and_then(|archetype| {
+                archetype
+                    .types()
+                    .iter()
+                    .find(|type_info| type_info.id() == archetype_component.component)
+                    .or_else(|| {
+                        archetype
+                            .sparse_set(archetype_component.component)
+                            .map(|set| set.type_info())
+                    })
+            })
+            .map_or("<unknown component>", |type_info| type_info.type_name())
+    };
+
+    let mut ambiguities = Vec::new();
+    // immediate thread local systems run exclusively, so they split the stage into independently ordered batches
+    let mut batch_start = 0;
+    for (index, system) in systems.iter().enumerate() {
+        if system.thread_local_execution() == ThreadLocalExecution::Immediate {
+            batch_start = index + 1;
+            continue;
+        }
+        for (earlier_index, earlier_system) in
+            systems.iter().enumerate().take(index).skip(batch_start)
+        {
+            if ancestors[index].contains(earlier_index) {
+                continue;
+            }
+            let mut conflicts = earlier_system
+                .archetype_component_access()
+                .get_conflicts(system.archetype_component_access())
+                .into_iter()
+                .map(&component_name)
+                .chain(
+                    earlier_system
+                        .resource_access()
+                        .get_conflicts(system.resource_access())
+                        .into_iter()
+                        .map(|type_id| {
+                            resources
+                                .get_type_name(*type_id)
+                                .unwrap_or("<unknown resource>")
+                        }),
+                )
+                .collect::<Vec<_>>();
+            if conflicts.is_empty() {
+                continue;
+            }
+            conflicts.sort_unstable();
+            conflicts.dedup();
+            ambiguities.push(SystemAmbiguity {
+                first: earlier_system.name(),
+                second: system.name(),
+                conflicts,
+            });
+        }
+    }
+    ambiguities
+}
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+/// A description of a graph (such as a [Schedule](crate::Schedule)) that can be exported for debugging.
+///
+/// Node ids are unique across the whole graph, including subgraphs, so edges may connect nodes of
+/// different subgraphs.
+#[derive(Debug, Clone, Default)]
+pub struct DebugGraph {
+    pub name: String,
+    pub nodes: Vec<DebugNode>,
+    pub edges: Vec<DebugEdge>,
+    pub subgraphs: Vec<DebugGraph>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DebugNode {
+    pub id: String,
+    pub label: String,
+    /// What this node represents, for example "system" or "run_criteria"
+    pub kind: &'static str,
+}
+
+#[derive(Debug, Clone)]
+pub struct DebugEdge {
+    pub from: String,
+    pub to: String,
+    /// Why this edge exists, for example "ordering" or "dependency"
+    pub kind: &'static str,
+    pub label: Option<String>,
+}
+
+impl DebugGraph {
+    pub fn new(name: impl Into<String>) -> Self {
+        DebugGraph {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn add_node(
+        &mut self,
+        id: impl Into<String>,
+        label: impl Into<String>,
+        kind: &'static str,
+    ) {
+        self.nodes.push(DebugNode {
+            id: id.into(),
+            label: label.into(),
+            kind,
+        });
+    }
+
+    pub fn add_edge(
+        &mut self,
+        from: impl Into<String>,
+        to: impl Into<String>,
+        kind: &'static str,
+        label: Option<String>,
+    ) {
+        self.edges.push(DebugEdge {
+            from: from.into(),
+            to: to.into(),
+            kind,
+            label,
+        });
+    }
+
+    /// Renders the graph in the Graphviz DOT format. Subgraphs are rendered as clusters.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::new();
+        writeln!(dot, "digraph {} {{", quote(&self.name)).unwrap();
+        writeln!(dot, "\trankdir=LR;").unwrap();
+        writeln!(dot, "\tlabel={};", quote(&self.name)).unwrap();
+        self.write_dot_body(&mut dot, 1);
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_dot_body(&self, dot: &mut String, depth: usize) {
+        let indent = "\t".repeat(depth);
+        for node in self.nodes.iter() {
+            let shape = match node.kind {
+                "run_criteria" => "diamond",
+                "stage" => "folder",
+                _ => "box",
+            };
+            writeln!(
+                dot,
+                "{}{} [label={}, shape={}];",
+                indent,
+                quote(&node.id),
+                quote(&node.label),
+                shape
+            )
+            .unwrap();
+        }
+        for subgraph in self.subgraphs.iter() {
+            writeln!(
+                dot,
+                "{}subgraph {} {{",
+                indent,
+                quote(&format!("cluster_{}", subgraph.name))
+            )
+            .unwrap();
+            writeln!(dot, "{}\tlabel={};", indent, quote(&subgraph.name)).unwrap();
+            subgraph.write_dot_body(dot, depth + 1);
+            writeln!(dot, "{}}}", indent).unwrap();
+        }
+        for edge in self.edges.iter() {
+            let style = match edge.kind {
+                "ordering" => "bold",
+                "run_criteria" => "dashed",
+                _ => "solid",
+            };
+            write!(
+                dot,
+                "{}{} -> {} [style={}",
+                indent,
+                quote(&edge.from),
+                quote(&edge.to),
+                style
+            )
+            .unwrap();
+            if let Some(label) = &edge.label {
+                write!(dot, ", label={}", quote(label)).unwrap();
+            }
+            dot.push_str("];\n");
+        }
+    }
+
+    /// Renders the graph as a JSON object with `name`, `nodes`, `edges` and `subgraphs` fields
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        self.write_json(&mut json);
+        json
+    }
+
+    fn write_json(&self, json: &mut String) {
+        write!(json, "{{\"name\":{},\"nodes\":[", quote(&self.name)).unwrap();
+        for (i, node) in self.nodes.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"id\":{},\"label\":{},\"kind\":{}}}",
+                quote(&node.id),
+                quote(&node.label),
+                quote(node.kind)
+            )
+            .unwrap();
+        }
+        json.push_str("],\"edges\":[");
+        for (i, edge) in self.edges.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            write!(
+                json,
+                "{{\"from\":{},\"to\":{},\"kind\":{},\"label\":{}}}",
+                quote(&edge.from),
+                quote(&edge.to),
+                quote(edge.kind),
+                edge.label
+                    .as_ref()
+                    .map_or_else(|| "null".to_string(), |label| quote(label))
+            )
+            .unwrap();
+        }
+        json.push_str("],\"subgraphs\":[");
+        for (i, subgraph) in self.subgraphs.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            subgraph.write_json(json);
+        }
+        json.push_str("]}");
+    }
+}
+
+/// Quotes and escapes a string. The result is valid both as a DOT id and as a JSON string.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
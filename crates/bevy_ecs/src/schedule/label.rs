@@ -0,0 +1,61 @@
+use std::{
+    any::Any,
+    fmt::Debug,
+    hash::{Hash, Hasher},
+};
+
+/// A label that can be attached to systems and referenced by ordering constraints.
+///
+/// Any `'static` type that is [Debug], [Clone], [Eq] and [Hash] can be used as a label, so both
+/// string labels (`"physics"`) and typed labels (`enum MyLabels { Physics }`) work. Labels of different
+/// types never compare equal, even if their values look the same.
+pub trait SystemLabel: Debug + Send + Sync + 'static {
+    fn as_any(&self) -> &dyn Any;
+    fn dyn_eq(&self, other: &dyn SystemLabel) -> bool;
+    fn dyn_hash(&self, state: &mut dyn Hasher);
+    fn dyn_clone(&self) -> Box<dyn SystemLabel>;
+}
+
+pub type BoxedSystemLabel = Box<dyn SystemLabel>;
+
+impl<T> SystemLabel for T
+where
+    T: Debug + Clone + Eq + Hash + Send + Sync + 'static,
+{
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn dyn_eq(&self, other: &dyn SystemLabel) -> bool {
+        other.as_any().downcast_ref::<T>() == Some(self)
+    }
+
+    fn dyn_hash(&self, mut state: &mut dyn Hasher) {
+        T::hash(self, &mut state);
+        std::any::TypeId::of::<T>().hash(&mut state);
+    }
+
+    fn dyn_clone(&self) -> Box<dyn SystemLabel> {
+        Box::new(self.clone())
+    }
+}
+
+impl PartialEq for dyn SystemLabel {
+    fn eq(&self, other: &Self) -> bool {
+        self.dyn_eq(other)
+    }
+}
+
+impl Eq for dyn SystemLabel {}
+
+impl Hash for dyn SystemLabel {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.dyn_hash(state);
+    }
+}
+
+impl Clone for BoxedSystemLabel {
+    fn clone(&self) -> Self {
+        (**self).dyn_clone()
+    }
+}
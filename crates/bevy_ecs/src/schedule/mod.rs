@@ -1,10 +1,22 @@
+mod ambiguity;
+mod debug_graph;
+mod label;
+mod run_criteria;
 mod stage;
 mod stage_executor;
 mod state;
+mod system_descriptor;
+mod system_set;
 
+pub use ambiguity::*;
+pub use debug_graph::*;
+pub use label::*;
+pub use run_criteria::*;
 pub use stage::*;
 pub use stage_executor::*;
 pub use state::*;
+pub use system_descriptor::*;
+pub use system_set::*;
 
 use crate::{BoxedSystem, IntoSystem, Resources, System, World};
 use bevy_utils::HashMap;
@@ -38,7 +50,7 @@ impl Schedule {
         self
     }
 
-    pub fn with_system_in_stage<S: System<In = (), Out = ()>>(
+    pub fn with_system_in_stage<S: Into<SystemDescriptor>>(
         mut self,
         stage_name: &'static str,
         system: S,
@@ -98,7 +110,7 @@ impl Schedule {
         self
     }
 
-    pub fn add_system_to_stage<S: System<In = (), Out = ()>>(
+    pub fn add_system_to_stage<S: Into<SystemDescriptor>>(
         &mut self,
         stage_name: &'static str,
         system: S,
@@ -111,7 +123,24 @@ impl Schedule {
                     stage_name
                 )
             });
-        stage.add_system(system.system());
+        stage.add_system(system);
+        self
+    }
+
+    pub fn add_system_set_to_stage(
+        &mut self,
+        stage_name: &'static str,
+        system_set: SystemSet,
+    ) -> &mut Self {
+        let stage = self
+            .get_stage_mut::<SystemStage>(stage_name)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Stage '{}' does not exist or is not a SystemStage",
+                    stage_name
+                )
+            });
+        stage.add_system_set(system_set);
         self
     }
 
@@ -150,6 +179,16 @@ impl Schedule {
         }
     }
 
+    /// Renders the stages and systems of this schedule in the Graphviz DOT format, see [Stage::debug_graph]
+    pub fn to_dot(&self) -> String {
+        self.debug_graph("schedule").to_dot()
+    }
+
+    /// Renders the stages and systems of this schedule as JSON, see [Stage::debug_graph]
+    pub fn to_json(&self) -> String {
+        self.debug_graph("schedule").to_json()
+    }
+
     /// Shorthand for [Schedule::initialize] and [Schedule::run]
     pub fn initialize_and_run(&mut self, world: &mut World, resources: &mut Resources) {
         self.initialize(world, resources);
@@ -195,6 +234,31 @@ impl Stage for Schedule {
             }
         }
     }
+
+    fn debug_graph(&self, name: &str) -> DebugGraph {
+        let mut graph = DebugGraph::new(name);
+        if let Some(run_criteria) = &self.run_criteria {
+            graph.add_node(
+                format!("{}/run_criteria", name),
+                run_criteria.name(),
+                "run_criteria",
+            );
+        }
+        for stage_name in self.stage_order.iter() {
+            let stage = &self.stages[stage_name];
+            graph
+                .subgraphs
+                .push(stage.debug_graph(&format!("{}/{}", name, stage_name)));
+        }
+        graph
+    }
+
+    fn ambiguities(&self, world: &World, resources: &Resources) -> Vec<SystemAmbiguity> {
+        self.stage_order
+            .iter()
+            .flat_map(|name| self.stages[name].ambiguities(world, resources))
+            .collect()
+    }
 }
 
 pub fn clear_trackers_system(world: &mut World, resources: &mut Resources) {
@@ -206,9 +270,13 @@ pub fn clear_trackers_system(world: &mut World, resources: &mut Resources) {
 mod tests {
     use crate::{
         resource::{Res, ResMut, Resources},
-        schedule::{ParallelSystemStageExecutor, Schedule, SystemStage},
+        schedule::{
+            ParallelSystemStageExecutor, Schedule, ShouldRun, Stage, StageError, State, StateError,
+            StateScoped, StateStage, SystemAmbiguity, SystemDescriptorCoercion, SystemSet,
+            SystemStage,
+        },
         system::Query,
-        Commands, Entity, IntoSystem, World,
+        CommandFlush, Commands, Entity, IntoSystem, System, World,
     };
     use bevy_tasks::{ComputeTaskPool, TaskPool};
     use fixedbitset::FixedBitSet;
@@ -518,4 +586,516 @@ mod tests {
             run_and_validate(&mut schedule, &mut world, &mut resources);
         }
     }
+
+    #[derive(Default)]
+    struct ExecutionOrder(Arc<Mutex<Vec<&'static str>>>);
+
+    #[test]
+    fn explicit_system_ordering() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(ExecutionOrder::default());
+
+        fn a(order: Res<ExecutionOrder>) {
+            order.0.lock().push("a");
+        }
+        fn b(order: Res<ExecutionOrder>) {
+            order.0.lock().push("b");
+        }
+        fn c(order: Res<ExecutionOrder>) {
+            order.0.lock().push("c");
+        }
+        fn d(order: Res<ExecutionOrder>) {
+            order.0.lock().push("d");
+        }
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        enum Labels {
+            C,
+        }
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(d.system().after(Labels::C))
+            .add_system(c.system().label(Labels::C).after("b"))
+            .add_system(b.system().label("b"))
+            .add_system(a.system().before("b"));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update", stage);
+
+        for _ in 0..100 {
+            schedule.initialize_and_run(&mut world, &mut resources);
+            let execution_order = resources.get::<ExecutionOrder>().unwrap();
+            let mut order = execution_order.0.lock();
+            assert_eq!(*order, ["a", "b", "c", "d"]);
+            order.clear();
+        }
+
+        let stage = schedule.get_stage::<SystemStage>("update").unwrap();
+        let names = stage
+            .systems()
+            .iter()
+            .map(|system| system.name())
+            .collect::<Vec<_>>();
+        assert!(names[0].ends_with("::a") && names[1].ends_with("::b"));
+        assert_eq!(
+            stage
+                .get_executor::<ParallelSystemStageExecutor>()
+                .unwrap()
+                .system_dependents(),
+            vec![vec![1], vec![2], vec![3], vec![]]
+        );
+    }
+
+    #[test]
+    fn stage_positions_and_command_flush() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(ExecutionOrder::default());
+
+        fn spawn(commands: &mut Commands, order: Res<ExecutionOrder>) {
+            commands.spawn((1u32,));
+            order.0.lock().push("spawn");
+        }
+        fn count(query: Query<&u32>, order: Res<ExecutionOrder>) {
+            order.0.lock().push(match query.iter().count() {
+                0 => "count 0",
+                1 => "count 1",
+                _ => "count more",
+            });
+        }
+        fn first(_world: &mut World, resources: &mut Resources) {
+            resources
+                .get::<ExecutionOrder>()
+                .unwrap()
+                .0
+                .lock()
+                .push("first");
+        }
+        fn last(world: &mut World, resources: &mut Resources) {
+            let entities = world.query::<&u32>().count();
+            resources
+                .get::<ExecutionOrder>()
+                .unwrap()
+                .0
+                .lock()
+                .push(if entities == 1 { "last 1" } else { "last" });
+        }
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(last.system().at_end())
+            .add_system(count.system().label("before flush").after("spawn"))
+            .add_system(CommandFlush::default().label("flush").after("before flush"))
+            .add_system(spawn.system().label("spawn"))
+            .add_system(count.system().after("flush"))
+            .add_system(first.system().at_start());
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update", stage);
+        schedule.initialize_and_run(&mut world, &mut resources);
+        let execution_order = resources.get::<ExecutionOrder>().unwrap();
+        assert_eq!(
+            *execution_order.0.lock(),
+            ["first", "spawn", "count 0", "count 1", "last 1"]
+        );
+    }
+
+    #[test]
+    fn unknown_label() {
+        fn empty() {}
+
+        let mut stage = SystemStage::parallel();
+        stage.add_system(empty.system().after("missing"));
+        match stage.rebuild_order() {
+            Err(StageError::UnknownLabel { label, .. }) => {
+                assert_eq!(&*label, &"missing" as &dyn crate::SystemLabel)
+            }
+            _ => panic!("expected an unknown label error"),
+        }
+    }
+
+    #[test]
+    fn dependency_cycle() {
+        fn first() {}
+        fn second() {}
+        fn third() {}
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(first.system().label("first").after("third"))
+            .add_system(second.system().label("second").after("first"))
+            .add_system(third.system().label("third").after("second"));
+        match stage.rebuild_order() {
+            Err(StageError::DependencyCycle(systems)) => assert_eq!(systems.len(), 3),
+            _ => panic!("expected a dependency cycle error"),
+        }
+
+        // the stage still runs, in insertion order
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update", stage);
+        schedule.initialize_and_run(&mut world, &mut resources);
+    }
+
+    #[test]
+    fn system_set_run_criteria() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(ExecutionOrder::default());
+        resources.insert(0usize);
+
+        fn always(order: Res<ExecutionOrder>) {
+            order.0.lock().push("always");
+        }
+        fn gated(order: Res<ExecutionOrder>) {
+            order.0.lock().push("gated");
+        }
+        fn looped(order: Res<ExecutionOrder>) {
+            order.0.lock().push("looped");
+        }
+        fn every_other_run(mut runs: ResMut<usize>) -> ShouldRun {
+            *runs += 1;
+            if *runs % 2 == 0 {
+                ShouldRun::Yes
+            } else {
+                ShouldRun::No
+            }
+        }
+        fn three_times(mut count: crate::Local<usize>) -> ShouldRun {
+            *count += 1;
+            match *count {
+                1 | 2 => ShouldRun::YesAndLoop,
+                3 => ShouldRun::Yes,
+                _ => ShouldRun::No,
+            }
+        }
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(always.system().label("always"))
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(every_other_run.system())
+                    .with_system(gated.system())
+                    .after("always"),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(three_times.system())
+                    .with_system(looped.system())
+                    .label("looped")
+                    .after("always"),
+            );
+        let mut schedule = Schedule::default();
+        schedule.add_stage("update", stage);
+
+        schedule.initialize_and_run(&mut world, &mut resources);
+        assert_eq!(
+            *resources.get::<ExecutionOrder>().unwrap().0.lock(),
+            ["always", "looped", "looped", "looped"]
+        );
+        // the criteria of the first set was evaluated exactly once
+        assert_eq!(*resources.get::<usize>().unwrap(), 1);
+
+        resources.get::<ExecutionOrder>().unwrap().0.lock().clear();
+        schedule.initialize_and_run(&mut world, &mut resources);
+        assert_eq!(
+            *resources.get::<ExecutionOrder>().unwrap().0.lock(),
+            ["always", "gated"]
+        );
+        assert_eq!(*resources.get::<usize>().unwrap(), 2);
+    }
+
+    #[test]
+    fn state_run_criteria() {
+        #[derive(Clone)]
+        enum AppState {
+            Menu,
+            InGame,
+        }
+
+        fn in_game(order: Res<ExecutionOrder>) {
+            order.0.lock().push("in_game");
+        }
+
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(ExecutionOrder::default());
+        resources.insert(State::new(AppState::Menu));
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage(
+            "update",
+            SystemStage::parallel().with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(State::on_update(AppState::InGame))
+                    .with_system(in_game.system()),
+            ),
+        );
+
+        schedule.initialize_and_run(&mut world, &mut resources);
+        assert!(resources
+            .get::<ExecutionOrder>()
+            .unwrap()
+            .0
+            .lock()
+            .is_empty());
+
+        *resources.get_mut::<State<AppState>>().unwrap() = State::new(AppState::InGame);
+        schedule.initialize_and_run(&mut world, &mut resources);
+        assert_eq!(
+            *resources.get::<ExecutionOrder>().unwrap().0.lock(),
+            ["in_game"]
+        );
+    }
+
+    #[test]
+    fn state_stack() {
+        #[derive(Clone, Debug, PartialEq)]
+        enum AppState {
+            InGame,
+            Paused,
+        }
+
+        fn push(name: &'static str) -> impl System<In = (), Out = ()> {
+            (move |order: Res<ExecutionOrder>| order.0.lock().push(name)).system()
+        }
+
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(ExecutionOrder::default());
+        resources.insert(State::new(AppState::InGame));
+
+        let mut stage = StateStage::<AppState>::default();
+        stage
+            .on_state_enter(AppState::InGame, push("enter_game"))
+            .on_state_update(AppState::InGame, push("update_game"))
+            .on_state_pause(AppState::InGame, push("pause_game"))
+            .on_state_resume(AppState::InGame, push("resume_game"))
+            .on_state_enter(AppState::Paused, push("enter_paused"))
+            .on_state_update(AppState::Paused, push("update_paused"))
+            .on_state_exit(AppState::Paused, push("exit_paused"));
+        let mut schedule = Schedule::default();
+        schedule.add_stage("state", stage);
+
+        let mut run_and_take_order = |world: &mut World, resources: &mut Resources| {
+            schedule.initialize_and_run(world, resources);
+            let execution_order = resources.get::<ExecutionOrder>().unwrap();
+            let order = execution_order.0.lock().drain(..).collect::<Vec<_>>();
+            order
+        };
+
+        assert_eq!(
+            run_and_take_order(&mut world, &mut resources),
+            ["enter_game", "update_game"]
+        );
+        let game_entity = world.spawn((StateScoped(AppState::InGame),));
+
+        {
+            let mut state = resources.get_mut::<State<AppState>>().unwrap();
+            assert!(matches!(state.pop(), Err(StateError::StackEmpty)));
+            state.push(AppState::Paused).unwrap();
+            assert_eq!(state.next(), Some(&AppState::Paused));
+        }
+        assert_eq!(
+            run_and_take_order(&mut world, &mut resources),
+            ["pause_game", "enter_paused", "update_paused"]
+        );
+        let paused_entity = world.spawn((StateScoped(AppState::Paused),));
+        {
+            let state = resources.get::<State<AppState>>().unwrap();
+            assert_eq!(state.current(), &AppState::Paused);
+            assert_eq!(state.paused(), [AppState::InGame]);
+        }
+
+        resources
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .pop()
+            .unwrap();
+        assert_eq!(
+            run_and_take_order(&mut world, &mut resources),
+            ["exit_paused", "resume_game", "update_game"]
+        );
+        {
+            let state = resources.get::<State<AppState>>().unwrap();
+            assert_eq!(state.current(), &AppState::InGame);
+            assert!(state.paused().is_empty());
+        }
+        assert!(!world.contains(paused_entity));
+        assert!(world.contains(game_entity));
+
+        // popping a state must keep the entities of the same state paused lower in the stack
+        resources
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .push(AppState::Paused)
+            .unwrap();
+        run_and_take_order(&mut world, &mut resources);
+        resources
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .push(AppState::InGame)
+            .unwrap();
+        assert_eq!(
+            run_and_take_order(&mut world, &mut resources),
+            ["enter_game", "update_game"]
+        );
+        let nested_game_entity = world.spawn((StateScoped(AppState::InGame),));
+
+        resources
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .pop()
+            .unwrap();
+        run_and_take_order(&mut world, &mut resources);
+        assert!(!world.contains(nested_game_entity));
+        assert!(world.contains(game_entity));
+
+        resources
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .pop()
+            .unwrap();
+        run_and_take_order(&mut world, &mut resources);
+        assert!(world.contains(game_entity));
+        resources
+            .get_mut::<State<AppState>>()
+            .unwrap()
+            .set_next(AppState::Paused)
+            .unwrap();
+        run_and_take_order(&mut world, &mut resources);
+        assert!(!world.contains(game_entity));
+    }
+
+    #[test]
+    fn schedule_debug_graph() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(0usize);
+
+        fn read(_: Res<usize>) {}
+        fn write(_: ResMut<usize>) {}
+        fn criteria() -> ShouldRun {
+            ShouldRun::Yes
+        }
+
+        let mut schedule = Schedule::default();
+        schedule.add_stage(
+            "update",
+            SystemStage::parallel()
+                .with_system(write.system().label("write"))
+                .with_system(read.system().after("write"))
+                .with_system_set(
+                    SystemSet::new()
+                        .with_run_criteria(criteria.system())
+                        .with_system(write.system()),
+                ),
+        );
+        schedule.initialize_and_run(&mut world, &mut resources);
+
+        let graph = schedule.debug_graph("schedule");
+        let stage = &graph.subgraphs[0];
+        assert_eq!(stage.name, "schedule/update");
+        assert_eq!(stage.nodes.len(), 4);
+        let edge_kinds = stage
+            .edges
+            .iter()
+            .map(|edge| (edge.from.as_str(), edge.to.as_str(), edge.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            edge_kinds,
+            vec![
+                (
+                    "schedule/update/set_run_criteria/0",
+                    "schedule/update/system/2",
+                    "run_criteria"
+                ),
+                (
+                    "schedule/update/system/0",
+                    "schedule/update/system/1",
+                    "ordering"
+                ),
+                (
+                    "schedule/update/system/0",
+                    "schedule/update/system/2",
+                    "dependency"
+                ),
+                (
+                    "schedule/update/system/1",
+                    "schedule/update/system/2",
+                    "dependency"
+                ),
+            ]
+        );
+
+        let dot = schedule.to_dot();
+        assert!(dot.contains("subgraph \"cluster_schedule/update\""));
+        assert!(dot.contains("\"schedule/update/system/0\" -> \"schedule/update/system/1\""));
+        let json = schedule.to_json();
+        assert!(
+            json.starts_with("{\"name\":\"schedule\",\"nodes\":[],\"edges\":[],\"subgraphs\":[")
+        );
+        assert!(json.contains("\"kind\":\"run_criteria\""));
+    }
+
+    #[test]
+    fn ambiguity_detection() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(ComputeTaskPool(TaskPool::default()));
+        resources.insert(0usize);
+        world.spawn((0u32, 0.0f32));
+
+        fn write_u32(_: Query<&mut u32>) {}
+        fn read_u32(_: Query<&u32>) {}
+        fn read_u32_ordered(_: Query<&u32>) {}
+        fn read_f32(_: Query<&f32>, _: Res<usize>) {}
+        fn write_res(_: ResMut<usize>) {}
+
+        let mut stage = SystemStage::parallel();
+        stage
+            .add_system(write_u32.system().label("write"))
+            .add_system(read_u32.system())
+            .add_system(read_u32_ordered.system().after("write"))
+            .add_system(read_f32.system())
+            .add_system(write_res.system());
+        stage.initialize(&mut world, &mut resources);
+        stage.run(&mut world, &mut resources);
+
+        let ambiguities = Stage::ambiguities(&stage, &world, &resources);
+        assert_eq!(
+            ambiguities,
+            vec![
+                SystemAmbiguity {
+                    first: write_u32.system().name(),
+                    second: read_u32.system().name(),
+                    conflicts: vec!["u32"],
+                },
+                SystemAmbiguity {
+                    first: read_f32.system().name(),
+                    second: write_res.system().name(),
+                    conflicts: vec!["usize"],
+                },
+            ]
+        );
+
+        let mut serial_stage = SystemStage::serial();
+        serial_stage
+            .add_system(write_u32.system())
+            .add_system(read_u32.system());
+        serial_stage.initialize(&mut world, &mut resources);
+        serial_stage.run(&mut world, &mut resources);
+        assert!(Stage::ambiguities(&serial_stage, &world, &resources).is_empty());
+    }
 }
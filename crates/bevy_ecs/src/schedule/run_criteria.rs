@@ -0,0 +1,159 @@
+use crate::{
+    AddedRes, ArchetypeComponent, ChangedRes, IntoSystem, Res, Resource, Resources, ShouldRun,
+    System, SystemId, ThreadLocalExecution, TypeAccess, World,
+};
+use std::{any::TypeId, borrow::Cow, marker::PhantomData};
+
+fn should_run(condition: bool) -> ShouldRun {
+    if condition {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Run criteria that runs while a `T` resource exists
+pub fn resource_exists<T: Resource>() -> impl System<In = (), Out = ShouldRun> {
+    (|resource: Option<Res<T>>| should_run(resource.is_some())).system()
+}
+
+/// Run criteria that runs when a `T` resource was inserted since the last tracker reset
+pub fn resource_added<T: Resource>() -> impl System<In = (), Out = ShouldRun> {
+    (|resource: Option<AddedRes<T>>| should_run(resource.is_some())).system()
+}
+
+/// Run criteria that runs when a `T` resource was inserted or mutated since the last tracker reset
+pub fn resource_changed<T: Resource>() -> impl System<In = (), Out = ShouldRun> {
+    (|resource: Option<ChangedRes<T>>| should_run(resource.is_some())).system()
+}
+
+/// Run criteria that runs when a `T` resource was removed since the last tracker reset, see
+/// [Resources::remove]
+pub fn resource_removed<T: Resource>() -> ResourceRemoved<T> {
+    ResourceRemoved {
+        system_id: SystemId::new(),
+        resource_access: Default::default(),
+        archetype_access: Default::default(),
+        marker: PhantomData,
+    }
+}
+
+/// The run criteria returned by [resource_removed]
+pub struct ResourceRemoved<T> {
+    system_id: SystemId,
+    resource_access: TypeAccess<TypeId>,
+    archetype_access: TypeAccess<ArchetypeComponent>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource> System for ResourceRemoved<T> {
+    type In = ();
+    type Out = ShouldRun;
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(std::any::type_name::<ResourceRemoved<T>>())
+    }
+
+    fn id(&self) -> SystemId {
+        self.system_id
+    }
+
+    fn update(&mut self, _world: &World) {}
+
+    fn archetype_component_access(&self) -> &TypeAccess<ArchetypeComponent> {
+        &self.archetype_access
+    }
+
+    fn resource_access(&self) -> &TypeAccess<TypeId> {
+        &self.resource_access
+    }
+
+    fn thread_local_execution(&self) -> ThreadLocalExecution {
+        ThreadLocalExecution::Immediate
+    }
+
+    unsafe fn run_unsafe(
+        &mut self,
+        _input: Self::In,
+        _world: &World,
+        resources: &Resources,
+    ) -> Option<Self::Out> {
+        // removals need unique access to Resources, so they can't happen while this runs
+        Some(should_run(resources.is_removed::<T>()))
+    }
+
+    fn run_thread_local(&mut self, _world: &mut World, _resources: &mut Resources) {}
+
+    fn initialize(&mut self, _world: &mut World, _resources: &mut Resources) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{resource_added, resource_changed, resource_exists, resource_removed};
+    use crate::{IntoSystem, ResMut, Resources, Stage, SystemSet, SystemStage, World};
+
+    struct Score(u32);
+
+    #[derive(Default)]
+    struct Ran(Vec<&'static str>);
+
+    fn exists(mut ran: ResMut<Ran>) {
+        ran.0.push("exists");
+    }
+
+    fn added(mut ran: ResMut<Ran>) {
+        ran.0.push("added");
+    }
+
+    fn changed(mut ran: ResMut<Ran>) {
+        ran.0.push("changed");
+    }
+
+    fn removed(mut ran: ResMut<Ran>) {
+        ran.0.push("removed");
+    }
+
+    #[test]
+    fn resource_run_criteria() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert(Ran::default());
+
+        let mut stage = SystemStage::serial()
+            .with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(resource_exists::<Score>())
+                    .with_system(exists.system()),
+            )
+            .with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(resource_added::<Score>())
+                    .with_system(added.system()),
+            )
+            .with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(resource_changed::<Score>())
+                    .with_system(changed.system()),
+            )
+            .with_system_set(
+                SystemSet::new()
+                    .with_run_criteria(resource_removed::<Score>())
+                    .with_system(removed.system()),
+            );
+        let mut run = |resources: &mut Resources| {
+            stage.run(&mut world, resources);
+            resources.clear_trackers();
+            std::mem::take(&mut resources.get_mut::<Ran>().unwrap().0)
+        };
+
+        assert_eq!(run(&mut resources), Vec::<&str>::new());
+        resources.insert(Score(0));
+        assert_eq!(run(&mut resources), vec!["exists", "added", "changed"]);
+        assert_eq!(run(&mut resources), vec!["exists"]);
+        resources.get_mut::<Score>().unwrap().0 += 1;
+        assert_eq!(run(&mut resources), vec!["exists", "changed"]);
+        assert_eq!(resources.remove::<Score>().unwrap().0, 1);
+        assert_eq!(run(&mut resources), vec!["removed"]);
+        assert_eq!(run(&mut resources), Vec::<&str>::new());
+    }
+}
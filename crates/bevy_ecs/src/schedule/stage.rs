@@ -1,16 +1,34 @@
-use std::{any::TypeId, borrow::Cow};
+use std::{any::TypeId, borrow::Cow, cmp::Reverse, collections::BinaryHeap};
 
 use crate::{
-    ArchetypeComponent, BoxedSystem, Resources, System, SystemId, ThreadLocalExecution, TypeAccess,
-    World,
+    ArchetypeComponent, BoxedSystem, BoxedSystemLabel, Resources, System, SystemId,
+    ThreadLocalExecution, TypeAccess, World,
+};
+use bevy_utils::{
+    tracing::{error, warn},
+    HashMap, HashSet,
 };
-use bevy_utils::HashSet;
 use downcast_rs::{impl_downcast, Downcast};
+use thiserror::Error;
 
-use super::{ParallelSystemStageExecutor, SerialSystemStageExecutor, SystemStageExecutor};
+use super::{
+    find_ambiguities, DebugGraph, ParallelSystemStageExecutor, ReportExecutionOrderAmbiguities,
+    SerialSystemStageExecutor, SystemAmbiguity, SystemDescriptor, SystemOrdering, SystemSet,
+    SystemStageExecutor,
+};
+use fixedbitset::FixedBitSet;
 
+#[derive(Debug, Error)]
 pub enum StageError {
+    #[error("System with id {0:?} already exists.")]
     SystemAlreadyExists(SystemId),
+    #[error("System {system} is ordered relative to the label {label:?}, but no system in the stage has that label.")]
+    UnknownLabel {
+        system: Cow<'static, str>,
+        label: BoxedSystemLabel,
+    },
+    #[error("Systems have a cyclic ordering dependency: {0:?}.")]
+    DependencyCycle(Vec<Cow<'static, str>>),
 }
 
 pub trait Stage: Downcast + Send + Sync {
@@ -20,12 +38,33 @@ pub trait Stage: Downcast + Send + Sync {
 
     /// Runs the stage. This happens once per update (after [Stage::initialize] is called).
     fn run(&mut self, world: &mut World, resources: &mut Resources);
+
+    /// Describes the contents of this stage for debugging. `name` is the name this stage was added with and
+    /// should prefix every node id so that ids stay unique across a [Schedule](crate::Schedule).
+    fn debug_graph(&self, name: &str) -> DebugGraph {
+        DebugGraph::new(name)
+    }
+
+    /// Lists the pairs of systems in this stage (and its nested stages) that conflict but whose relative
+    /// order is unspecified. See [SystemAmbiguity].
+    fn ambiguities(&self, _world: &World, _resources: &Resources) -> Vec<SystemAmbiguity> {
+        Vec::new()
+    }
 }
 
 impl_downcast!(Stage);
 
 pub struct SystemStage {
     systems: Vec<BoxedSystem>,
+    system_orderings: Vec<SystemOrdering>,
+    /// for each system, the index of the [SystemSet] run criteria that gates it (if any)
+    system_set_indices: Vec<Option<usize>>,
+    /// for each system, the indices of the systems it must run after because of its [SystemOrdering]
+    ordering_dependencies: Vec<Vec<usize>>,
+    set_run_criteria: Vec<BoxedSystem<(), ShouldRun>>,
+    uninitialized_set_run_criteria: Vec<usize>,
+    systems_modified: bool,
+    ambiguities_reported: bool,
     system_ids: HashSet<SystemId>,
     executor: Box<dyn SystemStageExecutor>,
     run_criteria: Option<BoxedSystem<(), ShouldRun>>,
@@ -41,13 +80,20 @@ impl SystemStage {
             run_criteria: None,
             run_criteria_initialized: false,
             systems: Default::default(),
+            system_orderings: Default::default(),
+            system_set_indices: Default::default(),
+            ordering_dependencies: Default::default(),
+            set_run_criteria: Default::default(),
+            uninitialized_set_run_criteria: Default::default(),
+            systems_modified: false,
+            ambiguities_reported: false,
             system_ids: Default::default(),
             uninitialized_systems: Default::default(),
             unexecuted_systems: Default::default(),
         }
     }
 
-    pub fn single<S: System<In = (), Out = ()>>(system: S) -> Self {
+    pub fn single<S: Into<SystemDescriptor>>(system: S) -> Self {
         Self::serial().with_system(system)
     }
 
@@ -59,8 +105,13 @@ impl SystemStage {
         Self::new(Box::new(ParallelSystemStageExecutor::default()))
     }
 
-    pub fn with_system<S: System<In = (), Out = ()>>(mut self, system: S) -> Self {
-        self.add_system_boxed(Box::new(system));
+    pub fn with_system<S: Into<SystemDescriptor>>(mut self, system: S) -> Self {
+        self.add_system(system);
+        self
+    }
+
+    pub fn with_system_set(mut self, system_set: SystemSet) -> Self {
+        self.add_system_set(system_set);
         self
     }
 
@@ -70,12 +121,37 @@ impl SystemStage {
         self
     }
 
-    pub fn add_system<S: System<In = (), Out = ()>>(&mut self, system: S) -> &mut Self {
-        self.add_system_boxed(Box::new(system));
-        self
+    pub fn add_system<S: Into<SystemDescriptor>>(&mut self, system: S) -> &mut Self {
+        let SystemDescriptor { system, ordering } = system.into();
+        self.add_system_with_ordering(system, ordering, None)
     }
 
     pub fn add_system_boxed(&mut self, system: BoxedSystem) -> &mut Self {
+        self.add_system_with_ordering(system, SystemOrdering::default(), None)
+    }
+
+    /// Adds all systems of the given [SystemSet]. If the set has run criteria, it is evaluated once per
+    /// stage run and gates every system in the set.
+    pub fn add_system_set(&mut self, system_set: SystemSet) -> &mut Self {
+        let (run_criteria, descriptors) = system_set.bake();
+        let set_index = run_criteria.map(|run_criteria| {
+            self.uninitialized_set_run_criteria
+                .push(self.set_run_criteria.len());
+            self.set_run_criteria.push(run_criteria);
+            self.set_run_criteria.len() - 1
+        });
+        for SystemDescriptor { system, ordering } in descriptors {
+            self.add_system_with_ordering(system, ordering, set_index);
+        }
+        self
+    }
+
+    fn add_system_with_ordering(
+        &mut self,
+        system: BoxedSystem,
+        ordering: SystemOrdering,
+        set_index: Option<usize>,
+    ) -> &mut Self {
         if self.system_ids.contains(&system.id()) {
             panic!(
                 "System with id {:?} ({}) already exists",
@@ -87,9 +163,142 @@ impl SystemStage {
         self.unexecuted_systems.push(self.systems.len());
         self.uninitialized_systems.push(self.systems.len());
         self.systems.push(system);
+        self.system_orderings.push(ordering);
+        self.system_set_indices.push(set_index);
+        self.systems_modified = true;
+        self.ambiguities_reported = false;
         self
     }
 
+    /// The systems of this stage. Once the stage has run, they are sorted in execution order.
+    pub fn systems(&self) -> &[BoxedSystem] {
+        &self.systems
+    }
+
+    /// Sorts the systems of this stage so that every system comes after the systems it is ordered after
+    /// (and before the systems it is ordered before). Systems without ordering constraints between them keep
+    /// their insertion order. This runs automatically before the stage executes after systems were added.
+    pub fn rebuild_order(&mut self) -> Result<(), StageError> {
+        self.systems_modified = false;
+        let result = self.sort_systems();
+        if result.is_err() {
+            self.ordering_dependencies = vec![Vec::new(); self.systems.len()];
+        }
+        result
+    }
+
+    fn sort_systems(&mut self) -> Result<(), StageError> {
+        let mut labelled_systems = HashMap::<&BoxedSystemLabel, Vec<usize>>::default();
+        for (index, ordering) in self.system_orderings.iter().enumerate() {
+            for label in ordering.labels.iter() {
+                labelled_systems.entry(label).or_default().push(index);
+            }
+        }
+
+        // dependencies[i] contains the indices of the systems that must run before system i
+        let mut dependencies = vec![Vec::new(); self.systems.len()];
+        for (index, ordering) in self.system_orderings.iter().enumerate() {
+            let constraints = ordering
+                .before
+                .iter()
+                .map(|label| (label, true))
+                .chain(ordering.after.iter().map(|label| (label, false)));
+            for (label, is_before) in constraints {
+                let targets =
+                    labelled_systems
+                        .get(label)
+                        .ok_or_else(|| StageError::UnknownLabel {
+                            system: self.systems[index].name(),
+                            label: label.clone(),
+                        })?;
+                for &target in targets.iter().filter(|target| **target != index) {
+                    let (first, second) = if is_before {
+                        (index, target)
+                    } else {
+                        (target, index)
+                    };
+                    if !dependencies[second].contains(&first) {
+                        dependencies[second].push(first);
+                    }
+                }
+            }
+        }
+
+        // systems at the start of the stage run before the others, systems at the end after the others
+        for (index, ordering) in self.system_orderings.iter().enumerate() {
+            for (other_index, other_ordering) in self.system_orderings.iter().enumerate() {
+                if ordering.position < other_ordering.position
+                    && !dependencies[other_index].contains(&index)
+                {
+                    dependencies[other_index].push(index);
+                }
+            }
+        }
+
+        // topological sort that prefers insertion order among systems that are ready
+        let mut dependency_counts = dependencies.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut dependents = vec![Vec::new(); self.systems.len()];
+        for (index, system_dependencies) in dependencies.iter().enumerate() {
+            for &dependency in system_dependencies.iter() {
+                dependents[dependency].push(index);
+            }
+        }
+        let mut ready = dependency_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count == 0)
+            .map(|(index, _)| Reverse(index))
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::with_capacity(self.systems.len());
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+            for &dependent in dependents[index].iter() {
+                dependency_counts[dependent] -= 1;
+                if dependency_counts[dependent] == 0 {
+                    ready.push(Reverse(dependent));
+                }
+            }
+        }
+
+        if order.len() != self.systems.len() {
+            let cycle = dependency_counts
+                .iter()
+                .enumerate()
+                .filter(|(_, count)| **count > 0)
+                .map(|(index, _)| self.systems[index].name())
+                .collect();
+            return Err(StageError::DependencyCycle(cycle));
+        }
+
+        let mut new_indices = vec![0; order.len()];
+        for (new_index, old_index) in order.iter().enumerate() {
+            new_indices[*old_index] = new_index;
+        }
+
+        reorder(&mut self.systems, &order);
+        reorder(&mut self.system_orderings, &order);
+        reorder(&mut self.system_set_indices, &order);
+        self.ordering_dependencies = order
+            .iter()
+            .map(|old_index| {
+                dependencies[*old_index]
+                    .iter()
+                    .map(|dependency| new_indices[*dependency])
+                    .collect()
+            })
+            .collect();
+
+        for index in self
+            .uninitialized_systems
+            .iter_mut()
+            .chain(self.unexecuted_systems.iter_mut())
+        {
+            *index = new_indices[*index];
+        }
+
+        Ok(())
+    }
+
     pub fn get_executor<T: SystemStageExecutor>(&self) -> Option<&T> {
         self.executor.downcast_ref()
     }
@@ -99,12 +308,92 @@ impl SystemStage {
     }
 
     pub fn run_once(&mut self, world: &mut World, resources: &mut Resources) {
-        let unexecuted_systems = std::mem::take(&mut self.unexecuted_systems);
-        self.executor
-            .execute_stage(&mut self.systems, &unexecuted_systems, world, resources);
+        if self.systems_modified {
+            if let Err(err) = self.rebuild_order() {
+                error!("{} Falling back to insertion order.", err);
+            }
+        }
+
+        let mut set_results = self
+            .set_run_criteria
+            .iter_mut()
+            .map(|run_criteria| evaluate_run_criteria(run_criteria, world, resources))
+            .collect::<Vec<_>>();
+        let mut unexecuted_systems = std::mem::take(&mut self.unexecuted_systems);
+        let mut should_run = FixedBitSet::with_capacity(self.systems.len());
+        let mut first_pass = true;
+        loop {
+            should_run.clear();
+            for (system_index, set_index) in self.system_set_indices.iter().enumerate() {
+                let run = match set_index {
+                    Some(set_index) => set_results[*set_index] != ShouldRun::No,
+                    // systems outside of sets only run once per stage run
+                    None => first_pass,
+                };
+                should_run.set(system_index, run);
+            }
+
+            self.executor.execute_stage(
+                &mut self.systems,
+                &unexecuted_systems,
+                &self.ordering_dependencies,
+                &should_run,
+                world,
+                resources,
+            );
+
+            // system accesses are only up to date once the executor has run them
+            if !self.ambiguities_reported {
+                self.ambiguities_reported = true;
+                if resources.contains::<ReportExecutionOrderAmbiguities>() {
+                    for ambiguity in Stage::ambiguities(self, world, resources) {
+                        warn!("{}", ambiguity);
+                    }
+                }
+            }
+
+            if !set_results.contains(&ShouldRun::YesAndLoop) {
+                break;
+            }
+
+            // sets that asked to loop are checked again, everything else is done for this run
+            for (run_criteria, result) in
+                self.set_run_criteria.iter_mut().zip(set_results.iter_mut())
+            {
+                *result = match result {
+                    ShouldRun::YesAndLoop => evaluate_run_criteria(run_criteria, world, resources),
+                    _ => ShouldRun::No,
+                };
+            }
+            unexecuted_systems.clear();
+            first_pass = false;
+        }
     }
 }
 
+fn reorder<T>(items: &mut Vec<T>, order: &[usize]) {
+    let mut items_by_index = std::mem::take(items)
+        .into_iter()
+        .map(Some)
+        .collect::<Vec<_>>();
+    items.extend(
+        order
+            .iter()
+            .map(|index| items_by_index[*index].take().unwrap()),
+    );
+}
+
+fn evaluate_run_criteria(
+    run_criteria: &mut BoxedSystem<(), ShouldRun>,
+    world: &mut World,
+    resources: &mut Resources,
+) -> ShouldRun {
+    let should_run = run_criteria.run((), world, resources);
+    run_criteria.run_thread_local(world, resources);
+    // don't run when no result is returned or false is returned
+    should_run.unwrap_or(ShouldRun::No)
+}
+
 impl Stage for SystemStage {
     fn initialize(&mut self, world: &mut World, resources: &mut Resources) {
         if let Some(ref mut run_criteria) = self.run_criteria {
@@ -114,6 +403,12 @@ impl Stage for SystemStage {
             }
         }
 
+        let uninitialized_set_run_criteria =
+            std::mem::take(&mut self.uninitialized_set_run_criteria);
+        for set_index in uninitialized_set_run_criteria.iter() {
+            self.set_run_criteria[*set_index].initialize(world, resources);
+        }
+
         let uninitialized_systems = std::mem::take(&mut self.uninitialized_systems);
         for system_index in uninitialized_systems.iter() {
             self.systems[*system_index].initialize(world, resources);
@@ -123,10 +418,7 @@ impl Stage for SystemStage {
     fn run(&mut self, world: &mut World, resources: &mut Resources) {
         loop {
             let should_run = if let Some(ref mut run_criteria) = self.run_criteria {
-                let should_run = run_criteria.run((), world, resources);
-                run_criteria.run_thread_local(world, resources);
-                // don't run when no result is returned or false is returned
-                should_run.unwrap_or(ShouldRun::No)
+                evaluate_run_criteria(run_criteria, world, resources)
             } else {
                 ShouldRun::Yes
             };
@@ -143,8 +435,75 @@ impl Stage for SystemStage {
             }
         }
     }
+
+    /// Includes the stage's systems, run criteria and explicit ordering constraints. If the stage uses a
+    /// [ParallelSystemStageExecutor] and has run at least once, the dependencies it derived are included too.
+    fn debug_graph(&self, name: &str) -> DebugGraph {
+        let mut graph = DebugGraph::new(name);
+        let system_id = |index: usize| format!("{}/system/{}", name, index);
+        for (index, system) in self.systems.iter().enumerate() {
+            graph.add_node(system_id(index), system.name(), "system");
+        }
+
+        if let Some(run_criteria) = &self.run_criteria {
+            graph.add_node(
+                format!("{}/run_criteria", name),
+                run_criteria.name(),
+                "run_criteria",
+            );
+        }
+        for (set_index, run_criteria) in self.set_run_criteria.iter().enumerate() {
+            let criteria_id = format!("{}/set_run_criteria/{}", name, set_index);
+            graph.add_node(criteria_id.clone(), run_criteria.name(), "run_criteria");
+            for (system_index, _) in self
+                .system_set_indices
+                .iter()
+                .enumerate()
+                .filter(|(_, index)| **index == Some(set_index))
+            {
+                graph.add_edge(
+                    criteria_id.clone(),
+                    system_id(system_index),
+                    "run_criteria",
+                    None,
+                );
+            }
+        }
+
+        for (index, dependencies) in self.ordering_dependencies.iter().enumerate() {
+            for dependency in dependencies.iter() {
+                graph.add_edge(system_id(*dependency), system_id(index), "ordering", None);
+            }
+        }
+
+        if let Some(executor) = self.get_executor::<ParallelSystemStageExecutor>() {
+            for (index, dependents) in executor.system_dependents().iter().enumerate() {
+                for dependent in dependents.iter() {
+                    let is_ordering = self
+                        .ordering_dependencies
+                        .get(*dependent)
+                        .map_or(false, |dependencies| dependencies.contains(&index));
+                    if !is_ordering {
+                        graph.add_edge(system_id(index), system_id(*dependent), "dependency", None);
+                    }
+                }
+            }
+        }
+
+        graph
+    }
+
+    /// Systems are only ambiguous with the parallel executor, because the serial executor always runs them in
+    /// order. Call this after the stage has run, so that system accesses are up to date.
+    fn ambiguities(&self, world: &World, resources: &Resources) -> Vec<SystemAmbiguity> {
+        if self.get_executor::<SerialSystemStageExecutor>().is_some() {
+            return Vec::new();
+        }
+        find_ambiguities(&self.systems, &self.ordering_dependencies, world, resources)
+    }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
 pub enum ShouldRun {
     /// No, the system should not run
     No,
@@ -10,10 +10,15 @@ use crate::{
 };
 
 pub trait SystemStageExecutor: Downcast + Send + Sync {
+    /// Runs `systems`, which are sorted in an order that satisfies their ordering constraints.
+    /// `ordering_dependencies[i]` lists the systems that were explicitly ordered before system `i`.
+    /// Systems that are not in `should_run` are skipped (their run criteria said no).
     fn execute_stage(
         &mut self,
         systems: &mut [BoxedSystem],
         changed_systems: &[usize],
+        ordering_dependencies: &[Vec<usize>],
+        should_run: &FixedBitSet,
         world: &mut World,
         resources: &mut Resources,
     );
@@ -29,10 +34,28 @@ impl SystemStageExecutor for SerialSystemStageExecutor {
         &mut self,
         systems: &mut [BoxedSystem],
         _changed_systems: &[usize],
+        _ordering_dependencies: &[Vec<usize>],
+        should_run: &FixedBitSet,
         world: &mut World,
         resources: &mut Resources,
     ) {
-        for system in systems.iter_mut() {
+        // the index of the first system whose commands have not been applied yet
+        let mut unflushed_start = 0;
+        for system_index in 0..systems.len() {
+            if !should_run.contains(system_index) {
+                continue;
+            }
+            if systems[system_index].is_command_flush() {
+                flush_commands(
+                    systems,
+                    unflushed_start..system_index,
+                    should_run,
+                    world,
+                    resources,
+                );
+                unflushed_start = system_index + 1;
+            }
+            let system = &mut systems[system_index];
             system.update(world);
             match system.thread_local_execution() {
                 ThreadLocalExecution::NextFlush => {
@@ -46,12 +69,36 @@ impl SystemStageExecutor for SerialSystemStageExecutor {
         }
 
         // "flush"
-        for system in systems.iter_mut() {
-            match system.thread_local_execution() {
-                ThreadLocalExecution::NextFlush => system.run_thread_local(world, resources),
-                ThreadLocalExecution::Immediate => { /* already ran immediate */ }
-            }
+        flush_commands(
+            systems,
+            unflushed_start..systems.len(),
+            should_run,
+            world,
+            resources,
+        );
+    }
+}
+
+/// Applies the buffered commands of the systems in `range` that ran
+fn flush_commands(
+    systems: &mut [BoxedSystem],
+    range: Range<usize>,
+    should_run: &FixedBitSet,
+    world: &mut World,
+    resources: &mut Resources,
+) {
+    for system_index in range {
+        let system = &mut systems[system_index];
+        if !should_run.contains(system_index)
+            || system.thread_local_execution() != ThreadLocalExecution::NextFlush
+        {
+            continue;
         }
+        #[cfg(feature = "trace")]
+        let system_span = bevy_utils::tracing::info_span!("system", name = system.name().as_ref());
+        #[cfg(feature = "trace")]
+        let _system_guard = system_span.enter();
+        system.run_thread_local(world, resources);
     }
 }
 
@@ -62,6 +109,7 @@ impl SystemStageExecutor for SerialSystemStageExecutor {
 /// * in a given stage, systems the read [archetype+component] X cannot run before systems registered before them that write [archetype+component] X
 /// * in a given stage, systems that mutate resource Y cannot run before systems registered before them that read/write resource Y
 /// * in a given stage, systems the read resource Y cannot run before systems registered before them that write resource Y
+/// * in a given stage, systems ordered after a label (or before it) respect that ordering
 pub struct ParallelSystemStageExecutor {
     /// each system's set of dependencies
     system_dependencies: Vec<FixedBitSet>,
@@ -112,6 +160,7 @@ impl ParallelSystemStageExecutor {
         &mut self,
         world: &World,
         systems: &mut [BoxedSystem],
+        ordering_dependencies: &[Vec<usize>],
         stage_changed: bool,
         next_thread_local_index: usize,
     ) -> Range<usize> {
@@ -193,6 +242,17 @@ impl ParallelSystemStageExecutor {
                             }
                         }
 
+                        // explicit ordering constraints on systems in this batch. earlier batches
+                        // have already finished by the time this batch runs
+                        for &dependency in ordering_dependencies[system_index].iter() {
+                            if dependency >= prepare_system_index_range.start
+                                && !self.system_dependencies[system_index].contains(dependency)
+                            {
+                                self.system_dependents[dependency].push(system_index);
+                                self.system_dependencies[system_index].insert(dependency);
+                            }
+                        }
+
                         current_archetype_access.union(archetype_access);
                         current_resource_access.union(resource_access);
 
@@ -288,12 +348,14 @@ impl ParallelSystemStageExecutor {
         }
     }
 
-    /// Runs the non-thread-local systems in the given prepared_system_range range
+    /// Runs the non-thread-local systems in the given prepared_system_range range. Systems that are not in
+    /// `should_run` still wait for their dependencies and notify their dependents, but are not executed.
     pub fn run_systems(
         &self,
         world: &World,
         resources: &Resources,
         systems: &mut [BoxedSystem],
+        should_run: &FixedBitSet,
         prepared_system_range: Range<usize>,
         compute_pool: &TaskPool,
     ) {
@@ -332,6 +394,7 @@ impl ParallelSystemStageExecutor {
                 let resources_ref = &*resources;
 
                 let trigger_events = &self.ready_events_of_dependents[system_index];
+                let run = should_run.contains(system_index);
 
                 // Verify that any dependent task has a > 0 count. If a dependent task has > 0
                 // count, then the current system we are starting now isn't blocking it from running
@@ -360,7 +423,7 @@ impl ParallelSystemStageExecutor {
 
                     // Execute the system - in a scope to ensure the system lock is dropped before
                     // triggering dependents
-                    {
+                    if run {
                         #[cfg(feature = "trace")]
                         let system_span = bevy_utils::tracing::info_span!(
                             "system",
@@ -391,6 +454,8 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
         &mut self,
         systems: &mut [BoxedSystem],
         changed_systems: &[usize],
+        ordering_dependencies: &[Vec<usize>],
+        should_run: &FixedBitSet,
         world: &mut World,
         resources: &mut Resources,
     ) {
@@ -437,6 +502,8 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
         // index of next thread local system in thread_local_system_indices. (always incremented by one
         // when prepare_to_next_thread_local is called. (We prepared up to index 0 above)
         let mut next_thread_local_index = 0;
+        // the index of the first system whose commands have not been applied yet
+        let mut unflushed_start = 0;
 
         {
             // Prepare all system up to and including the first thread local system. This will return
@@ -444,6 +511,7 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
             let prepared_system_range = self.prepare_to_next_thread_local(
                 world,
                 systems,
+                ordering_dependencies,
                 stage_changed,
                 next_thread_local_index,
             );
@@ -453,6 +521,7 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
                 world,
                 resources,
                 systems,
+                should_run,
                 prepared_system_range,
                 &*compute_pool,
             );
@@ -467,8 +536,18 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
             // Run the thread local system at the end of the range of systems we just processed
             let thread_local_system_index =
                 self.thread_local_system_indices[next_thread_local_index];
-            {
+            if should_run.contains(thread_local_system_index) {
                 // if a thread local system is ready to run, run it exclusively on the main thread
+                if systems[thread_local_system_index].is_command_flush() {
+                    flush_commands(
+                        systems,
+                        unflushed_start..thread_local_system_index,
+                        should_run,
+                        world,
+                        resources,
+                    );
+                    unflushed_start = thread_local_system_index + 1;
+                }
                 let system = systems[thread_local_system_index].as_mut();
 
                 #[cfg(feature = "trace")]
@@ -491,6 +570,7 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
             let run_ready_system_index_range = self.prepare_to_next_thread_local(
                 world,
                 systems,
+                ordering_dependencies,
                 stage_changed,
                 next_thread_local_index,
             );
@@ -499,25 +579,20 @@ impl SystemStageExecutor for ParallelSystemStageExecutor {
                 world,
                 resources,
                 systems,
+                should_run,
                 run_ready_system_index_range,
                 &*compute_pool,
             );
         }
 
         // "flush"
-        for system in systems.iter_mut() {
-            match system.thread_local_execution() {
-                ThreadLocalExecution::NextFlush => {
-                    #[cfg(feature = "trace")]
-                    let system_span =
-                        bevy_utils::tracing::info_span!("system", name = system.name().as_ref());
-                    #[cfg(feature = "trace")]
-                    let _system_guard = system_span.enter();
-                    system.run_thread_local(world, resources);
-                }
-                ThreadLocalExecution::Immediate => { /* already ran */ }
-            }
-        }
+        flush_commands(
+            systems,
+            unflushed_start..systems.len(),
+            should_run,
+            world,
+            resources,
+        );
 
         // If world's archetypes_generation is the same as it was before running any systems then
         // we can assume that all systems have correct archetype accesses.
@@ -1,12 +1,17 @@
-use crate::{Resource, Resources, Stage, System, SystemStage, World};
+use crate::{
+    DebugGraph, Entity, IntoSystem, Res, Resource, Resources, ShouldRun, Stage, System,
+    SystemAmbiguity, SystemDescriptor, SystemStage, Without, World,
+};
 use bevy_utils::HashMap;
-use std::{mem::Discriminant, ops::Deref};
+use std::{marker::PhantomData, mem::Discriminant, ops::Deref};
 use thiserror::Error;
 
 pub(crate) struct StateStages {
     update: Box<dyn Stage>,
     enter: Box<dyn Stage>,
     exit: Box<dyn Stage>,
+    pause: Box<dyn Stage>,
+    resume: Box<dyn Stage>,
 }
 
 impl Default for StateStages {
@@ -15,10 +20,28 @@ impl Default for StateStages {
             enter: Box::new(SystemStage::parallel()),
             update: Box::new(SystemStage::parallel()),
             exit: Box::new(SystemStage::parallel()),
+            pause: Box::new(SystemStage::parallel()),
+            resume: Box::new(SystemStage::parallel()),
         }
     }
 }
 
+/// Marks an entity as belonging to a state. The entity is despawned when that state exits, either because
+/// [State::set_next] replaced it or because [State::pop] removed it. Pausing the state with [State::push]
+/// keeps the entity alive.
+///
+/// Only the marked entity is despawned, not its children. If the same state is paused lower in the stack, its
+/// entities are kept until that paused state exits too.
+#[derive(Debug, Clone)]
+pub struct StateScoped<T>(pub T);
+
+/// The depth in the [State] stack of the state a [StateScoped] entity belongs to, recorded by the [StateStage]
+/// before it applies a state change
+struct StateScopeDepth<T> {
+    depth: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
 pub struct StateStage<T> {
     stages: HashMap<Discriminant<T>, StateStages>,
 }
@@ -48,6 +71,16 @@ impl<T> StateStage<T> {
         self
     }
 
+    pub fn with_pause_stage<S: Stage>(mut self, state: T, stage: S) -> Self {
+        self.set_pause_stage(state, stage);
+        self
+    }
+
+    pub fn with_resume_stage<S: Stage>(mut self, state: T, stage: S) -> Self {
+        self.set_resume_stage(state, stage);
+        self
+    }
+
     pub fn set_enter_stage<S: Stage>(&mut self, state: T, stage: S) -> &mut Self {
         let stages = self.state_stages(state);
         stages.enter = Box::new(stage);
@@ -66,36 +99,51 @@ impl<T> StateStage<T> {
         self
     }
 
-    pub fn on_state_enter<S: System<In = (), Out = ()>>(
-        &mut self,
-        state: T,
-        system: S,
-    ) -> &mut Self {
+    pub fn set_pause_stage<S: Stage>(&mut self, state: T, stage: S) -> &mut Self {
+        let stages = self.state_stages(state);
+        stages.pause = Box::new(stage);
+        self
+    }
+
+    pub fn set_resume_stage<S: Stage>(&mut self, state: T, stage: S) -> &mut Self {
+        let stages = self.state_stages(state);
+        stages.resume = Box::new(stage);
+        self
+    }
+
+    pub fn on_state_enter<S: Into<SystemDescriptor>>(&mut self, state: T, system: S) -> &mut Self {
         self.enter_stage(state, |system_stage: &mut SystemStage| {
             system_stage.add_system(system)
         })
     }
 
-    pub fn on_state_exit<S: System<In = (), Out = ()>>(
-        &mut self,
-        state: T,
-        system: S,
-    ) -> &mut Self {
+    pub fn on_state_exit<S: Into<SystemDescriptor>>(&mut self, state: T, system: S) -> &mut Self {
         self.exit_stage(state, |system_stage: &mut SystemStage| {
             system_stage.add_system(system)
         })
     }
 
-    pub fn on_state_update<S: System<In = (), Out = ()>>(
-        &mut self,
-        state: T,
-        system: S,
-    ) -> &mut Self {
+    pub fn on_state_update<S: Into<SystemDescriptor>>(&mut self, state: T, system: S) -> &mut Self {
         self.update_stage(state, |system_stage: &mut SystemStage| {
             system_stage.add_system(system)
         })
     }
 
+    /// Adds a system that runs when `state` is covered by a state pushed with [State::push]
+    pub fn on_state_pause<S: Into<SystemDescriptor>>(&mut self, state: T, system: S) -> &mut Self {
+        self.pause_stage(state, |system_stage: &mut SystemStage| {
+            system_stage.add_system(system)
+        })
+    }
+
+    /// Adds a system that runs when `state` becomes the current state again because the state above it was
+    /// popped with [State::pop]
+    pub fn on_state_resume<S: Into<SystemDescriptor>>(&mut self, state: T, system: S) -> &mut Self {
+        self.resume_stage(state, |system_stage: &mut SystemStage| {
+            system_stage.add_system(system)
+        })
+    }
+
     pub fn enter_stage<S: Stage, F: FnOnce(&mut S) -> &mut S>(
         &mut self,
         state: T,
@@ -141,6 +189,36 @@ impl<T> StateStage<T> {
         self
     }
 
+    pub fn pause_stage<S: Stage, F: FnOnce(&mut S) -> &mut S>(
+        &mut self,
+        state: T,
+        func: F,
+    ) -> &mut Self {
+        let stages = self.state_stages(state);
+        func(
+            stages
+                .pause
+                .downcast_mut()
+                .expect("'Pause' stage does not match the given type"),
+        );
+        self
+    }
+
+    pub fn resume_stage<S: Stage, F: FnOnce(&mut S) -> &mut S>(
+        &mut self,
+        state: T,
+        func: F,
+    ) -> &mut Self {
+        let stages = self.state_stages(state);
+        func(
+            stages
+                .resume
+                .downcast_mut()
+                .expect("'Resume' stage does not match the given type"),
+        );
+        self
+    }
+
     fn state_stages(&mut self, state: T) -> &mut StateStages {
         self.stages
             .entry(std::mem::discriminant(&state))
@@ -148,6 +226,69 @@ impl<T> StateStage<T> {
     }
 }
 
+#[allow(clippy::mem_discriminant_non_enum)]
+impl<T: Resource + Clone> StateStage<T> {
+    /// Records the stack depth of the [StateScoped] entities spawned since the last state change, so that exiting a
+    /// state doesn't despawn the entities of a paused state with the same discriminant
+    fn scope_new_entities(&mut self, world: &mut World, resources: &mut Resources) {
+        let state = resources.get::<State<T>>().expect("Missing state resource");
+        let depth_of = |scoped: &T| {
+            let discriminant = std::mem::discriminant(scoped);
+            if std::mem::discriminant(&state.current) == discriminant {
+                Some(state.stack.len())
+            } else {
+                state
+                    .stack
+                    .iter()
+                    .rposition(|paused| std::mem::discriminant(paused) == discriminant)
+            }
+        };
+        let new_entities = world
+            .query_filtered::<(Entity, &StateScoped<T>), Without<StateScopeDepth<T>>>()
+            .filter_map(|(entity, scoped)| Some((entity, depth_of(&scoped.0)?)))
+            .collect::<Vec<_>>();
+        drop(state);
+
+        world.with_resources(resources, |world| {
+            for (entity, depth) in new_entities {
+                let depth = StateScopeDepth::<T> {
+                    depth,
+                    marker: PhantomData,
+                };
+                world.insert_one(entity, depth).unwrap();
+            }
+        });
+    }
+
+    /// Exits the state at `depth` in the stack, and despawns its [StateScoped] entities
+    fn run_exit(
+        &mut self,
+        state: Discriminant<T>,
+        depth: usize,
+        world: &mut World,
+        resources: &mut Resources,
+    ) {
+        if let Some(state_stages) = self.stages.get_mut(&state) {
+            state_stages.exit.run(world, resources);
+        }
+
+        let scoped_entities = world
+            .query::<(Entity, &StateScoped<T>, Option<&StateScopeDepth<T>>)>()
+            .filter(|(_, scoped, scope_depth)| {
+                std::mem::discriminant(&scoped.0) == state
+                    && !matches!(scope_depth, Some(scope_depth) if scope_depth.depth != depth)
+            })
+            .map(|(entity, _, _)| entity)
+            .collect::<Vec<_>>();
+        world.with_resources(resources, |world| {
+            for entity in scoped_entities {
+                // the exit stage may have despawned it already
+                let _ = world.despawn(entity);
+            }
+        });
+    }
+}
+
 #[allow(clippy::mem_discriminant_non_enum)]
 impl<T: Resource + Clone> Stage for StateStage<T> {
     fn initialize(&mut self, world: &mut World, resources: &mut Resources) {
@@ -155,59 +296,147 @@ impl<T: Resource + Clone> Stage for StateStage<T> {
             state_stages.enter.initialize(world, resources);
             state_stages.update.initialize(world, resources);
             state_stages.exit.initialize(world, resources);
+            state_stages.pause.initialize(world, resources);
+            state_stages.resume.initialize(world, resources);
         }
     }
 
     fn run(&mut self, world: &mut World, resources: &mut Resources) {
-        let current_stage = loop {
-            let (next_stage, current_stage) = {
+        loop {
+            self.scope_new_entities(world, resources);
+            let (transition, depth) = {
                 let mut state = resources
                     .get_mut::<State<T>>()
                     .expect("Missing state resource");
-                let result = (
-                    state.next.as_ref().map(|next| std::mem::discriminant(next)),
-                    std::mem::discriminant(&state.current),
-                );
-
-                state.apply_next();
-
-                result
+                (state.apply_next(), state.stack.len())
             };
 
-            // if next_stage is Some, we just applied a new state
-            if let Some(next_stage) = next_stage {
-                if next_stage != current_stage {
-                    if let Some(current_state_stages) = self.stages.get_mut(&current_stage) {
-                        current_state_stages.exit.run(world, resources);
+            match transition {
+                Some(StateTransition::Set { from, to }) => {
+                    if from != to {
+                        self.run_exit(from, depth, world, resources);
+                    }
+                    if let Some(state_stages) = self.stages.get_mut(&to) {
+                        state_stages.enter.run(world, resources);
                     }
                 }
-
-                if let Some(next_state_stages) = self.stages.get_mut(&next_stage) {
-                    next_state_stages.enter.run(world, resources);
+                Some(StateTransition::Push { from, to }) => {
+                    if let Some(state_stages) = self.stages.get_mut(&from) {
+                        state_stages.pause.run(world, resources);
+                    }
+                    if let Some(state_stages) = self.stages.get_mut(&to) {
+                        state_stages.enter.run(world, resources);
+                    }
                 }
-            } else {
-                break current_stage;
+                Some(StateTransition::Pop { from, to }) => {
+                    // the popped state was one above the state it resumes
+                    self.run_exit(from, depth + 1, world, resources);
+                    if let Some(state_stages) = self.stages.get_mut(&to) {
+                        state_stages.resume.run(world, resources);
+                    }
+                }
+                None => break,
             }
-        };
+        }
 
+        let current_stage = std::mem::discriminant(
+            &resources
+                .get::<State<T>>()
+                .expect("Missing state resource")
+                .current,
+        );
         if let Some(current_state_stages) = self.stages.get_mut(&current_stage) {
             current_state_stages.update.run(world, resources);
         }
     }
+
+    fn debug_graph(&self, name: &str) -> DebugGraph {
+        let mut graph = DebugGraph::new(name);
+        for (discriminant, state_stages) in self.stages.iter() {
+            let state_name = format!("{}/{:?}", name, discriminant);
+            let mut state_graph = DebugGraph::new(state_name.clone());
+            state_graph.subgraphs.extend(vec![
+                state_stages
+                    .enter
+                    .debug_graph(&format!("{}/enter", state_name)),
+                state_stages
+                    .update
+                    .debug_graph(&format!("{}/update", state_name)),
+                state_stages
+                    .exit
+                    .debug_graph(&format!("{}/exit", state_name)),
+                state_stages
+                    .pause
+                    .debug_graph(&format!("{}/pause", state_name)),
+                state_stages
+                    .resume
+                    .debug_graph(&format!("{}/resume", state_name)),
+            ]);
+            graph.subgraphs.push(state_graph);
+        }
+        graph
+    }
+
+    fn ambiguities(&self, world: &World, resources: &Resources) -> Vec<SystemAmbiguity> {
+        self.stages
+            .values()
+            .flat_map(|state_stages| {
+                let mut ambiguities = state_stages.enter.ambiguities(world, resources);
+                ambiguities.extend(state_stages.update.ambiguities(world, resources));
+                ambiguities.extend(state_stages.exit.ambiguities(world, resources));
+                ambiguities.extend(state_stages.pause.ambiguities(world, resources));
+                ambiguities.extend(state_stages.resume.ambiguities(world, resources));
+                ambiguities
+            })
+            .collect()
+    }
 }
+
 #[derive(Debug, Error)]
 pub enum StateError {
     #[error("Attempted to change the state to the current state.")]
     AlreadyInState,
     #[error("Attempted to queue a state change, but there was already a state queued.")]
     StateAlreadyQueued,
+    #[error("Attempted to pop the current state, but there is no paused state below it.")]
+    StackEmpty,
+}
+
+#[derive(Debug)]
+enum StateOperation<T> {
+    Set(T),
+    Push(T),
+    Pop,
+}
+
+/// A change of the current state, as applied by [State::apply_next]
+enum StateTransition<T> {
+    Set {
+        from: Discriminant<T>,
+        to: Discriminant<T>,
+    },
+    Push {
+        from: Discriminant<T>,
+        to: Discriminant<T>,
+    },
+    Pop {
+        from: Discriminant<T>,
+        to: Discriminant<T>,
+    },
 }
 
+/// The current state of a [StateStage], along with the states it paused.
+///
+/// [State::set_next] replaces the current state. [State::push] pauses the current state and enters a new one on
+/// top of it, and [State::pop] exits the current state and resumes the paused state below it. Changes are queued
+/// and applied the next time the [StateStage] runs.
 #[derive(Debug)]
 pub struct State<T: Clone> {
     previous: Option<T>,
     current: T,
-    next: Option<T>,
+    /// paused states, from the bottom of the stack to the top
+    stack: Vec<T>,
+    operation: Option<StateOperation<T>>,
 }
 
 #[allow(clippy::mem_discriminant_non_enum)]
@@ -216,8 +445,9 @@ impl<T: Clone> State<T> {
         Self {
             current: state.clone(),
             previous: None,
+            stack: Vec::new(),
             // add value to queue so that we "enter" the state
-            next: Some(state),
+            operation: Some(StateOperation::Set(state)),
         }
     }
 
@@ -229,8 +459,18 @@ impl<T: Clone> State<T> {
         self.previous.as_ref()
     }
 
+    /// The state that will be current once the queued change is applied, if any
     pub fn next(&self) -> Option<&T> {
-        self.next.as_ref()
+        match &self.operation {
+            Some(StateOperation::Set(state)) | Some(StateOperation::Push(state)) => Some(state),
+            Some(StateOperation::Pop) => self.stack.last(),
+            None => None,
+        }
+    }
+
+    /// The states paused by [State::push], from the bottom of the stack to the top
+    pub fn paused(&self) -> &[T] {
+        &self.stack
     }
 
     /// Queue a state change. This will fail if there is already a state in the queue, or if the given `state` matches the current state
@@ -239,11 +479,11 @@ impl<T: Clone> State<T> {
             return Err(StateError::AlreadyInState);
         }
 
-        if self.next.is_some() {
+        if self.operation.is_some() {
             return Err(StateError::StateAlreadyQueued);
         }
 
-        self.next = Some(state);
+        self.operation = Some(StateOperation::Set(state));
         Ok(())
     }
 
@@ -253,17 +493,89 @@ impl<T: Clone> State<T> {
             return Err(StateError::AlreadyInState);
         }
 
-        self.next = Some(state);
+        self.operation = Some(StateOperation::Set(state));
         Ok(())
     }
 
-    fn apply_next(&mut self) {
-        if let Some(next) = self.next.take() {
-            let previous = std::mem::replace(&mut self.current, next);
-            if std::mem::discriminant(&previous) != std::mem::discriminant(&self.current) {
-                self.previous = Some(previous)
-            }
+    /// Queue pausing the current state and entering `state` on top of it. This will fail if there is already a
+    /// state change in the queue, or if the given `state` matches the current state
+    pub fn push(&mut self, state: T) -> Result<(), StateError> {
+        if std::mem::discriminant(&self.current) == std::mem::discriminant(&state) {
+            return Err(StateError::AlreadyInState);
+        }
+
+        if self.operation.is_some() {
+            return Err(StateError::StateAlreadyQueued);
+        }
+
+        self.operation = Some(StateOperation::Push(state));
+        Ok(())
+    }
+
+    /// Queue exiting the current state and resuming the state it paused. This will fail if there is already a
+    /// state change in the queue, or if no state is paused
+    pub fn pop(&mut self) -> Result<(), StateError> {
+        if self.stack.is_empty() {
+            return Err(StateError::StackEmpty);
+        }
+
+        if self.operation.is_some() {
+            return Err(StateError::StateAlreadyQueued);
         }
+
+        self.operation = Some(StateOperation::Pop);
+        Ok(())
+    }
+
+    fn apply_next(&mut self) -> Option<StateTransition<T>> {
+        let from = std::mem::discriminant(&self.current);
+        let transition = match self.operation.take()? {
+            StateOperation::Set(next) => {
+                let previous = std::mem::replace(&mut self.current, next);
+                if std::mem::discriminant(&previous) != std::mem::discriminant(&self.current) {
+                    self.previous = Some(previous)
+                }
+                StateTransition::Set {
+                    from,
+                    to: std::mem::discriminant(&self.current),
+                }
+            }
+            StateOperation::Push(next) => {
+                let previous = std::mem::replace(&mut self.current, next);
+                self.stack.push(previous.clone());
+                self.previous = Some(previous);
+                StateTransition::Push {
+                    from,
+                    to: std::mem::discriminant(&self.current),
+                }
+            }
+            StateOperation::Pop => {
+                let resumed = self.stack.pop()?;
+                self.previous = Some(std::mem::replace(&mut self.current, resumed));
+                StateTransition::Pop {
+                    from,
+                    to: std::mem::discriminant(&self.current),
+                }
+            }
+        };
+        Some(transition)
+    }
+}
+
+#[allow(clippy::mem_discriminant_non_enum)]
+impl<T: Resource + Clone> State<T> {
+    /// Run criteria that runs a [SystemSet](crate::SystemSet) while the current state matches `state`.
+    /// This allows gating systems on a state without adding a [StateStage].
+    pub fn on_update(state: T) -> impl System<In = (), Out = ShouldRun> {
+        let discriminant = std::mem::discriminant(&state);
+        (move |current: Res<State<T>>| {
+            if std::mem::discriminant(&current.current) == discriminant {
+                ShouldRun::Yes
+            } else {
+                ShouldRun::No
+            }
+        })
+        .system()
     }
 }
 
@@ -0,0 +1,121 @@
+use crate::{BoxedSystem, BoxedSystemLabel, System, SystemLabel};
+
+/// A system paired with the labels and ordering constraints it should be scheduled with.
+/// Systems convert into descriptors implicitly, so anything that accepts a descriptor also accepts a plain system.
+pub struct SystemDescriptor {
+    pub(crate) system: BoxedSystem,
+    pub(crate) ordering: SystemOrdering,
+}
+
+/// The labels of a system and the labels it must run before / after within its stage
+#[derive(Default, Clone)]
+pub struct SystemOrdering {
+    pub labels: Vec<BoxedSystemLabel>,
+    pub before: Vec<BoxedSystemLabel>,
+    pub after: Vec<BoxedSystemLabel>,
+    pub position: StagePosition,
+}
+
+/// Where a system runs within its stage, on top of its ordering constraints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StagePosition {
+    /// The system runs before every system that is not at the start of the stage
+    AtStart,
+    /// The system runs where its labels place it
+    Ordered,
+    /// The system runs after every system that is not at the end of the stage. Commands are still applied after
+    /// it, unless a [CommandFlush](crate::CommandFlush) is placed at the end of the stage before it.
+    AtEnd,
+}
+
+impl Default for StagePosition {
+    fn default() -> Self {
+        StagePosition::Ordered
+    }
+}
+
+impl SystemDescriptor {
+    pub fn new(system: BoxedSystem) -> Self {
+        Self {
+            system,
+            ordering: Default::default(),
+        }
+    }
+
+    pub fn ordering(&self) -> &SystemOrdering {
+        &self.ordering
+    }
+}
+
+impl<S: System<In = (), Out = ()>> From<S> for SystemDescriptor {
+    fn from(system: S) -> Self {
+        SystemDescriptor::new(Box::new(system))
+    }
+}
+
+/// Adds labels and ordering constraints to systems and [SystemDescriptor]s
+pub trait SystemDescriptorCoercion {
+    /// Assigns a label to the system. Several systems may share the same label.
+    fn label(self, label: impl SystemLabel) -> SystemDescriptor;
+
+    /// The system will run before every system with the given label in the same stage
+    fn before(self, label: impl SystemLabel) -> SystemDescriptor;
+
+    /// The system will run after every system with the given label in the same stage
+    fn after(self, label: impl SystemLabel) -> SystemDescriptor;
+
+    /// The system will run before the other systems of its stage, see [StagePosition::AtStart]
+    fn at_start(self) -> SystemDescriptor;
+
+    /// The system will run after the other systems of its stage, see [StagePosition::AtEnd]
+    fn at_end(self) -> SystemDescriptor;
+}
+
+impl SystemDescriptorCoercion for SystemDescriptor {
+    fn label(mut self, label: impl SystemLabel) -> SystemDescriptor {
+        self.ordering.labels.push(Box::new(label));
+        self
+    }
+
+    fn before(mut self, label: impl SystemLabel) -> SystemDescriptor {
+        self.ordering.before.push(Box::new(label));
+        self
+    }
+
+    fn after(mut self, label: impl SystemLabel) -> SystemDescriptor {
+        self.ordering.after.push(Box::new(label));
+        self
+    }
+
+    fn at_start(mut self) -> SystemDescriptor {
+        self.ordering.position = StagePosition::AtStart;
+        self
+    }
+
+    fn at_end(mut self) -> SystemDescriptor {
+        self.ordering.position = StagePosition::AtEnd;
+        self
+    }
+}
+
+impl<S: System<In = (), Out = ()>> SystemDescriptorCoercion for S {
+    fn label(self, label: impl SystemLabel) -> SystemDescriptor {
+        SystemDescriptor::from(self).label(label)
+    }
+
+    fn before(self, label: impl SystemLabel) -> SystemDescriptor {
+        SystemDescriptor::from(self).before(label)
+    }
+
+    fn after(self, label: impl SystemLabel) -> SystemDescriptor {
+        SystemDescriptor::from(self).after(label)
+    }
+
+    fn at_start(self) -> SystemDescriptor {
+        SystemDescriptor::from(self).at_start()
+    }
+
+    fn at_end(self) -> SystemDescriptor {
+        SystemDescriptor::from(self).at_end()
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{BoxedSystem, BoxedSystemLabel, ShouldRun, System, SystemDescriptor, SystemLabel};
+
+/// A group of systems that is added to a [SystemStage](crate::SystemStage) as one unit.
+///
+/// The set's run criteria (if any) is evaluated once per stage run and decides whether every system in the
+/// set runs. Labels and ordering constraints given to the set apply to each of its systems.
+#[derive(Default)]
+pub struct SystemSet {
+    run_criteria: Option<BoxedSystem<(), ShouldRun>>,
+    descriptors: Vec<SystemDescriptor>,
+    labels: Vec<BoxedSystemLabel>,
+    before: Vec<BoxedSystemLabel>,
+    after: Vec<BoxedSystemLabel>,
+}
+
+impl SystemSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_run_criteria<S: System<In = (), Out = ShouldRun>>(mut self, system: S) -> Self {
+        self.run_criteria = Some(Box::new(system));
+        self
+    }
+
+    pub fn with_system<S: Into<SystemDescriptor>>(mut self, system: S) -> Self {
+        self.descriptors.push(system.into());
+        self
+    }
+
+    /// Assigns a label to every system in the set
+    pub fn label(mut self, label: impl SystemLabel) -> Self {
+        self.labels.push(Box::new(label));
+        self
+    }
+
+    /// Every system in the set will run before every system with the given label
+    pub fn before(mut self, label: impl SystemLabel) -> Self {
+        self.before.push(Box::new(label));
+        self
+    }
+
+    /// Every system in the set will run after every system with the given label
+    pub fn after(mut self, label: impl SystemLabel) -> Self {
+        self.after.push(Box::new(label));
+        self
+    }
+
+    pub(crate) fn bake(self) -> (Option<BoxedSystem<(), ShouldRun>>, Vec<SystemDescriptor>) {
+        let SystemSet {
+            run_criteria,
+            mut descriptors,
+            labels,
+            before,
+            after,
+        } = self;
+        for descriptor in descriptors.iter_mut() {
+            descriptor.ordering.labels.extend(labels.iter().cloned());
+            descriptor.ordering.before.extend(before.iter().cloned());
+            descriptor.ordering.after.extend(after.iter().cloned());
+        }
+        (run_criteria, descriptors)
+    }
+}
@@ -1,7 +1,8 @@
-use super::SystemId;
+use super::{SystemId, SystemRegistry};
 use crate::{
     resource::{Resource, Resources},
-    Bundle, Component, ComponentError, DynamicBundle, Entity, EntityReserver, World,
+    BoxedSystemLabel, Bundle, Component, ComponentError, DynamicBundle, Entity, EntityReserver,
+    Relation, SystemLabel, World,
 };
 use bevy_utils::tracing::{debug, warn};
 use std::marker::PhantomData;
@@ -23,8 +24,8 @@ impl<T> Command for Spawn<T>
 where
     T: DynamicBundle + Send + Sync + 'static,
 {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        world.spawn(self.bundle);
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| world.spawn(self.bundle));
     }
 }
 
@@ -41,8 +42,10 @@ where
     I: IntoIterator + Send + Sync,
     I::Item: Bundle,
 {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        world.spawn_batch(self.bundles_iter);
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| {
+            world.spawn_batch(self.bundles_iter);
+        });
     }
 }
 
@@ -52,8 +55,8 @@ pub(crate) struct Despawn {
 }
 
 impl Command for Despawn {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        if let Err(e) = world.despawn(self.entity) {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        if let Err(e) = world.with_resources(resources, |world| world.despawn(self.entity)) {
             debug!("Failed to despawn entity {:?}: {}", self.entity, e);
         }
     }
@@ -71,8 +74,10 @@ impl<T> Command for Insert<T>
 where
     T: DynamicBundle + Send + Sync + 'static,
 {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        world.insert(self.entity, self.bundle).unwrap();
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world
+            .with_resources(resources, |world| world.insert(self.entity, self.bundle))
+            .unwrap();
     }
 }
 
@@ -89,8 +94,12 @@ impl<T> Command for InsertOne<T>
 where
     T: Component,
 {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        world.insert(self.entity, (self.component,)).unwrap();
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world
+            .with_resources(resources, |world| {
+                world.insert(self.entity, (self.component,))
+            })
+            .unwrap();
     }
 }
 
@@ -107,9 +116,11 @@ impl<T> Command for RemoveOne<T>
 where
     T: Component,
 {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
         if world.get::<T>(self.entity).is_ok() {
-            world.remove_one::<T>(self.entity).unwrap();
+            world
+                .with_resources(resources, |world| world.remove_one::<T>(self.entity))
+                .unwrap();
         }
     }
 }
@@ -127,16 +138,25 @@ impl<T> Command for Remove<T>
 where
     T: Bundle + Send + Sync + 'static,
 {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        match world.remove::<T>(self.entity) {
-            Ok(_) => (),
-            Err(ComponentError::MissingComponent(e)) => {
-                warn!(
-                    "Failed to remove components {:?} with error: {}. Falling back to inefficient one-by-one component removing.",
-                    std::any::type_name::<T>(),
-                    e
-                );
-                if let Err(e) = world.remove_one_by_one::<T>(self.entity) {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| {
+            match world.remove::<T>(self.entity) {
+                Ok(_) => (),
+                Err(ComponentError::MissingComponent(e)) => {
+                    warn!(
+                        "Failed to remove components {:?} with error: {}. Falling back to inefficient one-by-one component removing.",
+                        std::any::type_name::<T>(),
+                        e
+                    );
+                    if let Err(e) = world.remove_one_by_one::<T>(self.entity) {
+                        debug!(
+                            "Failed to remove components {:?} with error: {}",
+                            std::any::type_name::<T>(),
+                            e
+                        );
+                    }
+                }
+                Err(e) => {
                     debug!(
                         "Failed to remove components {:?} with error: {}",
                         std::any::type_name::<T>(),
@@ -144,14 +164,7 @@ where
                     );
                 }
             }
-            Err(e) => {
-                debug!(
-                    "Failed to remove components {:?} with error: {}",
-                    std::any::type_name::<T>(),
-                    e
-                );
-            }
-        }
+        });
     }
 }
 
@@ -177,6 +190,73 @@ impl<T: Resource> Command for InsertLocalResource<T> {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct Relate<R: Relation> {
+    source: Entity,
+    target: Entity,
+    phantom: PhantomData<R>,
+}
+
+impl<R: Relation> Command for Relate<R> {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| {
+            if let Err(e) = world.relate::<R>(self.source, self.target) {
+                debug!(
+                    "Failed to relate entity {:?} to {:?} with {}: {}",
+                    self.source,
+                    self.target,
+                    std::any::type_name::<R>(),
+                    e
+                );
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Unrelate<R: Relation> {
+    source: Entity,
+    target: Option<Entity>,
+    phantom: PhantomData<R>,
+}
+
+impl<R: Relation> Command for Unrelate<R> {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| match self.target {
+            Some(target) => {
+                world.unrelate::<R>(self.source, target);
+            }
+            None => world.unrelate_all::<R>(self.source),
+        });
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RunSystem {
+    system_id: SystemId,
+}
+
+impl Command for RunSystem {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        if let Err(e) = SystemRegistry::run(self.system_id, world, resources) {
+            warn!("Failed to run system {:?}: {}", self.system_id, e);
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct RunSystemByLabel {
+    label: BoxedSystemLabel,
+}
+
+impl Command for RunSystemByLabel {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        if let Err(e) = SystemRegistry::run_by_boxed_label(self.label, world, resources) {
+            warn!("Failed to run a registered system: {}", e);
+        }
+    }
+}
+
 /// A list of commands that will be run to populate a `World` and `Resources`.
 #[derive(Default)]
 pub struct Commands {
@@ -280,6 +360,47 @@ impl Commands {
         })
     }
 
+    /// See [`World::relate`].
+    pub fn relate<R: Relation>(&mut self, source: Entity, target: Entity) -> &mut Self {
+        self.add_command(Relate::<R> {
+            source,
+            target,
+            phantom: PhantomData,
+        })
+    }
+
+    /// See [`World::unrelate`].
+    pub fn unrelate<R: Relation>(&mut self, source: Entity, target: Entity) -> &mut Self {
+        self.add_command(Unrelate::<R> {
+            source,
+            target: Some(target),
+            phantom: PhantomData,
+        })
+    }
+
+    /// See [`World::unrelate_all`].
+    pub fn unrelate_all<R: Relation>(&mut self, source: Entity) -> &mut Self {
+        self.add_command(Unrelate::<R> {
+            source,
+            target: None,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Runs a system registered in the [SystemRegistry] resource when commands are applied.
+    ///
+    /// See [`SystemRegistry::run`].
+    pub fn run_system(&mut self, system_id: SystemId) -> &mut Self {
+        self.add_command(RunSystem { system_id })
+    }
+
+    /// Same as [`Self::run_system`], but looks the system up by one of its labels.
+    pub fn run_system_by_label(&mut self, label: impl SystemLabel) -> &mut Self {
+        self.add_command(RunSystemByLabel {
+            label: Box::new(label),
+        })
+    }
+
     /// See [`World::remove_one`].
     pub fn remove_one<T>(&mut self, entity: Entity) -> &mut Self
     where
@@ -372,6 +493,8 @@ impl Commands {
     }
 
     /// Runs all the stored commands on `world` and `resources`. The command buffer is emptied as a part of this call.
+    /// The commands that change entities lend `resources` to the component hooks they trigger, see
+    /// [`World::with_resources`].
     pub fn apply(&mut self, world: &mut World, resources: &mut Resources) {
         for command in self.commands.drain(..) {
             command.write(world, resources);
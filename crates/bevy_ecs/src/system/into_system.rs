@@ -336,7 +336,8 @@ mod tests {
         clear_trackers_system,
         resource::{Res, ResMut, Resources},
         schedule::Schedule,
-        ChangedRes, Entity, Local, Or, Query, QuerySet, System, SystemStage, With, World,
+        AddedRes, ChangedRes, Entity, Local, Or, Query, QueryError, QuerySet, System, SystemStage,
+        With, World,
     };
 
     #[derive(Debug, Eq, PartialEq, Default)]
@@ -404,6 +405,72 @@ mod tests {
         assert!(*resources.get::<bool>().unwrap(), "system ran");
     }
 
+    #[test]
+    fn query_get_many_and_join() {
+        struct Target(Entity);
+
+        fn query_system(
+            mut ran: ResMut<bool>,
+            entities: Res<Vec<Entity>>,
+            mut a_query: Query<&mut A>,
+            mut b_query: Query<&mut B>,
+            target_query: Query<&Target>,
+            c_query: Query<&C>,
+            d_query: Query<&D>,
+        ) {
+            let results = c_query.get_many(&[entities[2], entities[0]]);
+            assert!(results[0].is_ok());
+            assert!(matches!(results[1], Err(QueryError::NoSuchEntity)));
+
+            let results = a_query.get_many_mut(&[entities[2], entities[0], entities[3]]);
+            assert!(results[0].is_ok());
+            assert!(results[1].is_ok());
+            assert!(matches!(results[2], Err(QueryError::NoSuchEntity)));
+
+            let results = b_query.get_many_mut(&[entities[1], entities[1]]);
+            assert!(results[0].is_ok());
+            assert!(matches!(results[1], Err(QueryError::DuplicateEntity)));
+
+            assert_eq!(target_query.join(&d_query).count(), 1);
+            assert_eq!(a_query.join_mut(&mut b_query).count(), 1);
+
+            let joined = target_query
+                .join_by(&c_query, |target| Some(target.0))
+                .count();
+            assert_eq!(joined, 1);
+
+            *ran = true;
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(false);
+        let a = world.spawn((A,));
+        let a_b = world.spawn((A, B));
+        let a_c = world.spawn((A, C));
+        let d = world.spawn((D, Target(a_c)));
+        world.spawn((Target(a_b),));
+        resources.insert(vec![a, a_b, a_c, d]);
+
+        run_system(&mut world, &mut resources, query_system.system());
+
+        assert!(*resources.get::<bool>().unwrap(), "system ran");
+    }
+
+    #[test]
+    #[should_panic]
+    fn conflicting_query_join_system() {
+        fn sys(mut q1: Query<&mut A>, mut q2: Query<(&B, &mut A)>) {
+            for _ in q1.join_mut(&mut q2) {}
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        world.spawn((A, B));
+
+        run_system(&mut world, &mut resources, sys.system());
+    }
+
     #[test]
     fn or_query_set_system() {
         // Regression test for issue #762
@@ -514,6 +581,46 @@ mod tests {
         assert_eq!(*(world.get::<i32>(ent).unwrap()), 3);
     }
 
+    #[test]
+    fn optional_resource_system() {
+        fn count_system(
+            score: Option<ResMut<u32>>,
+            name: Option<AddedRes<&'static str>>,
+            mut counts: ResMut<Vec<(bool, bool)>>,
+        ) {
+            counts.push((score.is_some(), name.is_some()));
+            if let Some(mut score) = score {
+                *score += 1;
+            }
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(Vec::<(bool, bool)>::new());
+
+        let mut schedule = Schedule::default();
+        let mut update = SystemStage::parallel();
+        update.add_system(count_system.system());
+        schedule.add_stage("update", update);
+        schedule.add_stage(
+            "clear_trackers",
+            SystemStage::single(clear_trackers_system.system()),
+        );
+
+        schedule.initialize_and_run(&mut world, &mut resources);
+        resources.insert(0u32);
+        resources.insert("name");
+        schedule.initialize_and_run(&mut world, &mut resources);
+        schedule.initialize_and_run(&mut world, &mut resources);
+        assert_eq!(resources.remove::<u32>(), Some(2));
+        schedule.initialize_and_run(&mut world, &mut resources);
+
+        assert_eq!(
+            *resources.get::<Vec<(bool, bool)>>().unwrap(),
+            vec![(false, false), (true, true), (true, false), (false, false)]
+        );
+    }
+
     #[test]
     #[should_panic]
     fn conflicting_query_mut_system() {
@@ -6,6 +6,11 @@ use crate::{
 };
 use std::{any::TypeId, borrow::Cow};
 
+/// An exclusive system, created from a `FnMut(&mut World, &mut Resources)`. No other system runs while it runs.
+///
+/// It runs where its ordering constraints place it within its stage, and can be placed at the start or at the end of
+/// its stage with [at_start](crate::SystemDescriptorCoercion::at_start) and
+/// [at_end](crate::SystemDescriptorCoercion::at_end).
 pub struct ThreadLocalSystemFn {
     pub func: Box<dyn FnMut(&mut World, &mut Resources) + Send + Sync + 'static>,
     pub resource_access: TypeAccess<TypeId>,
@@ -70,3 +75,91 @@ where
         }
     }
 }
+
+/// A sync point that applies the buffered [Commands](crate::Commands) of the systems that ran before it in its
+/// stage, instead of at the end of the stage. Like exclusive systems, no other system runs while the commands are
+/// applied.
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// # use bevy_ecs::Stage;
+/// fn spawn(commands: &mut Commands) {
+///     commands.spawn((1u32,));
+/// }
+///
+/// fn count(query: Query<&u32>, mut count: ResMut<usize>) {
+///     *count = query.iter().count();
+/// }
+///
+/// let mut world = World::new();
+/// let mut resources = Resources::default();
+/// resources.insert(0usize);
+/// let mut stage = SystemStage::serial();
+/// stage
+///     .add_system(spawn.system().label("spawn"))
+///     .add_system(CommandFlush::default().label("flush").after("spawn"))
+///     .add_system(count.system().after("flush"));
+/// stage.initialize(&mut world, &mut resources);
+/// stage.run(&mut world, &mut resources);
+/// assert_eq!(*resources.get::<usize>().unwrap(), 1);
+/// ```
+pub struct CommandFlush {
+    id: SystemId,
+    resource_access: TypeAccess<TypeId>,
+    archetype_component_access: TypeAccess<ArchetypeComponent>,
+}
+
+impl Default for CommandFlush {
+    fn default() -> Self {
+        Self {
+            id: SystemId::new(),
+            resource_access: Default::default(),
+            archetype_component_access: Default::default(),
+        }
+    }
+}
+
+impl System for CommandFlush {
+    type In = ();
+    type Out = ();
+
+    fn name(&self) -> Cow<'static, str> {
+        Cow::Borrowed(std::any::type_name::<CommandFlush>())
+    }
+
+    fn id(&self) -> SystemId {
+        self.id
+    }
+
+    fn update(&mut self, _world: &World) {}
+
+    fn archetype_component_access(&self) -> &TypeAccess<ArchetypeComponent> {
+        &self.archetype_component_access
+    }
+
+    fn resource_access(&self) -> &TypeAccess<TypeId> {
+        &self.resource_access
+    }
+
+    fn thread_local_execution(&self) -> ThreadLocalExecution {
+        ThreadLocalExecution::Immediate
+    }
+
+    unsafe fn run_unsafe(
+        &mut self,
+        _input: (),
+        _world: &World,
+        _resources: &Resources,
+    ) -> Option<()> {
+        Some(())
+    }
+
+    // the executor applies the commands of the other systems when it reaches this one
+    fn run_thread_local(&mut self, _world: &mut World, _resources: &mut Resources) {}
+
+    fn initialize(&mut self, _world: &mut World, _resources: &mut Resources) {}
+
+    fn is_command_flush(&self) -> bool {
+        true
+    }
+}
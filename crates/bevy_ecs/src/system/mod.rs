@@ -6,6 +6,8 @@ mod query;
 mod system;
 mod system_chaining;
 mod system_param;
+mod system_piping;
+mod system_registry;
 
 pub use commands::*;
 pub use into_system::*;
@@ -14,3 +16,5 @@ pub use query::*;
 pub use system::*;
 pub use system_chaining::*;
 pub use system_param::*;
+pub use system_piping::*;
+pub use system_registry::*;
@@ -5,7 +5,8 @@ use crate::{
     ArchetypeComponent, Batch, BatchedIter, Component, ComponentError, Entity, Fetch, Mut,
     QueryFilter, QueryIter, ReadOnlyFetch, TypeAccess, World, WorldQuery,
 };
-use bevy_tasks::ParallelIterator;
+use bevy_tasks::{BatchSize, ParallelIterator, TaskPool};
+use bevy_utils::HashSet;
 use std::marker::PhantomData;
 
 /// Provides scoped access to a World according to a given [HecsQuery]
@@ -23,6 +24,8 @@ pub enum QueryError {
     CannotWriteArchetype,
     ComponentError(ComponentError),
     NoSuchEntity,
+    /// The entity was already requested earlier in the same mutable batch lookup
+    DuplicateEntity,
 }
 
 impl<'a, Q: WorldQuery, F: QueryFilter> Query<'a, Q, F> {
@@ -67,8 +70,11 @@ impl<'a, Q: WorldQuery, F: QueryFilter> Query<'a, Q, F> {
         self.world.query_unchecked()
     }
 
+    /// Iterates over the query results in parallel, in batches of `batch_size` results. With [BatchSize::Auto]
+    /// the batch size is derived from the number of matching entities and the threads of the [bevy_tasks::TaskPool]
+    /// consuming the iterator. This can only be called for read-only queries
     #[inline]
-    pub fn par_iter(&self, batch_size: usize) -> ParIter<'_, Q, F>
+    pub fn par_iter(&self, batch_size: impl Into<BatchSize>) -> ParIter<'_, Q, F>
     where
         Q::Fetch: ReadOnlyFetch,
     {
@@ -76,8 +82,9 @@ impl<'a, Q: WorldQuery, F: QueryFilter> Query<'a, Q, F> {
         unsafe { ParIter::new(self.world.query_batched_unchecked(batch_size)) }
     }
 
+    /// Iterates over the query results in parallel, see [Query::par_iter]
     #[inline]
-    pub fn par_iter_mut(&mut self, batch_size: usize) -> ParIter<'_, Q, F> {
+    pub fn par_iter_mut(&mut self, batch_size: impl Into<BatchSize>) -> ParIter<'_, Q, F> {
         // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
         unsafe { ParIter::new(self.world.query_batched_unchecked(batch_size)) }
     }
@@ -120,6 +127,127 @@ impl<'a, Q: WorldQuery, F: QueryFilter> Query<'a, Q, F> {
             .map_err(|_err| QueryError::NoSuchEntity)
     }
 
+    /// Gets the query results for each of the given `entities`, in order. An entity that does not match the
+    /// query only fails its own lookup.
+    pub fn get_many(
+        &self,
+        entities: &[Entity],
+    ) -> Vec<Result<<Q::Fetch as Fetch>::Item, QueryError>>
+    where
+        Q::Fetch: ReadOnlyFetch,
+    {
+        entities.iter().map(|entity| self.get(*entity)).collect()
+    }
+
+    /// Gets the query results for each of the given `entities`, in order. An entity that appears more than
+    /// once only succeeds the first time, later lookups return [QueryError::DuplicateEntity].
+    pub fn get_many_mut(
+        &mut self,
+        entities: &[Entity],
+    ) -> Vec<Result<<Q::Fetch as Fetch>::Item, QueryError>> {
+        let mut visited = HashSet::default();
+        let mut results = Vec::with_capacity(entities.len());
+        for entity in entities {
+            if visited.insert(*entity) {
+                // SAFE: each entity is fetched at most once, so the results do not alias
+                results.push(unsafe { self.get_unsafe(*entity) });
+            } else {
+                results.push(Err(QueryError::DuplicateEntity));
+            }
+        }
+        results
+    }
+
+    /// Iterates over the entities matching both this query and `other`, returning both results for each.
+    /// Two queries of the same system are checked for conflicting access when the system is initialized.
+    pub fn join<'s, Q2: WorldQuery, F2: QueryFilter>(
+        &'s self,
+        other: &'s Query<'_, Q2, F2>,
+    ) -> impl Iterator<
+        Item = (
+            <Q::Fetch as Fetch<'s>>::Item,
+            <Q2::Fetch as Fetch<'s>>::Item,
+        ),
+    > + 's
+    where
+        Q::Fetch: ReadOnlyFetch,
+        Q2::Fetch: ReadOnlyFetch,
+    {
+        // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
+        unsafe { self.world.query_unchecked::<(Entity, Q), F>() }
+            .filter_map(move |(entity, item)| Some((item, other.get(entity).ok()?)))
+    }
+
+    /// Like [Query::join], with mutable access to both queries
+    pub fn join_mut<'s, Q2: WorldQuery, F2: QueryFilter>(
+        &'s mut self,
+        other: &'s mut Query<'_, Q2, F2>,
+    ) -> impl Iterator<
+        Item = (
+            <Q::Fetch as Fetch<'s>>::Item,
+            <Q2::Fetch as Fetch<'s>>::Item,
+        ),
+    > + 's {
+        let other: &'s Query<'_, Q2, F2> = other;
+        // SAFE: system runs without conflicts with other systems. same-system queries have runtime borrow checks when they conflict
+        unsafe { self.world.query_unchecked::<(Entity, Q), F>() }.filter_map(
+            move |(entity, item)| {
+                // SAFE: the system checked that both queries do not conflict, and each entity is visited once
+                Some((item, unsafe { other.get_unsafe(entity) }.ok()?))
+            },
+        )
+    }
+
+    /// Iterates over this query, pairing each result with the result of `other` for the entity it references,
+    /// such as the entity of a `Parent`. Results whose `key` is `None` or whose entity does not match `other`
+    /// are skipped. Use [Query::get_many] to follow several references at once.
+    pub fn join_by<'s, Q2, F2, K>(
+        &'s self,
+        other: &'s Query<'_, Q2, F2>,
+        mut key: K,
+    ) -> impl Iterator<
+        Item = (
+            <Q::Fetch as Fetch<'s>>::Item,
+            <Q2::Fetch as Fetch<'s>>::Item,
+        ),
+    > + 's
+    where
+        Q::Fetch: ReadOnlyFetch,
+        Q2: WorldQuery,
+        Q2::Fetch: ReadOnlyFetch,
+        F2: QueryFilter,
+        K: FnMut(&<Q::Fetch as Fetch<'s>>::Item) -> Option<Entity> + 's,
+    {
+        self.iter().filter_map(move |item| {
+            let entity = key(&item)?;
+            Some((item, other.get(entity).ok()?))
+        })
+    }
+
+    /// Like [Query::join_by], with mutable access to this query. `other` is read only because several
+    /// results can reference the same entity.
+    pub fn join_by_mut<'s, Q2, F2, K>(
+        &'s mut self,
+        other: &'s Query<'_, Q2, F2>,
+        mut key: K,
+    ) -> impl Iterator<
+        Item = (
+            <Q::Fetch as Fetch<'s>>::Item,
+            <Q2::Fetch as Fetch<'s>>::Item,
+        ),
+    > + 's
+    where
+        Q2: WorldQuery,
+        Q2::Fetch: ReadOnlyFetch,
+        F2: QueryFilter,
+        K: FnMut(&<Q::Fetch as Fetch<'s>>::Item) -> Option<Entity> + 's,
+    {
+        self.iter_mut().filter_map(move |item| {
+            let entity = key(&item)?;
+            Some((item, other.get(entity).ok()?))
+        })
+    }
+
     /// Gets a reference to the entity's component of the given type. This will fail if the entity does not have
     /// the given component type or if the given component type does not match this query.
     pub fn get_component<T: Component>(&self, entity: Entity) -> Result<&T, QueryError> {
@@ -229,4 +357,8 @@ impl<'w, Q: WorldQuery, F: QueryFilter> ParallelIterator<Batch<'w, Q, F>> for Pa
     fn next_batch(&mut self) -> Option<Batch<'w, Q, F>> {
         self.batched_iter.next()
     }
+
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.batched_iter.set_thread_num(pool.thread_num());
+    }
 }
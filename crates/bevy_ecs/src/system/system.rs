@@ -49,6 +49,12 @@ pub trait System: Send + Sync + 'static {
     }
     fn run_thread_local(&mut self, world: &mut World, resources: &mut Resources);
     fn initialize(&mut self, _world: &mut World, _resources: &mut Resources);
+
+    /// Whether stage executors should apply the buffered commands of the systems that ran before this one when
+    /// they reach it, see [CommandFlush](crate::CommandFlush)
+    fn is_command_flush(&self) -> bool {
+        false
+    }
 }
 
 pub type BoxedSystem<In = (), Out = ()> = Box<dyn System<In = In, Out = Out>>;
@@ -1,7 +1,7 @@
 use crate::{
-    ArchetypeComponent, ChangedRes, Commands, Fetch, FromResources, Local, Or, Query, QueryAccess,
-    QueryFilter, QuerySet, QueryTuple, Res, ResMut, Resource, ResourceIndex, Resources,
-    SystemState, TypeAccess, World, WorldQuery,
+    AddedRes, ArchetypeComponent, ChangedRes, Commands, Fetch, FromResources, Local, MutatedRes,
+    Or, Query, QueryAccess, QueryFilter, QuerySet, QueryTuple, Res, ResMut, Resource,
+    ResourceIndex, Resources, SystemState, TypeAccess, World, WorldQuery,
 };
 use parking_lot::Mutex;
 use std::{any::TypeId, marker::PhantomData, sync::Arc};
@@ -248,6 +248,213 @@ impl<'a, T: Resource> FetchSystemParam<'a> for FetchChangedRes<T> {
     }
 }
 
+pub struct FetchAddedRes<T>(PhantomData<T>);
+
+impl<'a, T: Resource> SystemParam for AddedRes<'a, T> {
+    type Fetch = FetchAddedRes<T>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchAddedRes<T> {
+    type Item = AddedRes<'a, T>;
+
+    fn init(system_state: &mut SystemState, _world: &World, _resources: &mut Resources) {
+        if system_state.resource_access.is_write(&TypeId::of::<T>()) {
+            panic!(
+                "System `{}` has a `AddedRes<{res}>` parameter that conflicts with \
+                another parameter with mutable access to the same `{res}` resource.",
+                system_state.name,
+                res = std::any::type_name::<T>()
+            );
+        }
+        system_state.resource_access.add_read(TypeId::of::<T>());
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        let (value, added, _mutated) =
+            resources.get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global);
+        if *added.as_ptr() {
+            Some(AddedRes::new(value))
+        } else {
+            None
+        }
+    }
+}
+
+pub struct FetchMutatedRes<T>(PhantomData<T>);
+
+impl<'a, T: Resource> SystemParam for MutatedRes<'a, T> {
+    type Fetch = FetchMutatedRes<T>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchMutatedRes<T> {
+    type Item = MutatedRes<'a, T>;
+
+    fn init(system_state: &mut SystemState, _world: &World, _resources: &mut Resources) {
+        if system_state.resource_access.is_write(&TypeId::of::<T>()) {
+            panic!(
+                "System `{}` has a `MutatedRes<{res}>` parameter that conflicts with \
+                another parameter with mutable access to the same `{res}` resource.",
+                system_state.name,
+                res = std::any::type_name::<T>()
+            );
+        }
+        system_state.resource_access.add_read(TypeId::of::<T>());
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        let (value, _added, mutated) =
+            resources.get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global);
+        if *mutated.as_ptr() {
+            Some(MutatedRes::new(value))
+        } else {
+            None
+        }
+    }
+}
+
+/// Fetches an optional resource parameter, which is `None` when the resource does not exist instead of
+/// panicking. For [ChangedRes], [AddedRes] and [MutatedRes] it is also `None` when the resource did not
+/// change, and the system still runs.
+pub struct FetchOptionRes<F>(PhantomData<F>);
+
+impl<'a, T: Resource> SystemParam for Option<Res<'a, T>> {
+    type Fetch = FetchOptionRes<FetchRes<T>>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchOptionRes<FetchRes<T>> {
+    type Item = Option<Res<'a, T>>;
+
+    fn init(system_state: &mut SystemState, world: &World, resources: &mut Resources) {
+        FetchRes::<T>::init(system_state, world, resources);
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        Some(
+            resources
+                .try_get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global)
+                .map(|(value, _added, _mutated)| Res::new(value)),
+        )
+    }
+}
+
+impl<'a, T: Resource> SystemParam for Option<ResMut<'a, T>> {
+    type Fetch = FetchOptionRes<FetchResMut<T>>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchOptionRes<FetchResMut<T>> {
+    type Item = Option<ResMut<'a, T>>;
+
+    fn init(system_state: &mut SystemState, world: &World, resources: &mut Resources) {
+        FetchResMut::<T>::init(system_state, world, resources);
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        Some(
+            resources
+                .try_get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global)
+                .map(|(value, _added, mutated)| ResMut::new(value, mutated)),
+        )
+    }
+}
+
+impl<'a, T: Resource> SystemParam for Option<ChangedRes<'a, T>> {
+    type Fetch = FetchOptionRes<FetchChangedRes<T>>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchOptionRes<FetchChangedRes<T>> {
+    type Item = Option<ChangedRes<'a, T>>;
+
+    fn init(system_state: &mut SystemState, world: &World, resources: &mut Resources) {
+        FetchChangedRes::<T>::init(system_state, world, resources);
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        Some(
+            resources
+                .try_get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global)
+                .filter(|(_value, added, mutated)| *added.as_ptr() || *mutated.as_ptr())
+                .map(|(value, _added, _mutated)| ChangedRes::new(value)),
+        )
+    }
+}
+
+impl<'a, T: Resource> SystemParam for Option<AddedRes<'a, T>> {
+    type Fetch = FetchOptionRes<FetchAddedRes<T>>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchOptionRes<FetchAddedRes<T>> {
+    type Item = Option<AddedRes<'a, T>>;
+
+    fn init(system_state: &mut SystemState, world: &World, resources: &mut Resources) {
+        FetchAddedRes::<T>::init(system_state, world, resources);
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        Some(
+            resources
+                .try_get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global)
+                .filter(|(_value, added, _mutated)| *added.as_ptr())
+                .map(|(value, _added, _mutated)| AddedRes::new(value)),
+        )
+    }
+}
+
+impl<'a, T: Resource> SystemParam for Option<MutatedRes<'a, T>> {
+    type Fetch = FetchOptionRes<FetchMutatedRes<T>>;
+}
+
+impl<'a, T: Resource> FetchSystemParam<'a> for FetchOptionRes<FetchMutatedRes<T>> {
+    type Item = Option<MutatedRes<'a, T>>;
+
+    fn init(system_state: &mut SystemState, world: &World, resources: &mut Resources) {
+        FetchMutatedRes::<T>::init(system_state, world, resources);
+    }
+
+    #[inline]
+    unsafe fn get_param(
+        _system_state: &'a SystemState,
+        _world: &'a World,
+        resources: &'a Resources,
+    ) -> Option<Self::Item> {
+        Some(
+            resources
+                .try_get_unsafe_ref_with_added_and_mutated::<T>(ResourceIndex::Global)
+                .filter(|(_value, _added, mutated)| *mutated.as_ptr())
+                .map(|(value, _added, _mutated)| MutatedRes::new(value)),
+        )
+    }
+}
+
 pub struct FetchLocal<T>(PhantomData<T>);
 
 impl<'a, T: Resource + FromResources> SystemParam for Local<'a, T> {
@@ -0,0 +1,211 @@
+use crate::{
+    ArchetypeComponent, In, Resources, System, SystemId, ThreadLocalExecution, TypeAccess, World,
+};
+use bevy_utils::tracing::error;
+use std::{any::TypeId, borrow::Cow, fmt};
+
+/// The `Err` output of a system, passed to the error handler of an [IntoPipeErrorSystem::pipe_error] system
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemError {
+    /// The name of the system that failed
+    pub system: Cow<'static, str>,
+    /// The error, formatted with [fmt::Display]
+    pub error: String,
+}
+
+impl fmt::Display for SystemError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "system {} failed: {}", self.system, self.error)
+    }
+}
+
+impl std::error::Error for SystemError {}
+
+/// Logs the errors of a system with `error!`, which goes through `bevy_log` when the `LogPlugin` is used
+///
+/// ```
+/// # use bevy_ecs::prelude::*;
+/// fn fallible_system(value: Res<String>) -> Result<(), std::num::ParseIntError> {
+///     value.parse::<u32>()?;
+///     Ok(())
+/// }
+///
+/// let mut stage = SystemStage::parallel();
+/// stage.add_system(
+///     fallible_system
+///         .system()
+///         .pipe_error(log_system_error.system()),
+/// );
+/// ```
+pub fn log_system_error(In(error): In<SystemError>) {
+    error!("{}", error);
+}
+
+/// Panics with the name of the system and its error
+pub fn panic_on_system_error(In(error): In<SystemError>) {
+    panic!("{}", error);
+}
+
+/// A system returning `Result<(), E>` whose errors are passed to a `Handler` system, see
+/// [IntoPipeErrorSystem::pipe_error]
+pub struct PipeErrorSystem<SystemA, Handler> {
+    system: SystemA,
+    handler: Handler,
+    name: Cow<'static, str>,
+    id: SystemId,
+    archetype_component_access: TypeAccess<ArchetypeComponent>,
+    resource_access: TypeAccess<TypeId>,
+}
+
+impl<SystemA, Handler, E> System for PipeErrorSystem<SystemA, Handler>
+where
+    SystemA: System<Out = Result<(), E>>,
+    Handler: System<In = SystemError, Out = ()>,
+    E: fmt::Display,
+{
+    type In = SystemA::In;
+    type Out = ();
+
+    fn name(&self) -> Cow<'static, str> {
+        self.name.clone()
+    }
+
+    fn id(&self) -> SystemId {
+        self.id
+    }
+
+    fn update(&mut self, world: &World) {
+        self.archetype_component_access.clear();
+        self.resource_access.clear();
+        self.system.update(world);
+        self.handler.update(world);
+
+        self.archetype_component_access
+            .union(self.system.archetype_component_access());
+        self.archetype_component_access
+            .union(self.handler.archetype_component_access());
+        self.resource_access.union(self.system.resource_access());
+        self.resource_access.union(self.handler.resource_access());
+    }
+
+    fn archetype_component_access(&self) -> &TypeAccess<ArchetypeComponent> {
+        &self.archetype_component_access
+    }
+
+    fn resource_access(&self) -> &TypeAccess<TypeId> {
+        &self.resource_access
+    }
+
+    fn thread_local_execution(&self) -> ThreadLocalExecution {
+        ThreadLocalExecution::NextFlush
+    }
+
+    unsafe fn run_unsafe(
+        &mut self,
+        input: Self::In,
+        world: &World,
+        resources: &Resources,
+    ) -> Option<Self::Out> {
+        match self.system.run_unsafe(input, world, resources)? {
+            Ok(()) => Some(()),
+            Err(error) => {
+                let error = SystemError {
+                    system: self.system.name(),
+                    error: error.to_string(),
+                };
+                self.handler.run_unsafe(error, world, resources)
+            }
+        }
+    }
+
+    fn run_thread_local(&mut self, world: &mut World, resources: &mut Resources) {
+        self.system.run_thread_local(world, resources);
+        self.handler.run_thread_local(world, resources);
+    }
+
+    fn initialize(&mut self, world: &mut World, resources: &mut Resources) {
+        self.system.initialize(world, resources);
+        self.handler.initialize(world, resources);
+    }
+}
+
+pub trait IntoPipeErrorSystem<E>: System<Out = Result<(), E>> + Sized {
+    /// Passes the errors returned by this system to `handler`, which only runs when this system returns `Err`.
+    /// [log_system_error] and [panic_on_system_error] are common handlers.
+    fn pipe_error<Handler>(self, handler: Handler) -> PipeErrorSystem<Self, Handler>
+    where
+        Handler: System<In = SystemError, Out = ()>;
+}
+
+impl<SystemA, E> IntoPipeErrorSystem<E> for SystemA
+where
+    SystemA: System<Out = Result<(), E>>,
+{
+    fn pipe_error<Handler>(self, handler: Handler) -> PipeErrorSystem<SystemA, Handler>
+    where
+        Handler: System<In = SystemError, Out = ()>,
+    {
+        PipeErrorSystem {
+            name: Cow::Owned(format!("PipeError({}, {})", self.name(), handler.name())),
+            system: self,
+            handler,
+            archetype_component_access: Default::default(),
+            resource_access: Default::default(),
+            id: SystemId::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{panic_on_system_error, IntoPipeErrorSystem, SystemError};
+    use crate::{In, IntoSystem, Res, ResMut, Resources, System, World};
+
+    fn parse_system(value: Res<&'static str>, mut parsed: ResMut<u32>) -> Result<(), String> {
+        *parsed = value.parse().map_err(|_| format!("invalid {}", *value))?;
+        Ok(())
+    }
+
+    fn record_error(In(error): In<SystemError>, mut errors: ResMut<Vec<SystemError>>) {
+        errors.push(error);
+    }
+
+    #[test]
+    fn pipe_error() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert("1");
+        resources.insert(0u32);
+        resources.insert(Vec::<SystemError>::new());
+
+        let mut system = parse_system.system().pipe_error(record_error.system());
+        system.initialize(&mut world, &mut resources);
+        system.update(&world);
+        system.run((), &mut world, &mut resources);
+        assert_eq!(*resources.get::<u32>().unwrap(), 1);
+        assert!(resources.get::<Vec<SystemError>>().unwrap().is_empty());
+
+        *resources.get_mut::<&'static str>().unwrap() = "a";
+        system.run((), &mut world, &mut resources);
+        let errors = resources.get::<Vec<SystemError>>().unwrap();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].system.contains("parse_system"));
+        assert_eq!(errors[0].error, "invalid a");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid a")]
+    fn panic_on_error() {
+        let mut world = World::new();
+        let mut resources = Resources::default();
+        resources.insert("a");
+        resources.insert(0u32);
+
+        let mut system = parse_system
+            .system()
+            .pipe_error(panic_on_system_error.system());
+        system.initialize(&mut world, &mut resources);
+        system.update(&world);
+        system.run((), &mut world, &mut resources);
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    BoxedSystem, BoxedSystemLabel, Resources, SystemDescriptor, SystemId, SystemLabel, World,
+};
+use bevy_utils::HashMap;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SystemRegistryError {
+    #[error("There is no SystemRegistry resource.")]
+    MissingRegistry,
+    #[error("No system with id {0:?} is registered.")]
+    UnknownSystem(SystemId),
+    #[error("No registered system has the label {0:?}.")]
+    UnknownLabel(BoxedSystemLabel),
+    #[error("System with id {0:?} is already running. One-shot systems cannot run themselves.")]
+    AlreadyRunning(SystemId),
+}
+
+struct RegisteredSystem {
+    /// `None` while the system is running
+    system: Option<BoxedSystem>,
+    initialized: bool,
+}
+
+/// A resource that stores systems which are not part of any stage. They only run when requested, usually
+/// with [Commands::run_system](crate::Commands::run_system).
+///
+/// Registered systems support every [SystemParam](crate::SystemParam), and their [Local](crate::Local)
+/// resources are kept between runs.
+#[derive(Default)]
+pub struct SystemRegistry {
+    systems: HashMap<SystemId, RegisteredSystem>,
+    labels: HashMap<BoxedSystemLabel, SystemId>,
+}
+
+impl SystemRegistry {
+    /// Registers a system and returns the id it can be run with. Labels given to the system (for example with
+    /// `my_system.system().label("my_label")`) can be used to run it as well. Ordering constraints are ignored.
+    pub fn register<S: Into<SystemDescriptor>>(&mut self, system: S) -> SystemId {
+        let SystemDescriptor { system, ordering } = system.into();
+        let id = system.id();
+        for label in ordering.labels {
+            self.labels.insert(label, id);
+        }
+        self.systems.insert(
+            id,
+            RegisteredSystem {
+                system: Some(system),
+                initialized: false,
+            },
+        );
+        id
+    }
+
+    /// Removes a registered system, along with the labels pointing to it. Returns `None` if the system is unknown
+    /// or currently running.
+    pub fn remove(&mut self, id: SystemId) -> Option<BoxedSystem> {
+        let registered = self.systems.remove(&id)?;
+        self.labels.retain(|_, labelled_id| *labelled_id != id);
+        registered.system
+    }
+
+    pub fn contains(&self, id: SystemId) -> bool {
+        self.systems.contains_key(&id)
+    }
+
+    pub fn get_id(&self, label: impl SystemLabel) -> Option<SystemId> {
+        let label: BoxedSystemLabel = Box::new(label);
+        self.labels.get(&label).copied()
+    }
+
+    /// Runs the registered system with the given id right away, including its thread local part (which applies
+    /// its [Commands](crate::Commands)). The system is initialized the first time it runs.
+    pub fn run(
+        id: SystemId,
+        world: &mut World,
+        resources: &mut Resources,
+    ) -> Result<(), SystemRegistryError> {
+        let mut system = {
+            let mut registry = resources
+                .get_mut::<SystemRegistry>()
+                .ok_or(SystemRegistryError::MissingRegistry)?;
+            let registered = registry
+                .systems
+                .get_mut(&id)
+                .ok_or(SystemRegistryError::UnknownSystem(id))?;
+            let mut system = registered
+                .system
+                .take()
+                .ok_or(SystemRegistryError::AlreadyRunning(id))?;
+            if !registered.initialized {
+                registered.initialized = true;
+                // the registry can't stay borrowed while the system initializes
+                drop(registry);
+                system.initialize(world, resources);
+            }
+            system
+        };
+
+        system.update(world);
+        system.run((), world, resources);
+        system.run_thread_local(world, resources);
+
+        // the system may have removed itself from the registry while it ran
+        if let Some(mut registry) = resources.get_mut::<SystemRegistry>() {
+            if let Some(registered) = registry.systems.get_mut(&id) {
+                registered.system = Some(system);
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as [SystemRegistry::run], but looks the system up by label
+    pub fn run_by_label(
+        label: impl SystemLabel,
+        world: &mut World,
+        resources: &mut Resources,
+    ) -> Result<(), SystemRegistryError> {
+        Self::run_by_boxed_label(Box::new(label), world, resources)
+    }
+
+    pub(crate) fn run_by_boxed_label(
+        label: BoxedSystemLabel,
+        world: &mut World,
+        resources: &mut Resources,
+    ) -> Result<(), SystemRegistryError> {
+        let id = resources
+            .get::<SystemRegistry>()
+            .ok_or(SystemRegistryError::MissingRegistry)?
+            .labels
+            .get(&label)
+            .copied()
+            .ok_or(SystemRegistryError::UnknownLabel(label))?;
+        Self::run(id, world, resources)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SystemRegistry, SystemRegistryError};
+    use crate::{
+        resource::{Local, ResMut, Resources},
+        Commands, IntoSystem, SystemDescriptorCoercion, SystemId, World,
+    };
+
+    #[test]
+    fn run_registered_system() {
+        fn count_runs(mut runs: Local<usize>, mut total: ResMut<usize>, commands: &mut Commands) {
+            *runs += 1;
+            *total = *runs;
+            commands.spawn((*runs as u32,));
+        }
+
+        let mut world = World::default();
+        let mut resources = Resources::default();
+        resources.insert(0usize);
+        let mut registry = SystemRegistry::default();
+        let id = registry.register(count_runs.system().label("count_runs"));
+        resources.insert(registry);
+
+        let mut commands = Commands::default();
+        commands.set_entity_reserver(world.get_entity_reserver());
+        commands.run_system(id);
+        commands.apply(&mut world, &mut resources);
+        assert_eq!(*resources.get::<usize>().unwrap(), 1);
+
+        commands.run_system_by_label("count_runs");
+        commands.apply(&mut world, &mut resources);
+        // the local counter is kept between runs
+        assert_eq!(*resources.get::<usize>().unwrap(), 2);
+
+        let mut spawned = world.query::<&u32>().copied().collect::<Vec<_>>();
+        spawned.sort_unstable();
+        assert_eq!(spawned, vec![1, 2]);
+
+        assert!(matches!(
+            SystemRegistry::run(SystemId(0), &mut world, &mut resources),
+            Err(SystemRegistryError::UnknownSystem(_))
+        ));
+        assert!(matches!(
+            SystemRegistry::run_by_label("missing", &mut world, &mut resources),
+            Err(SystemRegistryError::UnknownLabel(_))
+        ));
+    }
+}
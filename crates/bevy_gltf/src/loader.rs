@@ -27,6 +27,7 @@ use gltf::{
     Material, Primitive,
 };
 use image::{GenericImageView, ImageFormat};
+use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, path::Path};
 use thiserror::Error;
 
@@ -59,13 +60,30 @@ pub enum GltfError {
 #[derive(Default)]
 pub struct GltfLoader;
 
+/// Settings of [GltfLoader], read from the `.meta` file of a GLTF file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GltfLoaderSettings {
+    /// Whether the cameras of the GLTF file are added to its scenes
+    pub load_cameras: bool,
+}
+
+impl Default for GltfLoaderSettings {
+    fn default() -> Self {
+        GltfLoaderSettings { load_cameras: true }
+    }
+}
+
 impl AssetLoader for GltfLoader {
     fn load<'a>(
         &'a self,
         bytes: &'a [u8],
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<()>> {
-        Box::pin(async move { Ok(load_gltf(bytes, load_context).await?) })
+        Box::pin(async move {
+            let settings: GltfLoaderSettings = load_context.settings()?;
+            Ok(load_gltf(bytes, load_context, &settings).await?)
+        })
     }
 
     fn extensions(&self) -> &[&str] {
@@ -76,6 +94,7 @@ impl AssetLoader for GltfLoader {
 async fn load_gltf<'a, 'b>(
     bytes: &'a [u8],
     load_context: &'a mut LoadContext<'b>,
+    settings: &'a GltfLoaderSettings,
 ) -> Result<(), GltfError> {
     let gltf = gltf::Gltf::from_slice(bytes)?;
     let buffer_data = load_buffers(&gltf, load_context, load_context.path()).await?;
@@ -231,7 +250,7 @@ async fn load_gltf<'a, 'b>(
             .spawn((Transform::default(), GlobalTransform::default()))
             .with_children(|parent| {
                 for node in scene.nodes() {
-                    let result = load_node(&node, parent, load_context, &buffer_data);
+                    let result = load_node(&node, parent, load_context, &buffer_data, settings);
                     if result.is_err() {
                         err = Some(result);
                         return;
@@ -309,6 +328,7 @@ fn load_node(
     world_builder: &mut WorldChildBuilder,
     load_context: &mut LoadContext,
     buffer_data: &[Vec<u8>],
+    settings: &GltfLoaderSettings,
 ) -> Result<(), GltfError> {
     let transform = gltf_node.transform();
     let mut gltf_error = None;
@@ -322,7 +342,7 @@ fn load_node(
     }
 
     // create camera node
-    if let Some(camera) = gltf_node.camera() {
+    if let Some(camera) = gltf_node.camera().filter(|_| settings.load_cameras) {
         node.with(VisibleEntities {
             ..Default::default()
         });
@@ -400,7 +420,7 @@ fn load_node(
 
         // append other nodes
         for child in gltf_node.children() {
-            if let Err(err) = load_node(&child, parent, load_context, buffer_data) {
+            if let Err(err) = load_node(&child, parent, load_context, buffer_data, settings) {
                 gltf_error = Some(err);
                 return;
             }
@@ -7,17 +7,20 @@ use std::marker::PhantomData;
 
 #[derive(Clone)]
 pub struct ReflectComponent {
-    add_component: fn(&mut World, resources: &Resources, Entity, &dyn Reflect),
+    add_component: fn(&mut World, resources: &mut Resources, Entity, &dyn Reflect),
     apply_component: fn(&mut World, Entity, &dyn Reflect),
     reflect_component: unsafe fn(&Archetype, usize) -> &dyn Reflect,
-    copy_component: fn(&World, &mut World, &Resources, Entity, Entity),
+    reflect_entity_component: fn(&World, Entity) -> Option<&dyn Reflect>,
+    copy_component: fn(&World, &mut World, &mut Resources, Entity, Entity),
 }
 
 impl ReflectComponent {
+    /// Adds the component to `entity`, built with [FromResources] and then patched with `component`. The hooks and
+    /// required components of the insertion are given `resources`.
     pub fn add_component(
         &self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         entity: Entity,
         component: &dyn Reflect,
     ) {
@@ -38,11 +41,20 @@ impl ReflectComponent {
         (self.reflect_component)(archetype, entity_index)
     }
 
+    /// Returns the component of `entity`, wherever it is stored
+    pub fn reflect_entity_component<'a>(
+        &self,
+        world: &'a World,
+        entity: Entity,
+    ) -> Option<&'a dyn Reflect> {
+        (self.reflect_entity_component)(world, entity)
+    }
+
     pub fn copy_component(
         &self,
         source_world: &World,
         destination_world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         source_entity: Entity,
         destination_entity: Entity,
     ) {
@@ -62,7 +74,9 @@ impl<C: Component + Reflect + FromResources> FromType<C> for ReflectComponent {
             add_component: |world, resources, entity, reflected_component| {
                 let mut component = C::from_resources(resources);
                 component.apply(reflected_component);
-                world.insert_one(entity, component).unwrap();
+                world.with_resources(resources, |world| {
+                    world.insert_one(entity, component).unwrap();
+                });
             },
             apply_component: |world, entity, reflected_component| {
                 let mut component = world.get_mut::<C>(entity).unwrap();
@@ -76,9 +90,11 @@ impl<C: Component + Reflect + FromResources> FromType<C> for ReflectComponent {
                 let source_component = source_world.get::<C>(source_entity).unwrap();
                 let mut destination_component = C::from_resources(resources);
                 destination_component.apply(source_component);
-                destination_world
-                    .insert_one(destination_entity, destination_component)
-                    .unwrap();
+                destination_world.with_resources(resources, |destination_world| {
+                    destination_world
+                        .insert_one(destination_entity, destination_component)
+                        .unwrap();
+                });
             },
             reflect_component: |archetype, index| {
                 unsafe {
@@ -87,6 +103,12 @@ impl<C: Component + Reflect + FromResources> FromType<C> for ReflectComponent {
                     ptr.as_ref().unwrap()
                 }
             },
+            reflect_entity_component: |world, entity| {
+                world
+                    .get::<C>(entity)
+                    .ok()
+                    .map(|component| component as &dyn Reflect)
+            },
         }
     }
 }
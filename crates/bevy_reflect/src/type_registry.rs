@@ -248,9 +248,65 @@ impl<T: for<'a> Deserialize<'a> + Reflect> FromType<T> for ReflectDeserialize {
     }
 }
 
+/// Reflects values of a type through raw pointers, like the pointers to components yielded by a `bevy_ecs`
+/// `DynamicQuery`
+#[derive(Clone)]
+pub struct ReflectFromPtr {
+    type_id: TypeId,
+    from_ptr: unsafe fn(*const u8) -> &'static dyn Reflect,
+    from_ptr_mut: unsafe fn(*mut u8) -> &'static mut dyn Reflect,
+}
+
+impl ReflectFromPtr {
+    /// The type of the values this reflects
+    pub fn type_id(&self) -> TypeId {
+        self.type_id
+    }
+
+    /// # Safety
+    /// `ptr` must point to a valid value of the type of [ReflectFromPtr::type_id], which must outlive `'a` and not
+    /// be mutated while the returned reference is live
+    pub unsafe fn as_reflect<'a>(&self, ptr: *const u8) -> &'a dyn Reflect {
+        (self.from_ptr)(ptr)
+    }
+
+    /// # Safety
+    /// `ptr` must point to a valid value of the type of [ReflectFromPtr::type_id], which must outlive `'a` and not
+    /// be accessed by anything else while the returned reference is live
+    pub unsafe fn as_reflect_mut<'a>(&self, ptr: *mut u8) -> &'a mut dyn Reflect {
+        (self.from_ptr_mut)(ptr)
+    }
+}
+
+impl<T: Reflect> FromType<T> for ReflectFromPtr {
+    fn from_type() -> Self {
+        ReflectFromPtr {
+            type_id: TypeId::of::<T>(),
+            from_ptr: |ptr| unsafe { &*ptr.cast::<T>() },
+            from_ptr_mut: |ptr| unsafe { &mut *ptr.cast::<T>() },
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::TypeRegistration;
+    use crate::{FromType, Reflect, ReflectFromPtr, TypeRegistration};
+
+    #[test]
+    fn reflect_from_ptr() {
+        let mut value = 1.5f32;
+        let from_ptr = <ReflectFromPtr as FromType<f32>>::from_type();
+        assert_eq!(from_ptr.type_id(), std::any::TypeId::of::<f32>());
+
+        let ptr = (&mut value as *mut f32).cast::<u8>();
+        let reflected = unsafe { from_ptr.as_reflect_mut(ptr) };
+        reflected.apply(&2.5f32);
+        assert_eq!(
+            unsafe { from_ptr.as_reflect(ptr) }.downcast_ref::<f32>(),
+            Some(&2.5)
+        );
+        assert_eq!(value, 2.5);
+    }
 
     #[test]
     fn test_get_short_name() {
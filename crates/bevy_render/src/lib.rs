@@ -49,9 +49,9 @@ use renderer::{AssetRenderResourceBindings, RenderResourceBindings};
 use shader::ShaderLoader;
 #[cfg(feature = "hdr")]
 use texture::HdrTextureLoader;
-#[cfg(feature = "png")]
-use texture::ImageTextureLoader;
 use texture::TextureResourceSystemState;
+#[cfg(feature = "png")]
+use texture::{ImageTextureLoader, ImageTextureSaver};
 
 /// The names of "render" App stages
 pub mod stage {
@@ -83,7 +83,8 @@ impl Plugin for RenderPlugin {
     fn build(&self, app: &mut AppBuilder) {
         #[cfg(feature = "png")]
         {
-            app.init_asset_loader::<ImageTextureLoader>();
+            app.init_asset_loader::<ImageTextureLoader>()
+                .init_asset_saver::<ImageTextureSaver>();
         }
         #[cfg(feature = "hdr")]
         {
@@ -121,6 +122,7 @@ impl Plugin for RenderPlugin {
         .register_type::<Draw>()
         .register_type::<Visible>()
         .register_type::<RenderPipelines>()
+        .register_required_components::<RenderPipelines>()
         .register_type::<OrthographicProjection>()
         .register_type::<PerspectiveProjection>()
         .register_type::<MainPass>()
@@ -6,7 +6,7 @@ use crate::{
     renderer::RenderResourceBindings,
 };
 use bevy_asset::{Assets, Handle};
-use bevy_ecs::{Query, Res, ResMut};
+use bevy_ecs::{Query, RequiredComponents, Res, ResMut};
 use bevy_reflect::{Reflect, ReflectComponent};
 use bevy_utils::HashSet;
 
@@ -39,8 +39,9 @@ impl RenderPipeline {
     }
 }
 
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, Reflect, RequiredComponents)]
 #[reflect(Component)]
+#[require(Draw)]
 pub struct RenderPipelines {
     pub pipelines: Vec<RenderPipeline>,
     #[reflect(ignore)]
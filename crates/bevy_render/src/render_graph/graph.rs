@@ -1,5 +1,8 @@
-use super::{Edge, Node, NodeId, NodeLabel, NodeState, RenderGraphError, SlotLabel, SystemNode};
-use bevy_ecs::{Commands, Schedule, SystemStage};
+use super::{
+    Edge, Node, NodeId, NodeLabel, NodeState, RenderGraphError, ResourceSlots, SlotLabel,
+    SystemNode,
+};
+use bevy_ecs::{Commands, DebugGraph, Schedule, SystemStage};
 use bevy_utils::HashMap;
 use std::{borrow::Cow, fmt::Debug};
 pub struct RenderGraph {
@@ -277,6 +280,63 @@ impl RenderGraph {
     pub fn take_commands(&mut self) -> Commands {
         std::mem::take(&mut self.commands)
     }
+
+    /// Describes the nodes of this graph and the edges between them. Slot edges are labeled with the
+    /// names of the slots they connect. Use [DebugGraph::to_dot] or [DebugGraph::to_json] to export it.
+    pub fn debug_graph(&self) -> DebugGraph {
+        let mut graph = DebugGraph::new("render_graph");
+        let node_id = |id: NodeId| format!("render_graph/{:?}", id);
+        let slot_name = |slots: &ResourceSlots, index: usize| {
+            slots
+                .get_slot(index)
+                .map(|slot| slot.info.name.to_string())
+                .unwrap_or_else(|_| index.to_string())
+        };
+
+        let mut nodes = self.iter_nodes().collect::<Vec<_>>();
+        nodes.sort_by(|a, b| a.name.cmp(&b.name));
+        for node in nodes {
+            let label = match &node.name {
+                Some(name) => name.to_string(),
+                None => format!("{:?}", node.id),
+            };
+            graph.add_node(node_id(node.id), label, "node");
+
+            for edge in node.edges.output_edges.iter() {
+                match edge {
+                    Edge::SlotEdge {
+                        input_node,
+                        input_index,
+                        output_node,
+                        output_index,
+                    } => {
+                        let input_slot = self
+                            .get_node_state(*input_node)
+                            .map(|input_node| slot_name(&input_node.input_slots, *input_index))
+                            .unwrap_or_else(|_| input_index.to_string());
+                        graph.add_edge(
+                            node_id(*output_node),
+                            node_id(*input_node),
+                            "slot",
+                            Some(format!(
+                                "{} -> {}",
+                                slot_name(&node.output_slots, *output_index),
+                                input_slot
+                            )),
+                        );
+                    }
+                    Edge::NodeEdge {
+                        input_node,
+                        output_node,
+                    } => {
+                        graph.add_edge(node_id(*output_node), node_id(*input_node), "node", None);
+                    }
+                }
+            }
+        }
+
+        graph
+    }
 }
 
 impl Debug for RenderGraph {
@@ -403,6 +463,45 @@ mod tests {
         assert!(output_nodes("D", &graph).is_empty(), "D has no outputs");
     }
 
+    #[test]
+    pub fn test_debug_graph() {
+        let mut graph = RenderGraph::default();
+        graph.add_node("A", TestNode::new(0, 1));
+        graph.add_node("B", TestNode::new(0, 1));
+        graph.add_node("C", TestNode::new(1, 0));
+        graph.add_slot_edge("A", "out_0", "C", "in_0").unwrap();
+        graph.add_node_edge("B", "C").unwrap();
+
+        let debug_graph = graph.debug_graph();
+        let labels = debug_graph
+            .nodes
+            .iter()
+            .map(|node| node.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, vec!["A", "B", "C"]);
+
+        let c_id = &debug_graph.nodes[2].id;
+        let slot_edge = debug_graph
+            .edges
+            .iter()
+            .find(|edge| edge.kind == "slot")
+            .unwrap();
+        assert_eq!(&slot_edge.from, &debug_graph.nodes[0].id);
+        assert_eq!(&slot_edge.to, c_id);
+        assert_eq!(slot_edge.label.as_deref(), Some("out_0 -> in_0"));
+        let node_edge = debug_graph
+            .edges
+            .iter()
+            .find(|edge| edge.kind == "node")
+            .unwrap();
+        assert_eq!(&node_edge.from, &debug_graph.nodes[1].id);
+        assert_eq!(&node_edge.to, c_id);
+
+        let dot = debug_graph.to_dot();
+        assert!(dot.starts_with("digraph \"render_graph\""));
+        assert!(dot.contains("label=\"out_0 -> in_0\""));
+    }
+
     #[test]
     pub fn test_get_node_typed() {
         struct MyNode {
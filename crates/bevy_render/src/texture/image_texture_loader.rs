@@ -1,12 +1,35 @@
-use super::{Extent3d, Texture, TextureDimension, TextureFormat};
+use super::{AddressMode, Extent3d, FilterMode, Texture, TextureDimension, TextureFormat};
 use anyhow::Result;
 use bevy_asset::{AssetLoader, LoadContext, LoadedAsset};
 use bevy_utils::BoxedFuture;
+use serde::{Deserialize, Serialize};
 
 /// Loader for images that can be read by the `image` crate.
 #[derive(Clone, Default)]
 pub struct ImageTextureLoader;
 
+/// Settings of [ImageTextureLoader], read from the `.meta` file of an image
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImageTextureSettings {
+    /// Whether the color data of the image is in sRGB space. Set it to `false` for linear data such as normal maps.
+    pub srgb: bool,
+    /// Overrides the filter mode of the sampler when magnifying, minifying and between mipmaps
+    pub filter_mode: Option<FilterMode>,
+    /// Overrides the address mode of the sampler in all dimensions
+    pub address_mode: Option<AddressMode>,
+}
+
+impl Default for ImageTextureSettings {
+    fn default() -> Self {
+        ImageTextureSettings {
+            srgb: true,
+            filter_mode: None,
+            address_mode: None,
+        }
+    }
+}
+
 const FILE_EXTENSIONS: &[&str] = &["png", "dds", "tga", "jpg", "jpeg", "bmp"];
 
 impl AssetLoader for ImageTextureLoader {
@@ -18,6 +41,8 @@ impl AssetLoader for ImageTextureLoader {
         Box::pin(async move {
             use bevy_core::AsBytes;
 
+            let settings: ImageTextureSettings = load_context.settings()?;
+
             // Find the image type we expect. A file with the extension "png" should
             // probably load as a PNG.
 
@@ -146,12 +171,26 @@ impl AssetLoader for ImageTextureLoader {
                 }
             }
 
-            let texture = Texture::new(
+            let format = match format {
+                TextureFormat::Rgba8UnormSrgb if !settings.srgb => TextureFormat::Rgba8Unorm,
+                TextureFormat::Bgra8UnormSrgb if !settings.srgb => TextureFormat::Bgra8Unorm,
+                format => format,
+            };
+
+            let mut texture = Texture::new(
                 Extent3d::new(width, height, 1),
                 TextureDimension::D2,
                 data,
                 format,
             );
+            if let Some(filter_mode) = settings.filter_mode {
+                texture.sampler.mag_filter = filter_mode;
+                texture.sampler.min_filter = filter_mode;
+                texture.sampler.mipmap_filter = filter_mode;
+            }
+            if let Some(address_mode) = settings.address_mode {
+                texture.sampler.set_address_mode(address_mode);
+            }
             load_context.set_default_asset(LoadedAsset::new(texture));
             Ok(())
         })
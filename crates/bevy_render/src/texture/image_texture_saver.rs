@@ -0,0 +1,113 @@
+use super::{Texture, TextureDimension, TextureFormat};
+use anyhow::Result;
+use bevy_asset::AssetSaver;
+use bevy_utils::BoxedFuture;
+use image::{DynamicImage, ImageBuffer, ImageOutputFormat};
+use std::path::Path;
+use thiserror::Error;
+
+/// Saver for [Texture]s, encoding them as PNG images.
+#[derive(Clone, Default)]
+pub struct ImageTextureSaver;
+
+#[derive(Error, Debug)]
+pub enum ImageTextureSaverError {
+    #[error("only 2D textures can be saved as images, found {0:?}")]
+    UnsupportedDimension(TextureDimension),
+    #[error("textures with the format {0:?} can not be saved as images")]
+    UnsupportedFormat(TextureFormat),
+}
+
+impl AssetSaver for ImageTextureSaver {
+    type Asset = Texture;
+
+    fn save<'a>(&'a self, asset: &'a Texture, _path: &'a Path) -> BoxedFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move { texture_to_png(asset) })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["png"]
+    }
+}
+
+fn texture_to_png(texture: &Texture) -> Result<Vec<u8>> {
+    if texture.dimension != TextureDimension::D2 || texture.size.depth != 1 {
+        return Err(ImageTextureSaverError::UnsupportedDimension(texture.dimension).into());
+    }
+
+    let width = texture.size.width;
+    let height = texture.size.height;
+    let data = texture.data.clone();
+    let data_u16 = || {
+        texture
+            .data
+            .chunks_exact(2)
+            .map(|bytes| u16::from_ne_bytes([bytes[0], bytes[1]]))
+            .collect::<Vec<u16>>()
+    };
+
+    // the buffer sizes always match, `Texture::new` asserts it
+    let dyn_img = match texture.format {
+        TextureFormat::R8Unorm => {
+            DynamicImage::ImageLuma8(ImageBuffer::from_raw(width, height, data).unwrap())
+        }
+        TextureFormat::Rg8Unorm => {
+            DynamicImage::ImageLumaA8(ImageBuffer::from_raw(width, height, data).unwrap())
+        }
+        TextureFormat::Rgba8Unorm | TextureFormat::Rgba8UnormSrgb => {
+            DynamicImage::ImageRgba8(ImageBuffer::from_raw(width, height, data).unwrap())
+        }
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb => DynamicImage::ImageRgba8(
+            DynamicImage::ImageBgra8(ImageBuffer::from_raw(width, height, data).unwrap())
+                .into_rgba8(),
+        ),
+        TextureFormat::R16Uint => {
+            DynamicImage::ImageLuma16(ImageBuffer::from_raw(width, height, data_u16()).unwrap())
+        }
+        TextureFormat::Rg16Uint => {
+            DynamicImage::ImageLumaA16(ImageBuffer::from_raw(width, height, data_u16()).unwrap())
+        }
+        TextureFormat::Rgba16Uint => {
+            DynamicImage::ImageRgba16(ImageBuffer::from_raw(width, height, data_u16()).unwrap())
+        }
+        format => return Err(ImageTextureSaverError::UnsupportedFormat(format).into()),
+    };
+
+    let mut bytes = Vec::new();
+    dyn_img.write_to(&mut bytes, ImageOutputFormat::Png)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::texture_to_png;
+    use crate::texture::{Extent3d, Texture, TextureDimension, TextureFormat};
+
+    #[test]
+    fn texture_png_round_trip() {
+        let data = vec![
+            255, 0, 0, 255, //
+            0, 255, 0, 128, //
+            0, 0, 255, 0, //
+            10, 20, 30, 40,
+        ];
+        let texture = Texture::new(
+            Extent3d::new(2, 2, 1),
+            TextureDimension::D2,
+            data.clone(),
+            TextureFormat::Rgba8UnormSrgb,
+        );
+
+        let bytes = texture_to_png(&texture).unwrap();
+        let image = image::load_from_memory_with_format(&bytes, image::ImageFormat::Png).unwrap();
+        assert_eq!(image.into_rgba8().into_raw(), data);
+
+        let texture = Texture::new(
+            Extent3d::new(1, 1, 1),
+            TextureDimension::D2,
+            vec![0; 16],
+            TextureFormat::Rgba32Float,
+        );
+        assert!(texture_to_png(&texture).is_err());
+    }
+}
@@ -8,6 +8,8 @@ mod hdr_texture_loader;
     feature = "bmp"
 ))]
 mod image_texture_loader;
+#[cfg(feature = "png")]
+mod image_texture_saver;
 mod sampler_descriptor;
 #[allow(clippy::module_inception)]
 mod texture;
@@ -24,6 +26,8 @@ pub use hdr_texture_loader::*;
     feature = "bmp"
 ))]
 pub use image_texture_loader::*;
+#[cfg(feature = "png")]
+pub use image_texture_saver::*;
 pub use sampler_descriptor::*;
 pub use texture::*;
 pub use texture_descriptor::*;
@@ -18,7 +18,7 @@ pub struct DynamicScene {
 }
 
 pub struct Entity {
-    pub entity: u32,
+    pub entity: bevy_ecs::Entity,
     pub components: Vec<Box<dyn Reflect>>,
 }
 
@@ -35,12 +35,17 @@ impl DynamicScene {
             for (index, entity) in archetype.iter_entities().enumerate() {
                 if index == entities.len() {
                     entities.push(Entity {
-                        entity: entity.id(),
+                        entity: *entity,
                         components: Vec::new(),
                     })
                 }
                 for type_info in archetype.types() {
-                    if let Some(registration) = type_registry.get(type_info.id()) {
+                    // components registered at runtime can't be reflected
+                    let registration = type_info
+                        .id()
+                        .type_id()
+                        .and_then(|type_id| type_registry.get(type_id));
+                    if let Some(registration) = registration {
                         if let Some(reflect_component) = registration.data::<ReflectComponent>() {
                             // SAFE: the index comes directly from a currently live component
                             unsafe {
@@ -51,6 +56,20 @@ impl DynamicScene {
                         }
                     }
                 }
+                for type_info in world.sparse_component_types(*entity) {
+                    let reflect_component = type_info
+                        .id()
+                        .type_id()
+                        .and_then(|type_id| type_registry.get(type_id))
+                        .and_then(|registration| registration.data::<ReflectComponent>());
+                    if let Some(reflect_component) = reflect_component {
+                        if let Some(component) =
+                            reflect_component.reflect_entity_component(world, *entity)
+                        {
+                            entities[index].components.push(component.clone_value());
+                        }
+                    }
+                }
             }
 
             scene.entities.extend(entities.drain(..));
@@ -62,14 +81,14 @@ impl DynamicScene {
     pub fn write_to_world(
         &self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
     ) -> Result<(), DynamicSceneToWorldError> {
-        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let type_registry = resources.get_cloned::<TypeRegistryArc>().unwrap();
         let type_registry = type_registry.read();
         let mut entity_map = EntityMap::default();
         for scene_entity in self.entities.iter() {
             let new_entity = world.reserve_entity();
-            entity_map.insert(bevy_ecs::Entity::new(scene_entity.entity), new_entity);
+            entity_map.insert(scene_entity.entity, new_entity);
             for component in scene_entity.components.iter() {
                 let registration = type_registry
                     .get_with_name(component.type_name())
@@ -101,12 +120,12 @@ impl DynamicScene {
         Ok(())
     }
 
-    // TODO: move to AssetSaver when it is implemented
+    /// Serializes the scene to RON, which is what [SceneSaver](crate::SceneSaver) writes
     pub fn serialize_ron(&self, registry: &TypeRegistryArc) -> Result<String, ron::Error> {
         serialize_ron(SceneSerializer::new(self, registry))
     }
 
-    pub fn get_scene(&self, resources: &Resources) -> Result<Scene, DynamicSceneToWorldError> {
+    pub fn get_scene(&self, resources: &mut Resources) -> Result<Scene, DynamicSceneToWorldError> {
         let mut world = World::default();
         self.write_to_world(&mut world, resources)?;
         Ok(Scene::new(world))
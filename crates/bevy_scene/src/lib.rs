@@ -2,6 +2,7 @@ mod command;
 mod dynamic_scene;
 mod scene;
 mod scene_loader;
+mod scene_saver;
 mod scene_spawner;
 pub mod serde;
 
@@ -10,6 +11,7 @@ pub use command::*;
 pub use dynamic_scene::*;
 pub use scene::*;
 pub use scene_loader::*;
+pub use scene_saver::*;
 pub use scene_spawner::*;
 
 pub mod prelude {
@@ -31,8 +33,150 @@ impl Plugin for ScenePlugin {
         app.add_asset::<DynamicScene>()
             .add_asset::<Scene>()
             .init_asset_loader::<SceneLoader>()
+            .init_asset_saver::<SceneSaver>()
             .init_resource::<SceneSpawner>()
             .add_stage_after(stage::EVENT, SCENE_STAGE, SystemStage::parallel())
             .add_system_to_stage(SCENE_STAGE, scene_spawner_system.system());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{serde::SceneDeserializer, DynamicScene, Scene, ScenePlugin, SceneSpawner};
+    use bevy_app::App;
+    use bevy_asset::{AddAsset, AssetPlugin, AssetServer, Assets, MemoryAssetIo};
+    use bevy_ecs::{FromResources, Resources, StorageType, World};
+    use bevy_reflect::{
+        Reflect, ReflectComponent, ReflectPlugin, RegisterTypeBuilder, TypeRegistryArc,
+    };
+    use bevy_tasks::TaskPool;
+    use serde::de::DeserializeSeed;
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Health {
+        value: u32,
+    }
+
+    #[derive(Reflect, Default, Debug, PartialEq)]
+    #[reflect(Component)]
+    struct Selected {
+        order: u32,
+    }
+
+    #[test]
+    fn round_trip_sparse_components() {
+        let mut app = App::build();
+        app.add_resource(AssetServer::new(
+            MemoryAssetIo::default(),
+            TaskPool::default(),
+        ))
+        .add_plugin(ReflectPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(ScenePlugin)
+        .register_type::<Health>()
+        .register_type::<Selected>();
+        let resources = &mut app.app.resources;
+        let type_registry = resources.get_cloned::<TypeRegistryArc>().unwrap();
+
+        let mut world = World::default();
+        world
+            .set_storage_type::<Selected>(StorageType::SparseSet)
+            .unwrap();
+        world.spawn((Health { value: 3 }, Selected { order: 1 }));
+        world.spawn((Health { value: 5 },));
+
+        let ron = DynamicScene::from_world(&world, &type_registry)
+            .serialize_ron(&type_registry)
+            .unwrap();
+        let mut deserializer = ron::de::Deserializer::from_str(&ron).unwrap();
+        let dynamic_scene = SceneDeserializer {
+            type_registry: &*type_registry.read(),
+        }
+        .deserialize(&mut deserializer)
+        .unwrap();
+        let scene = dynamic_scene.get_scene(resources).unwrap();
+        let mut loaded = scene
+            .world
+            .query::<(&Health, Option<&Selected>)>()
+            .map(|(health, selected)| (health.value, selected.map(|selected| selected.order)))
+            .collect::<Vec<_>>();
+        loaded.sort_unstable();
+        assert_eq!(loaded, vec![(3, Some(1)), (5, None)]);
+
+        let scene_handle = resources.get_mut::<Assets<Scene>>().unwrap().add(scene);
+        let mut spawned_world = World::default();
+        spawned_world
+            .set_storage_type::<Selected>(StorageType::SparseSet)
+            .unwrap();
+        SceneSpawner::default()
+            .spawn_sync(&mut spawned_world, resources, scene_handle)
+            .unwrap();
+        let mut spawned = spawned_world
+            .query::<(&Health, Option<&Selected>)>()
+            .map(|(health, selected)| (health.value, selected.map(|selected| selected.order)))
+            .collect::<Vec<_>>();
+        spawned.sort_unstable();
+        assert_eq!(spawned, vec![(3, Some(1)), (5, None)]);
+    }
+
+    #[derive(Reflect, Default)]
+    #[reflect(Component)]
+    struct Tinted {
+        strength: u32,
+    }
+
+    struct DefaultTint(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Tint(u32);
+
+    impl FromResources for Tint {
+        fn from_resources(resources: &Resources) -> Self {
+            Tint(resources.get::<DefaultTint>().unwrap().0)
+        }
+    }
+
+    #[test]
+    fn spawned_scenes_build_required_components_from_resources() {
+        let mut app = App::build();
+        app.add_resource(AssetServer::new(
+            MemoryAssetIo::default(),
+            TaskPool::default(),
+        ))
+        .add_resource(DefaultTint(7))
+        .add_plugin(ReflectPlugin)
+        .add_plugin(AssetPlugin)
+        .add_plugin(ScenePlugin)
+        .register_type::<Tinted>();
+        let app = &mut app.app;
+        app.world.require::<Tinted, Tint>();
+
+        let dynamic_scene = DynamicScene {
+            entities: vec![crate::Entity {
+                entity: bevy_ecs::Entity::new(0),
+                components: vec![Box::new(Tinted { strength: 2 })],
+            }],
+        };
+        let scene = dynamic_scene.get_scene(&mut app.resources).unwrap();
+        let dynamic_scene_handle = app
+            .resources
+            .get_mut::<Assets<DynamicScene>>()
+            .unwrap()
+            .add(dynamic_scene);
+        let scene_handle = app.resources.get_mut::<Assets<Scene>>().unwrap().add(scene);
+        {
+            let mut scene_spawner = app.resources.get_mut::<SceneSpawner>().unwrap();
+            scene_spawner.spawn_dynamic(dynamic_scene_handle);
+            scene_spawner.spawn(scene_handle);
+        }
+        app.update();
+
+        let spawned = app
+            .world
+            .query::<(&Tinted, &Tint)>()
+            .map(|(tinted, tint)| (tinted.strength, tint.0))
+            .collect::<Vec<_>>();
+        assert_eq!(spawned, vec![(2, 7), (2, 7)]);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::DynamicScene;
+use anyhow::Result;
+use bevy_asset::AssetSaver;
+use bevy_ecs::{FromResources, Resources};
+use bevy_reflect::TypeRegistryArc;
+use bevy_utils::BoxedFuture;
+use std::path::Path;
+
+#[derive(Debug)]
+pub struct SceneSaver {
+    type_registry: TypeRegistryArc,
+}
+
+impl FromResources for SceneSaver {
+    fn from_resources(resources: &Resources) -> Self {
+        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        SceneSaver {
+            type_registry: (&*type_registry).clone(),
+        }
+    }
+}
+
+impl AssetSaver for SceneSaver {
+    type Asset = DynamicScene;
+
+    fn save<'a>(
+        &'a self,
+        asset: &'a DynamicScene,
+        _path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Vec<u8>>> {
+        Box::pin(async move { Ok(asset.serialize_ron(&self.type_registry)?.into_bytes()) })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scn"]
+    }
+}
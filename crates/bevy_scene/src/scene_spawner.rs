@@ -2,12 +2,45 @@ use crate::{DynamicScene, Scene};
 use bevy_app::prelude::*;
 use bevy_asset::{AssetEvent, Assets, Handle};
 use bevy_ecs::{Entity, EntityMap, Resources, World};
-use bevy_reflect::{ReflectComponent, ReflectMapEntities, TypeRegistryArc};
+use bevy_reflect::{Reflect, ReflectComponent, ReflectMapEntities, TypeRegistryArc};
 use bevy_transform::prelude::Parent;
 use bevy_utils::HashMap;
+use std::any::TypeId;
 use thiserror::Error;
 use uuid::Uuid;
 
+/// A component copied out of a scene asset, added to the world once the asset is no longer borrowed so the
+/// [Resources] can be lent to the world
+struct CopiedComponent {
+    entity: Entity,
+    type_id: TypeId,
+    /// Whether a component the entity already has is kept as is instead of being patched
+    keep_existing: bool,
+    reflect_component: ReflectComponent,
+    value: Box<dyn Reflect>,
+}
+
+fn add_copied_components(
+    world: &mut World,
+    resources: &mut Resources,
+    components: Vec<CopiedComponent>,
+) {
+    for component in components {
+        if !world.has_component_type(component.entity, component.type_id) {
+            component.reflect_component.add_component(
+                world,
+                resources,
+                component.entity,
+                &*component.value,
+            );
+        } else if !component.keep_existing {
+            component
+                .reflect_component
+                .apply_component(world, component.entity, &*component.value);
+        }
+    }
+}
+
 #[derive(Debug)]
 struct InstanceInfo {
     entity_map: EntityMap,
@@ -90,7 +123,7 @@ impl SceneSpawner {
     pub fn spawn_dynamic_sync(
         &mut self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         scene_handle: &Handle<DynamicScene>,
     ) -> Result<(), SceneSpawnError> {
         let instance_id = InstanceId::new();
@@ -109,53 +142,57 @@ impl SceneSpawner {
 
     fn spawn_dynamic_internal(
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         scene_handle: &Handle<DynamicScene>,
         instance_info: &mut InstanceInfo,
     ) -> Result<(), SceneSpawnError> {
-        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let type_registry = resources.get_cloned::<TypeRegistryArc>().unwrap();
         let type_registry = type_registry.read();
-        let scenes = resources.get::<Assets<DynamicScene>>().unwrap();
-        let scene = scenes
-            .get(scene_handle)
-            .ok_or_else(|| SceneSpawnError::NonExistentScene {
-                handle: scene_handle.clone_weak(),
-            })?;
-
-        for scene_entity in scene.entities.iter() {
-            let entity = *instance_info
-                .entity_map
-                // TODO: use Entity type directly in scenes to properly encode generation / avoid the need to patch things up?
-                .entry(bevy_ecs::Entity::new(scene_entity.entity))
-                .or_insert_with(|| world.reserve_entity());
-            for component in scene_entity.components.iter() {
-                let registration = type_registry
-                    .get_with_name(component.type_name())
-                    .ok_or_else(|| SceneSpawnError::UnregisteredType {
-                        type_name: component.type_name().to_string(),
+        let mut components = Vec::new();
+        {
+            let scenes = resources.get::<Assets<DynamicScene>>().unwrap();
+            let scene =
+                scenes
+                    .get(scene_handle)
+                    .ok_or_else(|| SceneSpawnError::NonExistentScene {
+                        handle: scene_handle.clone_weak(),
                     })?;
-                let reflect_component =
-                    registration.data::<ReflectComponent>().ok_or_else(|| {
-                        SceneSpawnError::UnregisteredComponent {
+
+            for scene_entity in scene.entities.iter() {
+                let entity = *instance_info
+                    .entity_map
+                    .entry(scene_entity.entity)
+                    .or_insert_with(|| world.reserve_entity());
+                for component in scene_entity.components.iter() {
+                    let registration = type_registry
+                        .get_with_name(component.type_name())
+                        .ok_or_else(|| SceneSpawnError::UnregisteredType {
                             type_name: component.type_name().to_string(),
-                        }
-                    })?;
-                if world.has_component_type(entity, registration.type_id()) {
-                    if registration.short_name() != "Camera" {
-                        reflect_component.apply_component(world, entity, &**component);
-                    }
-                } else {
-                    reflect_component.add_component(world, resources, entity, &**component);
+                        })?;
+                    let reflect_component =
+                        registration.data::<ReflectComponent>().ok_or_else(|| {
+                            SceneSpawnError::UnregisteredComponent {
+                                type_name: component.type_name().to_string(),
+                            }
+                        })?;
+                    components.push(CopiedComponent {
+                        entity,
+                        type_id: registration.type_id(),
+                        keep_existing: registration.short_name() == "Camera",
+                        reflect_component: reflect_component.clone(),
+                        value: component.clone_value(),
+                    });
                 }
             }
         }
+        add_copied_components(world, resources, components);
         Ok(())
     }
 
     pub fn spawn_sync(
         &mut self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         scene_handle: Handle<Scene>,
     ) -> Result<InstanceId, SceneSpawnError> {
         self.spawn_sync_internal(world, resources, scene_handle, InstanceId::new())
@@ -164,51 +201,63 @@ impl SceneSpawner {
     fn spawn_sync_internal(
         &mut self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         scene_handle: Handle<Scene>,
         instance_id: InstanceId,
     ) -> Result<InstanceId, SceneSpawnError> {
         let mut instance_info = InstanceInfo {
             entity_map: EntityMap::default(),
         };
-        let type_registry = resources.get::<TypeRegistryArc>().unwrap();
+        let type_registry = resources.get_cloned::<TypeRegistryArc>().unwrap();
         let type_registry = type_registry.read();
-        let scenes = resources.get::<Assets<Scene>>().unwrap();
-        let scene =
-            scenes
-                .get(&scene_handle)
-                .ok_or_else(|| SceneSpawnError::NonExistentRealScene {
-                    handle: scene_handle.clone(),
-                })?;
-
-        for archetype in scene.world.archetypes() {
-            for scene_entity in archetype.iter_entities() {
-                let entity = *instance_info
-                    .entity_map
-                    .entry(*scene_entity)
-                    .or_insert_with(|| world.reserve_entity());
-                for type_info in archetype.types() {
-                    let registration = type_registry.get(type_info.id()).ok_or_else(|| {
-                        SceneSpawnError::UnregisteredType {
-                            type_name: type_info.type_name().to_string(),
-                        }
+        let mut components = Vec::new();
+        {
+            let scenes = resources.get::<Assets<Scene>>().unwrap();
+            let scene =
+                scenes
+                    .get(&scene_handle)
+                    .ok_or_else(|| SceneSpawnError::NonExistentRealScene {
+                        handle: scene_handle.clone(),
                     })?;
-                    let reflect_component =
-                        registration.data::<ReflectComponent>().ok_or_else(|| {
-                            SceneSpawnError::UnregisteredComponent {
+
+            for archetype in scene.world.archetypes() {
+                for scene_entity in archetype.iter_entities() {
+                    let entity = *instance_info
+                        .entity_map
+                        .entry(*scene_entity)
+                        .or_insert_with(|| world.reserve_entity());
+                    let types = archetype
+                        .types()
+                        .iter()
+                        .chain(scene.world.sparse_component_types(*scene_entity));
+                    for type_info in types {
+                        let registration = type_info
+                            .id()
+                            .type_id()
+                            .and_then(|type_id| type_registry.get(type_id))
+                            .ok_or_else(|| SceneSpawnError::UnregisteredType {
+                                type_name: type_info.type_name().to_string(),
+                            })?;
+                        let reflect_component = registration
+                            .data::<ReflectComponent>()
+                            .ok_or_else(|| SceneSpawnError::UnregisteredComponent {
                                 type_name: registration.name().to_string(),
-                            }
-                        })?;
-                    reflect_component.copy_component(
-                        &scene.world,
-                        world,
-                        resources,
-                        *scene_entity,
-                        entity,
-                    );
+                            })?;
+                        let component = reflect_component
+                            .reflect_entity_component(&scene.world, *scene_entity)
+                            .unwrap();
+                        components.push(CopiedComponent {
+                            entity,
+                            type_id: registration.type_id(),
+                            keep_existing: false,
+                            reflect_component: reflect_component.clone(),
+                            value: component.clone_value(),
+                        });
+                    }
                 }
             }
         }
+        add_copied_components(world, resources, components);
         for registration in type_registry.iter() {
             if let Some(map_entities_reflect) = registration.data::<ReflectMapEntities>() {
                 map_entities_reflect
@@ -228,7 +277,7 @@ impl SceneSpawner {
     pub fn update_spawned_scenes(
         &mut self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
         scene_handles: &[Handle<DynamicScene>],
     ) -> Result<(), SceneSpawnError> {
         for scene_handle in scene_handles {
@@ -260,7 +309,7 @@ impl SceneSpawner {
     pub fn spawn_queued_scenes(
         &mut self,
         world: &mut World,
-        resources: &Resources,
+        resources: &mut Resources,
     ) -> Result<(), SceneSpawnError> {
         let scenes_to_spawn = std::mem::take(&mut self.dynamic_scenes_to_spawn);
 
@@ -324,27 +373,35 @@ impl SceneSpawner {
 }
 
 pub fn scene_spawner_system(world: &mut World, resources: &mut Resources) {
-    let mut scene_spawner = resources.get_mut::<SceneSpawner>().unwrap();
-    let scene_asset_events = resources.get::<Events<AssetEvent<DynamicScene>>>().unwrap();
+    // the spawner is taken out so the resources can be lent to the world while scenes are spawned
+    let mut scene_spawner = std::mem::take(&mut *resources.get_mut::<SceneSpawner>().unwrap());
 
     let mut updated_spawned_scenes = Vec::new();
-    for event in scene_spawner
-        .scene_asset_event_reader
-        .iter(&scene_asset_events)
     {
-        if let AssetEvent::Modified { handle } = event {
-            if scene_spawner.spawned_dynamic_scenes.contains_key(handle) {
-                updated_spawned_scenes.push(handle.clone_weak());
+        let scene_asset_events = resources.get::<Events<AssetEvent<DynamicScene>>>().unwrap();
+        for event in scene_spawner
+            .scene_asset_event_reader
+            .iter(&scene_asset_events)
+        {
+            if let AssetEvent::Modified { handle } = event {
+                if scene_spawner.spawned_dynamic_scenes.contains_key(handle) {
+                    updated_spawned_scenes.push(handle.clone_weak());
+                }
             }
         }
     }
 
-    scene_spawner.despawn_queued_scenes(world).unwrap();
+    world.with_resources(resources, |world| {
+        scene_spawner.despawn_queued_scenes(world).unwrap();
+    });
     scene_spawner
         .spawn_queued_scenes(world, resources)
         .unwrap_or_else(|err| panic!("{}", err));
     scene_spawner
         .update_spawned_scenes(world, resources, &updated_spawned_scenes)
         .unwrap();
-    scene_spawner.set_scene_instance_parent_sync(world);
+    world.with_resources(resources, |world| {
+        scene_spawner.set_scene_instance_parent_sync(world);
+    });
+    *resources.get_mut::<SceneSpawner>().unwrap() = scene_spawner;
 }
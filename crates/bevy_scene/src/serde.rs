@@ -181,7 +181,7 @@ impl<'a, 'de> Visitor<'de> for SceneEntityVisitor<'a> {
                     if id.is_some() {
                         return Err(Error::duplicate_field(ENTITY_FIELD_ENTITY));
                     }
-                    id = Some(map.next_value::<u32>()?);
+                    id = Some(map.next_value::<bevy_ecs::Entity>()?);
                 }
                 EntityField::Components => {
                     if components.is_some() {
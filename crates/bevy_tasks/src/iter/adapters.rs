@@ -1,4 +1,4 @@
-use crate::iter::ParallelIterator;
+use crate::{iter::ParallelIterator, TaskPool};
 
 #[derive(Debug)]
 pub struct Chain<T, U> {
@@ -15,6 +15,11 @@ where
 {
     type Item = T::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.left.prepare(pool);
+        self.right.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<B> {
         if self.left_in_progress {
             match self.left.next_batch() {
@@ -40,6 +45,10 @@ where
 {
     type Item = T;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<std::iter::Map<B, F>> {
         self.iter.next_batch().map(|b| b.map(self.f.clone()))
     }
@@ -59,6 +68,10 @@ where
 {
     type Item = P::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<std::iter::Filter<B, F>> {
         self.iter
             .next_batch()
@@ -80,6 +93,10 @@ where
 {
     type Item = R;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<std::iter::FilterMap<B, F>> {
         self.iter.next_batch().map(|b| b.filter_map(self.f.clone()))
     }
@@ -101,6 +118,10 @@ where
 {
     type Item = U::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     // This extends each batch using the flat map. The other option is
     // to turn each IntoIter into its own batch.
     fn next_batch(&mut self) -> Option<std::iter::FlatMap<B, U, F>> {
@@ -122,6 +143,10 @@ where
 {
     type Item = <P::Item as IntoIterator>::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     // This extends each batch using the flatten. The other option is to
     // turn each IntoIter into its own batch.
     fn next_batch(&mut self) -> Option<std::iter::Flatten<B>> {
@@ -141,6 +166,12 @@ where
 {
     type Item = P::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        if let Some(iter) = &mut self.iter {
+            iter.prepare(pool);
+        }
+    }
+
     fn next_batch(&mut self) -> Option<B> {
         match &mut self.iter {
             Some(iter) => match iter.next_batch() {
@@ -169,6 +200,10 @@ where
 {
     type Item = P::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<std::iter::Inspect<B, F>> {
         self.iter.next_batch().map(|b| b.inspect(self.f.clone()))
     }
@@ -187,6 +222,10 @@ where
 {
     type Item = T;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<std::iter::Copied<B>> {
         self.iter.next_batch().map(|b| b.copied())
     }
@@ -205,6 +244,10 @@ where
 {
     type Item = T;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<std::iter::Cloned<B>> {
         self.iter.next_batch().map(|b| b.cloned())
     }
@@ -223,6 +266,10 @@ where
 {
     type Item = P::Item;
 
+    fn prepare(&mut self, pool: &TaskPool) {
+        self.iter.prepare(pool);
+    }
+
     fn next_batch(&mut self) -> Option<B> {
         match self.curr.as_mut().and_then(|c| c.next_batch()) {
             batch @ Some(_) => batch,
@@ -3,6 +3,38 @@ use crate::TaskPool;
 mod adapters;
 pub use adapters::*;
 
+/// How a [ParallelIterator] splits its items into batches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchSize {
+    /// Batches of at most the given number of items
+    Fixed(usize),
+    /// Batches sized from the number of items and the thread count of the [TaskPool] consuming the
+    /// iterator, so that each thread gets [BatchSize::BATCHES_PER_THREAD] batches to balance uneven work
+    Auto,
+}
+
+impl BatchSize {
+    /// The number of batches per thread targeted by [BatchSize::Auto]
+    pub const BATCHES_PER_THREAD: usize = 4;
+
+    /// Returns the number of items per batch when splitting `len` items over `thread_num` threads
+    pub fn get(self, len: usize, thread_num: usize) -> usize {
+        match self {
+            BatchSize::Fixed(batch_size) => batch_size.max(1),
+            BatchSize::Auto => {
+                let batch_count = thread_num.max(1) * Self::BATCHES_PER_THREAD;
+                (len / batch_count).max(1)
+            }
+        }
+    }
+}
+
+impl From<usize> for BatchSize {
+    fn from(batch_size: usize) -> Self {
+        BatchSize::Fixed(batch_size)
+    }
+}
+
 /// ParallelIterator closely emulates the std::iter::Iterator
 /// interface. However, it uses bevy_task to compute batches in parallel.
 ///
@@ -32,10 +64,15 @@ where
         (0, None)
     }
 
+    /// Called with the [TaskPool] consuming this iterator before its first batch is taken. Iterators
+    /// using [BatchSize::Auto] size their batches for the thread count of `pool` here.
+    fn prepare(&mut self, _pool: &TaskPool) {}
+
     /// Consumes the parallel iterator and returns the number of items.
     ///
     /// See [`Iterator::count()`](https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.count)
     fn count(mut self, pool: &TaskPool) -> usize {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 s.spawn(async move { batch.count() })
@@ -48,7 +85,8 @@ where
     /// Consumes the parallel iterator and returns the last item.
     ///
     /// See [`Iterator::last()`](https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.last)
-    fn last(mut self, _pool: &TaskPool) -> Option<Self::Item> {
+    fn last(mut self, pool: &TaskPool) -> Option<Self::Item> {
+        self.prepare(pool);
         let mut last_item = None;
         while let Some(batch) = self.next_batch() {
             last_item = batch.last();
@@ -60,7 +98,8 @@ where
     ///
     /// See [`Iterator::nth()`](https://doc.rust-lang.org/std/iter/trait.Iterator.html#method.nth)
     // TODO: Optimize with size_hint on each batch
-    fn nth(mut self, _pool: &TaskPool, n: usize) -> Option<Self::Item> {
+    fn nth(mut self, pool: &TaskPool, n: usize) -> Option<Self::Item> {
+        self.prepare(pool);
         let mut i = 0;
         while let Some(batch) = self.next_batch() {
             for item in batch {
@@ -107,6 +146,7 @@ where
     where
         F: FnMut(Self::Item) + Send + Clone + Sync,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let newf = f.clone();
@@ -197,6 +237,7 @@ where
         C: std::iter::FromIterator<Self::Item>,
         Self::Item: Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 s.spawn(async move { batch.collect::<Vec<_>>() });
@@ -217,6 +258,7 @@ where
         F: FnMut(&Self::Item) -> bool + Send + Sync + Clone,
         Self::Item: Send + 'static,
     {
+        self.prepare(pool);
         let (mut a, mut b) = <(C, C)>::default();
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
@@ -244,6 +286,7 @@ where
         F: FnMut(C, Self::Item) -> C + Send + Sync + Clone,
         C: Clone + Send + Sync + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let newf = f.clone();
@@ -262,6 +305,7 @@ where
     where
         F: FnMut(Self::Item) -> bool + Send + Sync + Clone,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(mut batch) = self.next_batch() {
                 let newf = f.clone();
@@ -281,6 +325,7 @@ where
     where
         F: FnMut(Self::Item) -> bool + Send + Sync + Clone,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(mut batch) = self.next_batch() {
                 let newf = f.clone();
@@ -301,6 +346,7 @@ where
     where
         F: FnMut(Self::Item) -> bool + Send + Sync + Clone,
     {
+        self.prepare(pool);
         let poses = pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let mut newf = f.clone();
@@ -334,6 +380,7 @@ where
     where
         Self::Item: Ord + Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 s.spawn(async move { batch.max() });
@@ -351,6 +398,7 @@ where
     where
         Self::Item: Ord + Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 s.spawn(async move { batch.min() });
@@ -370,6 +418,7 @@ where
         F: FnMut(&Self::Item) -> R + Send + Sync + Clone,
         Self::Item: Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let newf = f.clone();
@@ -389,6 +438,7 @@ where
         F: FnMut(&Self::Item, &Self::Item) -> std::cmp::Ordering + Send + Sync + Clone,
         Self::Item: Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let newf = f.clone();
@@ -409,6 +459,7 @@ where
         F: FnMut(&Self::Item) -> R + Send + Sync + Clone,
         Self::Item: Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let newf = f.clone();
@@ -428,6 +479,7 @@ where
         F: FnMut(&Self::Item, &Self::Item) -> std::cmp::Ordering + Send + Sync + Clone,
         Self::Item: Send + 'static,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 let newf = f.clone();
@@ -482,6 +534,7 @@ where
         S: std::iter::Sum<Self::Item> + Send + 'static,
         R: std::iter::Sum<S>,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 s.spawn(async move { batch.sum() });
@@ -499,6 +552,7 @@ where
         S: std::iter::Product<Self::Item> + Send + 'static,
         R: std::iter::Product<S>,
     {
+        self.prepare(pool);
         pool.scope(|s| {
             while let Some(batch) = self.next_batch() {
                 s.spawn(async move { batch.product() });
@@ -508,3 +562,65 @@ where
         .product()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BatchSize, ParallelIterator};
+    use crate::TaskPoolBuilder;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    struct ParChunks<'a> {
+        slice: &'a [usize],
+        batch_size: BatchSize,
+        resolved_batch_size: Arc<AtomicUsize>,
+    }
+
+    impl<'a> ParallelIterator<std::slice::Iter<'a, usize>> for ParChunks<'a> {
+        type Item = &'a usize;
+
+        fn next_batch(&mut self) -> Option<std::slice::Iter<'a, usize>> {
+            if self.slice.is_empty() {
+                return None;
+            }
+            let batch_size = self.resolved_batch_size.load(Ordering::Relaxed);
+            let (batch, rest) = self.slice.split_at(batch_size.min(self.slice.len()));
+            self.slice = rest;
+            Some(batch.iter())
+        }
+
+        fn prepare(&mut self, pool: &crate::TaskPool) {
+            let batch_size = self.batch_size.get(self.slice.len(), pool.thread_num());
+            self.resolved_batch_size
+                .store(batch_size, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn batch_size() {
+        assert_eq!(BatchSize::from(16).get(1000, 4), 16);
+        assert_eq!(BatchSize::Fixed(0).get(1000, 4), 1);
+        assert_eq!(BatchSize::Auto.get(1000, 4), 62);
+        assert_eq!(BatchSize::Auto.get(3, 4), 1);
+        assert_eq!(BatchSize::Auto.get(0, 0), 1);
+    }
+
+    #[test]
+    fn auto_batch_size_uses_consuming_pool() {
+        let values = (0..1000).collect::<Vec<usize>>();
+        let pool = TaskPoolBuilder::new().num_threads(2).build();
+        let resolved_batch_size = Arc::new(AtomicUsize::new(0));
+        let sum: usize = ParChunks {
+            slice: &values,
+            batch_size: BatchSize::Auto,
+            resolved_batch_size: resolved_batch_size.clone(),
+        }
+        .map(|value| value * 2)
+        .sum::<usize, usize>(&pool);
+
+        assert_eq!(sum, 999 * 1000);
+        assert_eq!(resolved_batch_size.load(Ordering::Relaxed), 125);
+    }
+}
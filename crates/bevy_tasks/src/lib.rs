@@ -21,11 +21,11 @@ mod countdown_event;
 pub use countdown_event::CountdownEvent;
 
 mod iter;
-pub use iter::ParallelIterator;
+pub use iter::{BatchSize, ParallelIterator};
 
 pub mod prelude {
     pub use crate::{
-        iter::ParallelIterator,
+        iter::{BatchSize, ParallelIterator},
         slice::{ParallelSlice, ParallelSliceMut},
         usages::{AsyncComputeTaskPool, ComputeTaskPool, IoTaskPool},
     };
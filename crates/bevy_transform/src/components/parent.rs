@@ -2,7 +2,7 @@ use bevy_ecs::{Entity, FromResources, MapEntities};
 use bevy_reflect::{Reflect, ReflectComponent, ReflectMapEntities};
 use std::ops::{Deref, DerefMut};
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Reflect)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Reflect, MapEntities)]
 #[reflect(Component, MapEntities)]
 pub struct Parent(pub Entity);
 
@@ -16,16 +16,6 @@ impl FromResources for Parent {
     }
 }
 
-impl MapEntities for Parent {
-    fn map_entities(
-        &mut self,
-        entity_map: &bevy_ecs::EntityMap,
-    ) -> Result<(), bevy_ecs::MapEntitiesError> {
-        self.0 = entity_map.get(self.0)?;
-        Ok(())
-    }
-}
-
 impl Deref for Parent {
     type Target = Entity;
 
@@ -40,20 +30,10 @@ impl DerefMut for Parent {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Reflect)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Reflect, MapEntities)]
 #[reflect(Component, MapEntities)]
 pub struct PreviousParent(pub(crate) Entity);
 
-impl MapEntities for PreviousParent {
-    fn map_entities(
-        &mut self,
-        entity_map: &bevy_ecs::EntityMap,
-    ) -> Result<(), bevy_ecs::MapEntitiesError> {
-        self.0 = entity_map.get(self.0)?;
-        Ok(())
-    }
-}
-
 // TODO: Better handle this case see `impl FromResources for Parent`
 impl FromResources for PreviousParent {
     fn from_resources(_resources: &bevy_ecs::Resources) -> Self {
@@ -1,10 +1,12 @@
 use super::GlobalTransform;
+use bevy_ecs::RequiredComponents;
 use bevy_math::{Mat3, Mat4, Quat, Vec3};
 use bevy_reflect::{Reflect, ReflectComponent};
 use std::ops::Mul;
 
-#[derive(Debug, PartialEq, Clone, Copy, Reflect)]
+#[derive(Debug, PartialEq, Clone, Copy, Reflect, RequiredComponents)]
 #[reflect(Component)]
+#[require(GlobalTransform)]
 pub struct Transform {
     pub translation: Vec3,
     pub rotation: Quat,
@@ -10,26 +10,28 @@ pub struct InsertChildren {
 }
 
 impl Command for InsertChildren {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        for child in self.children.iter() {
-            world
-                .insert(*child, (Parent(self.parent), PreviousParent(self.parent)))
-                .unwrap();
-        }
-        {
-            let mut added = false;
-            if let Ok(mut children) = world.get_mut::<Children>(self.parent) {
-                children.0.insert_from_slice(self.index, &self.children);
-                added = true;
-            }
-
-            // NOTE: ideally this is just an else statement, but currently that _incorrectly_ fails borrow-checking
-            if !added {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| {
+            for child in self.children.iter() {
                 world
-                    .insert_one(self.parent, Children(self.children))
+                    .insert(*child, (Parent(self.parent), PreviousParent(self.parent)))
                     .unwrap();
             }
-        }
+            {
+                let mut added = false;
+                if let Ok(mut children) = world.get_mut::<Children>(self.parent) {
+                    children.0.insert_from_slice(self.index, &self.children);
+                    added = true;
+                }
+
+                // NOTE: ideally this is just an else statement, but currently that _incorrectly_ fails borrow-checking
+                if !added {
+                    world
+                        .insert_one(self.parent, Children(self.children))
+                        .unwrap();
+                }
+            }
+        });
     }
 }
 
@@ -45,26 +47,28 @@ pub struct ChildBuilder<'a> {
 }
 
 impl Command for PushChildren {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        for child in self.children.iter() {
-            world
-                .insert(*child, (Parent(self.parent), PreviousParent(self.parent)))
-                .unwrap();
-        }
-        {
-            let mut added = false;
-            if let Ok(mut children) = world.get_mut::<Children>(self.parent) {
-                children.0.extend(self.children.iter().cloned());
-                added = true;
-            }
-
-            // NOTE: ideally this is just an else statement, but currently that _incorrectly_ fails borrow-checking
-            if !added {
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| {
+            for child in self.children.iter() {
                 world
-                    .insert_one(self.parent, Children(self.children))
+                    .insert(*child, (Parent(self.parent), PreviousParent(self.parent)))
                     .unwrap();
             }
-        }
+            {
+                let mut added = false;
+                if let Ok(mut children) = world.get_mut::<Children>(self.parent) {
+                    children.0.extend(self.children.iter().cloned());
+                    added = true;
+                }
+
+                // NOTE: ideally this is just an else statement, but currently that _incorrectly_ fails borrow-checking
+                if !added {
+                    world
+                        .insert_one(self.parent, Children(self.children))
+                        .unwrap();
+                }
+            }
+        });
     }
 }
 
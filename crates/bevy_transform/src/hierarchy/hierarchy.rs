@@ -33,8 +33,10 @@ fn despawn_with_children_recursive_inner(world: &mut World, entity: Entity) {
 }
 
 impl Command for DespawnRecursive {
-    fn write(self: Box<Self>, world: &mut World, _resources: &mut Resources) {
-        despawn_with_children_recursive(world, self.entity);
+    fn write(self: Box<Self>, world: &mut World, resources: &mut Resources) {
+        world.with_resources(resources, |world| {
+            despawn_with_children_recursive(world, self.entity)
+        });
     }
 }
 
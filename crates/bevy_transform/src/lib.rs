@@ -21,6 +21,7 @@ impl Plugin for TransformPlugin {
             .register_type::<PreviousParent>()
             .register_type::<Transform>()
             .register_type::<GlobalTransform>()
+            .register_required_components::<Transform>()
             // add transform systems to startup so the first update is "correct"
             .add_startup_system_to_stage(startup_stage::POST_STARTUP, parent_update_system.system())
             .add_startup_system_to_stage(
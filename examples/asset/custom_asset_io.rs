@@ -18,6 +18,15 @@ impl AssetIo for CustomAssetIo {
         self.0.load_path(path)
     }
 
+    fn save_path<'a>(
+        &'a self,
+        path: &'a Path,
+        bytes: &'a [u8],
+    ) -> BoxedFuture<'a, Result<(), AssetIoError>> {
+        println!("save_path({:?})", path);
+        self.0.save_path(path, bytes)
+    }
+
     fn read_directory(
         &self,
         path: &Path,
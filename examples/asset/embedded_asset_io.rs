@@ -0,0 +1,51 @@
+use bevy::{
+    asset::{embedded_asset_io, LayeredAssetIo},
+    prelude::*,
+};
+
+/// A plugin that bundles the assets used by this example into the binary, so it also runs without the `assets`
+/// folder. Files in the `assets` folder still take precedence, which lets users override the embedded assets.
+struct EmbeddedAssetIoPlugin;
+
+impl Plugin for EmbeddedAssetIoPlugin {
+    fn build(&self, app: &mut AppBuilder) {
+        let task_pool = app
+            .resources()
+            .get::<bevy::tasks::IoTaskPool>()
+            .expect("`IoTaskPool` resource not found.")
+            .0
+            .clone();
+
+        // layers are tried in order: first the platform default asset io, then the embedded assets
+        let asset_io = LayeredAssetIo::default()
+            .with_boxed_layer(bevy::asset::create_platform_default_asset_io(app))
+            .with_layer(embedded_asset_io!("assets", "branding/icon.png"));
+
+        app.add_resource(AssetServer::new(asset_io, task_pool));
+    }
+}
+
+fn main() {
+    App::build()
+        .add_plugins_with(DefaultPlugins, |group| {
+            // like in the `custom_asset_io` example, the asset server has to be created
+            // between the `CorePlugin` and the `AssetPlugin`
+            group.add_before::<bevy::asset::AssetPlugin, _>(EmbeddedAssetIoPlugin)
+        })
+        .add_startup_system(setup.system())
+        .run();
+}
+
+fn setup(
+    commands: &mut Commands,
+    asset_server: Res<AssetServer>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let texture_handle = asset_server.load("branding/icon.png");
+    commands
+        .spawn(Camera2dBundle::default())
+        .spawn(SpriteBundle {
+            material: materials.add(texture_handle.into()),
+            ..Default::default()
+        });
+}
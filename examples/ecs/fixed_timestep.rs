@@ -22,6 +22,12 @@ fn main() {
                 )
                 .with_system(fixed_update.system()),
         )
+        // system sets can also have run criteria, which avoids adding a stage per timestep
+        .add_system_set(
+            SystemSet::new()
+                .with_run_criteria(FixedTimestep::step(0.5))
+                .with_system(half_second_update.system()),
+        )
         .run();
 }
 
@@ -44,3 +50,11 @@ fn fixed_update(mut last_time: Local<f64>, time: Res<Time>, fixed_timesteps: Res
 
     *last_time = time.seconds_since_startup();
 }
+
+fn half_second_update(mut last_time: Local<f64>, time: Res<Time>) {
+    println!(
+        "half_second_update: {}",
+        time.seconds_since_startup() - *last_time
+    );
+    *last_time = time.seconds_since_startup();
+}
@@ -9,6 +9,7 @@ fn main() {
     App::build()
         .add_resource(AssetServerSettings {
             asset_folder: "/".to_string(),
+            ..Default::default()
         })
         .add_plugins(DefaultPlugins)
         .add_asset::<RustSourceCode>()